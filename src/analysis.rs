@@ -0,0 +1,705 @@
+//! This module contains tools for analysing the game as a whole, such as exporting every reachable
+//! position labelled with its optimal moves and game-theoretic value, or finding out how far away
+//! a forced result is. Also generates and grades endgame puzzles for practice.
+//!
+//! The board and the minimax search hold no interior mutability, so all of it is `Send + Sync`
+//! and positions can be analysed on as many threads as needed.
+
+pub mod replay;
+
+pub use crate::player::minimax::{
+    calculate_best_move_with_stats, calculate_best_move_within, SearchStats,
+};
+
+use crate::board::{Board, BoardMove, Cell};
+use crate::player::minimax;
+use rand::Rng;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Result which the player to move can't avoid (`Loss`) or can force (`Win`) with perfect play.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    Win,
+    Loss,
+}
+
+/// Explains why a move was chosen, for teaching purposes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MoveReason {
+    /// The move completes a line.
+    Winning,
+    /// The move takes the cell the opponent needed to complete a line.
+    Blocking,
+    /// The move creates two threats at once, so the opponent can only block one of them.
+    Fork,
+    /// No other move was possible.
+    Forced,
+}
+
+impl fmt::Display for MoveReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            MoveReason::Winning => "winning move",
+            MoveReason::Blocking => "blocking your threat",
+            MoveReason::Fork => "creating a fork",
+            MoveReason::Forced => "forced - only move",
+        };
+        write!(f, "{text}")
+    }
+}
+
+/// How a move compares to the best moves available.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MoveQuality {
+    /// One of the best moves.
+    Optimal,
+    /// Keeps the game-theoretic result, but not in the best way (e.g. a slower win).
+    Inaccuracy,
+    /// Throws away a win or a draw.
+    Blunder,
+}
+
+/// Verdict on a move, together with the value of the position for the player who made it before
+/// and after the move: 1 for a forced win, 0 for a draw and -1 for a forced loss.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MoveJudgement {
+    pub quality: MoveQuality,
+    pub value_before: i32,
+    pub value_after: i32,
+}
+
+impl MoveJudgement {
+    /// Returns how much the move changed the value of the position, never more than 0.
+    pub fn value_change(&self) -> i32 {
+        self.value_after - self.value_before
+    }
+}
+
+impl fmt::Display for MoveJudgement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let describe = |value: i32| match value.signum() {
+            1 => "win",
+            -1 => "loss",
+            _ => "draw",
+        };
+
+        match self.quality {
+            MoveQuality::Optimal => write!(f, "optimal move"),
+            MoveQuality::Inaccuracy => write!(
+                f,
+                "inaccuracy - still a {}, but there was a better move",
+                describe(self.value_after)
+            ),
+            MoveQuality::Blunder => write!(
+                f,
+                "blunder - turned a {} into a {}",
+                describe(self.value_before),
+                describe(self.value_after)
+            ),
+        }
+    }
+}
+
+/// Move which wasn't one of the best, found when reviewing a finished game.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mistake {
+    /// Number of the move in the game, counting from 1
+    pub ply: usize,
+    /// Mark of the player who made the move
+    pub symbol: Cell,
+    pub played: BoardMove,
+    pub best_moves: Vec<BoardMove>,
+    pub judgement: MoveJudgement,
+}
+
+impl fmt::Display for Mistake {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let best_moves: Vec<_> = self
+            .best_moves
+            .iter()
+            .map(|board_move| (board_move.index() + 1).to_string())
+            .collect();
+
+        write!(
+            f,
+            "move {}, {} at {}: {} (better: {})",
+            self.ply,
+            self.symbol,
+            self.played.index() + 1,
+            self.judgement,
+            best_moves.join(", ")
+        )
+    }
+}
+
+/// Replays the moves of a game from its starting board and lists every move which wasn't one of
+/// the best, with the better alternatives. An empty list means a clean game.
+///
+/// # Examples
+///
+/// ```
+/// use tic_tac_terminal::analysis;
+/// use tic_tac_terminal::board::{Board, BoardMove};
+///
+/// // 'X' answers the center with an edge, which loses
+/// let moves = [5, 2].map(|cell| BoardMove::try_new(cell).unwrap());
+/// let mistakes = analysis::review_game(&Board::new(), &moves);
+///
+/// assert_eq!(mistakes.len(), 1);
+/// assert_eq!(mistakes[0].ply, 2);
+/// ```
+pub fn review_game(starting_board: &Board, moves: &[BoardMove]) -> Vec<Mistake> {
+    let mut board = *starting_board;
+    let mut mistakes = vec![];
+
+    for (ply, board_move) in (1..).zip(moves) {
+        let (is_best, best_moves) = grade_move(&board, board_move);
+        if !is_best {
+            mistakes.push(Mistake {
+                ply,
+                symbol: board.current_player_symbol(),
+                played: *board_move,
+                best_moves,
+                judgement: judge_move(&board, board_move),
+            });
+        }
+
+        board[board_move.index()] = board.current_player_symbol();
+    }
+
+    mistakes
+}
+
+/// Judges a move of the player to move by comparing it to the best moves in the position, given
+/// the board from before the move.
+///
+/// # Examples
+///
+/// ```
+/// use tic_tac_terminal::analysis::{self, MoveQuality};
+/// use tic_tac_terminal::board::{Board, BoardMove};
+///
+/// let board: Board = "OO.XX....".parse().unwrap();
+///
+/// let judgement = analysis::judge_move(&board, &BoardMove::try_new(3).unwrap());
+/// assert_eq!(judgement.quality, MoveQuality::Optimal);
+///
+/// let judgement = analysis::judge_move(&board, &BoardMove::try_new(7).unwrap());
+/// assert_eq!(judgement.quality, MoveQuality::Blunder);
+/// ```
+pub fn judge_move(board: &Board, board_move: &BoardMove) -> MoveJudgement {
+    let (is_best, _best_moves) = grade_move(board, board_move);
+
+    let mut after = *board;
+    after[board_move.index()] = board.current_player_symbol();
+
+    let value_before = position_value(board);
+    let value_after = -position_value(&after); // The opponent is to move after the move
+    let quality = if is_best {
+        MoveQuality::Optimal
+    } else if value_after == value_before {
+        MoveQuality::Inaccuracy
+    } else {
+        MoveQuality::Blunder
+    };
+
+    MoveJudgement {
+        quality,
+        value_before,
+        value_after,
+    }
+}
+
+/// Returns the game-theoretic value of the position for the player to move: 1 for a forced win,
+/// 0 for a draw and -1 for a forced loss.
+fn position_value(board: &Board) -> i32 {
+    if board.get_winning_line().is_some() {
+        -1 // The previous player has already won
+    } else if board.is_full() {
+        0
+    } else {
+        minimax::evaluate(board).1.signum()
+    }
+}
+
+/// Returns moves which leave `symbol` with at least two different cells to win on.
+///
+/// # Examples
+///
+/// ```
+/// use tic_tac_terminal::analysis;
+/// use tic_tac_terminal::board::{Board, BoardMove, Cell};
+///
+/// let board: Board = "OX..O...X".parse().unwrap();
+///
+/// assert_eq!(
+///     analysis::find_forks(&board, Cell::O),
+///     vec![BoardMove::try_new(4).unwrap(), BoardMove::try_new(7).unwrap()]
+/// );
+/// ```
+pub fn find_forks(board: &Board, symbol: Cell) -> Vec<BoardMove> {
+    board
+        .get_possible_moves()
+        .into_iter()
+        .filter(|board_move| {
+            let mut after = *board;
+            after[board_move.index()] = symbol;
+
+            let threats = after
+                .get_possible_moves()
+                .iter()
+                .filter(|threat| after.is_winning_move(symbol, threat))
+                .count();
+            threats >= 2
+        })
+        .collect()
+}
+
+/// Classifies a move of the player to move, given the board from before the move. Returns `None`
+/// for moves which are neither of the described kinds.
+///
+/// # Examples
+///
+/// ```
+/// use tic_tac_terminal::analysis::{self, MoveReason};
+/// use tic_tac_terminal::board::{Board, BoardMove};
+///
+/// let board: Board = "OO.XX....".parse().unwrap();
+///
+/// assert_eq!(
+///     analysis::explain_move(&board, &BoardMove::try_new(3).unwrap()),
+///     Some(MoveReason::Winning)
+/// );
+/// ```
+pub fn explain_move(board: &Board, board_move: &BoardMove) -> Option<MoveReason> {
+    let symbol = board.current_player_symbol();
+
+    if board.is_winning_move(symbol, board_move) {
+        Some(MoveReason::Winning)
+    } else if board.is_winning_move(symbol.opposite(), board_move) {
+        Some(MoveReason::Blocking)
+    } else if find_forks(board, symbol).contains(board_move) {
+        Some(MoveReason::Fork)
+    } else if board.get_possible_moves().len() == 1 {
+        Some(MoveReason::Forced)
+    } else {
+        None
+    }
+}
+
+/// Returns the forced outcome of the position for the player to move, together with the number of
+/// moves (counting both players) until the game ends with perfect play from both sides. The winner
+/// finishes as quickly as possible, while the loser holds out as long as possible. Returns `None`
+/// if the position is a draw or the game is already over.
+///
+/// # Examples
+///
+/// ```
+/// use tic_tac_terminal::analysis::{self, Outcome};
+/// use tic_tac_terminal::board::Board;
+///
+/// let board = "OO.XX....".parse().unwrap();
+///
+/// assert_eq!(analysis::forced_outcome_distance(&board), Some((Outcome::Win, 1)));
+/// assert_eq!(analysis::forced_outcome_distance(&Board::new()), None);
+/// ```
+pub fn forced_outcome_distance(board: &Board) -> Option<(Outcome, u32)> {
+    if board.is_terminal() {
+        return None;
+    }
+
+    // Minimax scores are `100 - depth` for a win and `depth - 100` for a loss, where `depth` is
+    // the number of moves until the end of the game
+    let (_, score) = minimax::evaluate(board);
+    match score.signum() {
+        1 => Some((Outcome::Win, (100 - score) as u32)),
+        -1 => Some((Outcome::Loss, (100 + score) as u32)),
+        _ => None,
+    }
+}
+
+/// Generates a random legal position with `empty_cells` empty cells left, in which the game is
+/// still undecided (nobody has won and a line can still be completed).
+///
+/// # Panics
+///
+/// Panics if `empty_cells` is 0 or greater than 9, as no such undecided position exists.
+pub fn random_endgame(rng: &mut impl Rng, empty_cells: usize) -> Board {
+    assert!(
+        (1..=9).contains(&empty_cells),
+        "Undecided position needs 1 to 9 empty cells"
+    );
+
+    'attempt: loop {
+        let mut board = Board::new();
+
+        for _ in empty_cells..9 {
+            let moves = board.get_possible_moves();
+            let board_move = moves[rng.gen_range(0..moves.len())];
+            board[board_move.index()] = board.current_player_symbol();
+
+            if board.get_winning_line().is_some() {
+                continue 'attempt;
+            }
+        }
+
+        if !board.is_drawn() {
+            debug_assert!(board.is_legal_position());
+            return board;
+        }
+    }
+}
+
+/// Checks whether the move is one of the best moves in the position, i.e. it doesn't worsen the
+/// game-theoretic value for the player to move. Returns the verdict along with all best moves.
+///
+/// # Examples
+///
+/// ```
+/// use tic_tac_terminal::analysis;
+/// use tic_tac_terminal::board::{Board, BoardMove};
+///
+/// let board: Board = "OO.XX....".parse().unwrap();
+/// let (is_best, best_moves) = analysis::grade_move(&board, &BoardMove::try_new(3).unwrap());
+///
+/// assert!(is_best);
+/// assert_eq!(best_moves, vec![BoardMove::try_new(3).unwrap()]);
+/// ```
+pub fn grade_move(board: &Board, board_move: &BoardMove) -> (bool, Vec<BoardMove>) {
+    let (best_moves, _score) = minimax::evaluate(board);
+    (best_moves.contains(board_move), best_moves)
+}
+
+/// Exports every reachable, non-terminal position (one per symmetry class) to a CSV file. Each
+/// row holds the board (`O`, `X` and `.` for empty cells, row by row), the optimal moves (cell
+/// numbers 1-9 separated with spaces) and the value of the position for the player to move (1 for
+/// a win, 0 for a draw, -1 for a loss). Returns the number of exported positions.
+///
+/// # Examples
+///
+/// ```no_run
+/// use tic_tac_terminal::analysis;
+///
+/// let count = analysis::export_positions("positions.csv").unwrap();
+/// println!("Exported {count} positions");
+/// ```
+pub fn export_positions(path: impl AsRef<Path>) -> io::Result<usize> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    let count = write_positions(&mut writer, 9)?;
+    writer.flush()?;
+
+    Ok(count)
+}
+
+fn write_positions(writer: &mut impl Write, max_depth: usize) -> io::Result<usize> {
+    let positions = canonical_positions(max_depth);
+
+    writeln!(writer, "board,best_moves,value")?;
+    for board in &positions {
+        let (best_moves, score) = minimax::evaluate(board);
+        let best_moves: Vec<_> = best_moves
+            .iter()
+            .map(|board_move| (board_move.index() + 1).to_string())
+            .collect();
+
+        writeln!(
+            writer,
+            "{},{},{}",
+            board,
+            best_moves.join(" "),
+            score.signum()
+        )?;
+    }
+
+    Ok(positions.len())
+}
+
+/// Returns canonical forms of all non-terminal positions reachable within `max_depth` moves from
+/// the empty board, in order of increasing depth.
+fn canonical_positions(max_depth: usize) -> Vec<Board> {
+    let mut positions = vec![];
+    let mut seen = HashSet::new();
+    let mut current_depth = vec![Board::new()];
+
+    for depth in 0..=max_depth {
+        let mut next_depth = vec![];
+
+        for board in current_depth {
+            if board.is_terminal() {
+                continue;
+            }
+
+            let canonical = board.canonical();
+            if !seen.insert(canonical.to_string()) {
+                continue;
+            }
+            positions.push(canonical);
+
+            if depth < max_depth {
+                for board_move in canonical.get_possible_moves() {
+                    let mut next_board = canonical;
+                    next_board[board_move.index()] = canonical.current_player_symbol();
+                    next_depth.push(next_board);
+                }
+            }
+        }
+
+        current_depth = next_depth;
+    }
+
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn analysis_across_threads() {
+        assert_send_sync::<Board>();
+        assert_send_sync::<BoardMove>();
+        assert_send_sync::<Outcome>();
+        assert_send_sync::<replay::Replay>();
+
+        let positions: Vec<Board> = ["OO.XX....", "OX..O...X", "OX.OO...X", "........."]
+            .iter()
+            .map(|position| position.parse().unwrap())
+            .collect();
+        let expected: Vec<_> = positions.iter().map(forced_outcome_distance).collect();
+
+        let results: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = positions
+                .iter()
+                .map(|board| scope.spawn(move || forced_outcome_distance(board)))
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        assert_eq!(
+            results, expected,
+            "Evaluating positions concurrently should match evaluating them one by one"
+        );
+    }
+
+    #[test]
+    fn game_review() {
+        let moves = |cells: &[usize]| -> Vec<_> {
+            cells
+                .iter()
+                .map(|&cell| BoardMove::try_new(cell).unwrap())
+                .collect()
+        };
+
+        // 'X' answers the center with an edge and 'O' misses the win that follows
+        let mistakes = review_game(&Board::new(), &moves(&[5, 2, 1, 9, 3, 7]));
+        assert_eq!(
+            mistakes
+                .iter()
+                .map(|mistake| (mistake.ply, mistake.symbol, mistake.judgement.quality))
+                .collect::<Vec<_>>(),
+            vec![
+                (2, Cell::X, MoveQuality::Blunder),
+                (5, Cell::O, MoveQuality::Blunder)
+            ]
+        );
+        assert_eq!(
+            mistakes[0].best_moves,
+            moves(&[1, 3, 7, 9]),
+            "Only corners hold the draw"
+        );
+        assert_eq!(
+            mistakes[0].to_string(),
+            "move 2, X at 2: blunder - turned a draw into a loss (better: 1, 3, 7, 9)"
+        );
+
+        assert!(
+            review_game(&Board::new(), &moves(&[5, 1, 9, 3, 2, 8, 7, 4, 6])).is_empty(),
+            "Perfectly played draw is a clean game"
+        );
+        assert!(review_game(&Board::new(), &[]).is_empty());
+    }
+
+    #[test]
+    fn move_judgements() {
+        // 'O' wins right away at 3
+        let board: Board = "OO.XX....".parse().unwrap();
+
+        let win = judge_move(&board, &BoardMove::try_new(3).unwrap());
+        assert_eq!(win.quality, MoveQuality::Optimal);
+        assert_eq!(win.value_change(), 0);
+
+        let block = judge_move(&board, &BoardMove::try_new(6).unwrap());
+        assert_eq!(
+            block,
+            MoveJudgement {
+                quality: MoveQuality::Blunder,
+                value_before: 1,
+                value_after: 0,
+            },
+            "Blocking instead of winning only draws"
+        );
+        assert_eq!(block.to_string(), "blunder - turned a win into a draw");
+
+        let blunder = judge_move(&board, &BoardMove::try_new(7).unwrap());
+        assert_eq!(blunder.quality, MoveQuality::Blunder);
+        assert_eq!(blunder.value_change(), -2, "Ignoring both rows loses");
+
+        // 'X' wins either way, but the fork at 3 is quicker than cell 2
+        let board: Board = "O...X...X".parse().unwrap();
+        let slower = judge_move(&board, &BoardMove::try_new(2).unwrap());
+        assert_eq!(slower.quality, MoveQuality::Inaccuracy);
+        assert_eq!(slower.value_change(), 0);
+    }
+
+    #[test]
+    fn move_reasons() {
+        let board: Board = "OO.XX....".parse().unwrap();
+        assert_eq!(
+            explain_move(&board, &BoardMove::try_new(3).unwrap()),
+            Some(MoveReason::Winning),
+            "'O' completes the top row"
+        );
+
+        let board: Board = "XX.O...O.".parse().unwrap();
+        assert_eq!(
+            explain_move(&board, &BoardMove::try_new(3).unwrap()),
+            Some(MoveReason::Blocking),
+            "'O' stops 'X' from completing the top row"
+        );
+
+        let board: Board = "OX..O...X".parse().unwrap();
+        assert_eq!(
+            explain_move(&board, &BoardMove::try_new(4).unwrap()),
+            Some(MoveReason::Fork),
+            "'O' threatens both the left column and the middle row"
+        );
+
+        let board: Board = "OOXXXOOX.".parse().unwrap();
+        assert_eq!(
+            explain_move(&board, &BoardMove::try_new(9).unwrap()),
+            Some(MoveReason::Forced),
+            "Only one cell is left"
+        );
+
+        let board = Board::new();
+        assert_eq!(explain_move(&board, &BoardMove::try_new(5).unwrap()), None);
+    }
+
+    #[test]
+    fn canonical_position_counts() {
+        assert_eq!(canonical_positions(0).len(), 1, "Only the empty board");
+        assert_eq!(
+            canonical_positions(1).len(),
+            1 + 3,
+            "First move can only be a corner, an edge or the center"
+        );
+        assert_eq!(
+            canonical_positions(2).len(),
+            1 + 3 + 12,
+            "There are 12 distinct positions after two moves"
+        );
+        assert_eq!(
+            canonical_positions(3).len(),
+            1 + 3 + 12 + 38,
+            "There are 38 distinct positions after three moves"
+        );
+    }
+
+    #[test]
+    fn exported_rows() {
+        let mut output = vec![];
+
+        let count = write_positions(&mut output, 1).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+
+        assert_eq!(count, 4);
+        assert_eq!(lines.len(), count + 1, "Header and one row per position");
+        assert_eq!(lines[0], "board,best_moves,value");
+        assert_eq!(
+            lines[1], ".........,1 2 3 4 5 6 7 8 9,0",
+            "Every first move leads to a draw"
+        );
+    }
+
+    #[test]
+    fn forced_outcome_distances() {
+        let win_in_1: Board = "OO.XX....".parse().unwrap();
+        assert_eq!(
+            forced_outcome_distance(&win_in_1),
+            Some((Outcome::Win, 1)),
+            "'O' completes the top row right away"
+        );
+
+        // 'O' plays cell 4, threatening both the left column and the middle row
+        let win_in_3: Board = "OX..O...X".parse().unwrap();
+        assert_eq!(
+            forced_outcome_distance(&win_in_3),
+            Some((Outcome::Win, 3)),
+            "'O' should force a win with a fork"
+        );
+
+        let loss_in_2: Board = "OX.OO...X".parse().unwrap();
+        assert_eq!(
+            forced_outcome_distance(&loss_in_2),
+            Some((Outcome::Loss, 2)),
+            "'X' can block only one of two threats"
+        );
+
+        assert_eq!(
+            forced_outcome_distance(&Board::new()),
+            None,
+            "Empty board is a draw"
+        );
+        assert_eq!(
+            forced_outcome_distance(&"OOOXX....".parse().unwrap()),
+            None,
+            "Finished game has no forced outcome"
+        );
+    }
+
+    #[test]
+    fn random_endgames_are_undecided() {
+        let mut rng = StdRng::seed_from_u64(891);
+
+        for empty_cells in 1..=5 {
+            for _ in 0..20 {
+                let board = random_endgame(&mut rng, empty_cells);
+
+                assert!(board.is_legal_position(), "{board} should be legal");
+                assert_eq!(board.get_possible_moves().len(), empty_cells);
+                assert!(
+                    board.get_winning_line().is_none(),
+                    "{board} shouldn't be won yet"
+                );
+                assert!(!board.is_drawn(), "{board} shouldn't be drawn yet");
+            }
+        }
+    }
+
+    #[test]
+    fn grading_moves() {
+        // 'O' has to block the middle column, anything else loses
+        let board: Board = "OX..X...O".parse().unwrap();
+        let block = BoardMove::try_new(8).unwrap();
+        let blunder = BoardMove::try_new(3).unwrap();
+
+        let (is_best, best_moves) = grade_move(&board, &block);
+        assert!(is_best, "Blocking the threat should be graded as best");
+        assert_eq!(best_moves, vec![block]);
+
+        let (is_best, _) = grade_move(&board, &blunder);
+        assert!(
+            !is_best,
+            "Ignoring the threat should be graded as a mistake"
+        );
+    }
+}
@@ -0,0 +1,326 @@
+//! This module contains a replay of a recorded game, which can be stepped through move by move and
+//! branched off at any point to explore what would have happened after a different move. Games are
+//! recorded as transcripts: cell numbers (1-9) of the moves in order, separated by spaces or
+//! commas, e.g. `5 1 9 3`.
+
+use crate::board::{Board, BoardMove, MoveError};
+use std::fmt;
+
+/// Reason why a transcript can't be replayed, together with the position of the offending move in
+/// the transcript, counting from 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TranscriptError {
+    NotAMove(usize),
+    OutOfRange(usize),
+    CellTaken(usize),
+    GameOver(usize), // Move played after the game had already ended
+}
+
+impl fmt::Display for TranscriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotAMove(position) => write!(f, "Move {position} is not a number"),
+            Self::OutOfRange(position) => write!(f, "Move {position} is not between 1 and 9"),
+            Self::CellTaken(position) => write!(f, "Move {position} is on a taken cell"),
+            Self::GameOver(position) => {
+                write!(f, "Move {position} is played after the game ended")
+            }
+        }
+    }
+}
+
+/// Checks that the transcript can be played out from the empty board, without replaying it.
+/// Reports the first move that can't be played.
+///
+/// # Examples
+///
+/// ```
+/// use tic_tac_terminal::analysis::replay::{self, TranscriptError};
+///
+/// assert_eq!(replay::validate_transcript("5 1 9 3"), Ok(()));
+/// assert_eq!(
+///     replay::validate_transcript("5, 1, 5"),
+///     Err(TranscriptError::CellTaken(3))
+/// );
+/// ```
+pub fn validate_transcript(transcript: &str) -> Result<(), TranscriptError> {
+    parse_transcript(transcript).map(|_moves| ())
+}
+
+/// Parses the moves of a transcript, checking that each of them is legal on the board left by the
+/// previous ones.
+fn parse_transcript(transcript: &str) -> Result<Vec<BoardMove>, TranscriptError> {
+    let mut moves = vec![];
+    let mut unparsable = None;
+
+    let tokens = transcript
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|token| !token.is_empty());
+    for (position, token) in (1..).zip(tokens) {
+        let board_move = match token.parse() {
+            Ok(number) => {
+                BoardMove::try_new(number).map_err(|_| TranscriptError::OutOfRange(position))
+            }
+            Err(_) => Err(TranscriptError::NotAMove(position)),
+        };
+
+        match board_move {
+            Ok(board_move) => moves.push(board_move),
+            Err(error) => {
+                unparsable = Some(error);
+                break;
+            }
+        }
+    }
+
+    // Moves before an unparsable one are still checked, since an illegal one would come first
+    Board::new()
+        .play_moves(&moves)
+        .map_err(|(index, error)| match error {
+            MoveError::GameOver => TranscriptError::GameOver(index + 1),
+            MoveError::CellTaken | MoveError::NotAMark => TranscriptError::CellTaken(index + 1),
+        })?;
+
+    unparsable.map_or(Ok(moves), Err)
+}
+
+/// Recorded game together with a position in it (the number of moves played so far, or ply).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    starting_board: Board,
+    moves: Vec<BoardMove>,
+    ply: usize,
+}
+
+impl Replay {
+    /// Creates a replay of the given moves played from `starting_board`, positioned at the start.
+    /// Fails if any move is illegal (cell taken or the game already won).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::analysis::replay::Replay;
+    /// use tic_tac_terminal::board::{Board, BoardMove};
+    ///
+    /// let moves = [5, 1, 9].map(|cell| BoardMove::try_new(cell).unwrap());
+    /// let replay = Replay::new(Board::new(), moves.to_vec()).unwrap();
+    ///
+    /// assert_eq!(replay.ply(), 0);
+    /// assert_eq!(replay.board(), Board::new());
+    /// ```
+    #[allow(clippy::result_unit_err)]
+    pub fn new(starting_board: Board, moves: Vec<BoardMove>) -> Result<Self, ()> {
+        starting_board.play_moves(&moves).map_err(|_| ())?;
+
+        Ok(Self {
+            starting_board,
+            moves,
+            ply: 0,
+        })
+    }
+
+    /// Returns all recorded moves, including those after the current position.
+    pub fn moves(&self) -> &[BoardMove] {
+        &self.moves
+    }
+
+    /// Returns the number of moves played up to the current position.
+    pub fn ply(&self) -> usize {
+        self.ply
+    }
+
+    /// Returns the board at the current position.
+    pub fn board(&self) -> Board {
+        let mut board = self.starting_board;
+
+        for board_move in &self.moves[..self.ply] {
+            board[board_move.index()] = board.current_player_symbol();
+        }
+
+        board
+    }
+
+    /// Moves one move forward. Returns `false` at the end of the recording.
+    pub fn step_forward(&mut self) -> bool {
+        self.go_to(self.ply + 1).is_ok()
+    }
+
+    /// Moves one move back. Returns `false` at the start of the recording.
+    pub fn step_back(&mut self) -> bool {
+        self.ply
+            .checked_sub(1)
+            .is_some_and(|ply| self.go_to(ply).is_ok())
+    }
+
+    /// Jumps to the position after `ply` moves. Fails if the recording is shorter than that.
+    #[allow(clippy::result_unit_err)]
+    pub fn go_to(&mut self, ply: usize) -> Result<(), ()> {
+        if ply > self.moves.len() {
+            return Err(());
+        }
+
+        self.ply = ply;
+        Ok(())
+    }
+
+    /// Plays a move at the current position. If it differs from the recorded one, the recording
+    /// branches off: every later move is discarded and the new move takes its place. Fails if the
+    /// move is illegal in the current position.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::analysis::replay::Replay;
+    /// use tic_tac_terminal::board::{Board, BoardMove};
+    ///
+    /// let moves = [5, 1, 9].map(|cell| BoardMove::try_new(cell).unwrap());
+    /// let mut replay = Replay::new(Board::new(), moves.to_vec()).unwrap();
+    ///
+    /// replay.go_to(1).unwrap();
+    /// replay.play(BoardMove::try_new(3).unwrap()).unwrap(); // What if 'X' took a different corner?
+    ///
+    /// assert_eq!(replay.board().to_string(), "..X.O....");
+    /// assert_eq!(replay.moves().len(), 2);
+    /// ```
+    #[allow(clippy::result_unit_err)]
+    pub fn play(&mut self, board_move: BoardMove) -> Result<(), ()> {
+        self.board().play_moves(&[board_move]).map_err(|_| ())?;
+
+        if self.moves.get(self.ply) != Some(&board_move) {
+            self.moves.truncate(self.ply);
+            self.moves.push(board_move);
+        }
+        self.ply += 1;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn moves(cells: &[usize]) -> Vec<BoardMove> {
+        cells
+            .iter()
+            .map(|&cell| BoardMove::try_new(cell).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn stepping_through() {
+        let mut replay = Replay::new(Board::new(), moves(&[5, 1, 9, 3])).unwrap();
+
+        assert!(!replay.step_back(), "Can't step back from the start");
+        assert!(replay.step_forward());
+        assert!(replay.step_forward());
+        assert_eq!(replay.board().to_string(), "X...O....");
+
+        replay.go_to(4).unwrap();
+        assert!(!replay.step_forward(), "Can't step past the end");
+        assert!(replay.step_back());
+        assert_eq!(replay.ply(), 3);
+        assert_eq!(replay.go_to(5), Err(()), "Recording has only 4 moves");
+    }
+
+    #[test]
+    fn branching_off() {
+        let mut replay = Replay::new(Board::new(), moves(&[5, 1, 9, 3, 2])).unwrap();
+
+        replay.go_to(3).unwrap();
+        replay.play(BoardMove::try_new(7).unwrap()).unwrap();
+
+        assert_eq!(
+            replay.moves(),
+            moves(&[5, 1, 9, 7]),
+            "Moves after the fork should be replaced by the new move"
+        );
+        assert_eq!(replay.ply(), 4);
+        assert_eq!(replay.board().to_string(), "X...O.X.O");
+
+        replay.play(BoardMove::try_new(3).unwrap()).unwrap();
+        assert_eq!(replay.moves().len(), 5, "New line can be played on");
+
+        replay.go_to(1).unwrap();
+        replay.play(BoardMove::try_new(1).unwrap()).unwrap();
+        assert_eq!(
+            replay.moves().len(),
+            5,
+            "Playing the recorded move should keep the rest of the recording"
+        );
+    }
+
+    #[test]
+    fn valid_transcripts() {
+        assert_eq!(validate_transcript("5 1 9 3 2"), Ok(()));
+        assert_eq!(
+            validate_transcript(" 5,1,\n9 ,3 "),
+            Ok(()),
+            "Spaces, commas and line breaks all separate moves"
+        );
+        assert_eq!(validate_transcript(""), Ok(()), "Nothing played yet");
+        assert_eq!(
+            parse_transcript("1 4 2 5 3").unwrap(),
+            moves(&[1, 4, 2, 5, 3]),
+            "Game may end on the last move"
+        );
+    }
+
+    #[test]
+    fn invalid_transcripts() {
+        assert_eq!(
+            validate_transcript("5 1 9 1"),
+            Err(TranscriptError::CellTaken(4)),
+            "Cell 1 is repeated"
+        );
+        assert_eq!(
+            validate_transcript("5 10 9"),
+            Err(TranscriptError::OutOfRange(2))
+        );
+        assert_eq!(
+            validate_transcript("5 0"),
+            Err(TranscriptError::OutOfRange(2))
+        );
+        assert_eq!(
+            validate_transcript("5 one"),
+            Err(TranscriptError::NotAMove(2))
+        );
+        assert_eq!(
+            validate_transcript("5 5 one"),
+            Err(TranscriptError::CellTaken(2)),
+            "Illegal move comes before the unparsable one"
+        );
+        assert_eq!(
+            validate_transcript("1 4 2 5 3 6"),
+            Err(TranscriptError::GameOver(6)),
+            "'O' completed the top row with the 5th move"
+        );
+        assert_eq!(
+            TranscriptError::CellTaken(4).to_string(),
+            "Move 4 is on a taken cell"
+        );
+    }
+
+    #[test]
+    fn illegal_moves() {
+        assert_eq!(
+            Replay::new(Board::new(), moves(&[5, 5])),
+            Err(()),
+            "Cell 5 is taken"
+        );
+        assert_eq!(
+            Replay::new(Board::new(), moves(&[1, 4, 2, 5, 3, 6])),
+            Err(()),
+            "Game is over after 'O' completes the top row"
+        );
+
+        let mut replay = Replay::new(Board::new(), moves(&[5, 1])).unwrap();
+        replay.go_to(2).unwrap();
+        assert_eq!(replay.play(BoardMove::try_new(1).unwrap()), Err(()));
+        assert_eq!(
+            replay.moves().len(),
+            2,
+            "Illegal move shouldn't change the recording"
+        );
+    }
+}
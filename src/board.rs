@@ -1,10 +1,14 @@
 //! This module contains structs which represent the board and specific cells, as well as provides
 //! some helper methods to manipulate them.
 
+pub mod lines;
+
 use crate::game::WinningLineIndex;
 use std::{
+    collections::HashSet,
     fmt,
-    ops::{Index, IndexMut},
+    ops::{Deref, Index, IndexMut},
+    str::FromStr,
 };
 
 type Cells = [Cell; 9];
@@ -19,11 +23,39 @@ pub enum Cell {
     Empty(char),
     O,
     X,
+    Blocked, // Cell which neither player can use (for variant puzzles)
+}
+
+/// Square board of `N` x `N` cells, where a full row, column or diagonal wins, or with a shorter
+/// win length, any run of that many marks in a row. Classic 3x3 board is the default. On a
+/// toroidal board diagonals also wrap around the edges.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Board<const N: usize = 3> {
+    cells: [[Cell; N]; N],
+    toroidal: bool,
+    win_length: usize,
+}
+
+/// Reason why a mark can't be placed on the board.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MoveError {
+    CellTaken,
+    NotAMark, // Only 'O' and 'X' can be placed
+    GameOver, // Someone has already won
+}
+
+/// How a finished game ended.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TerminalOutcome {
+    Win(Cell), // Mark which completed a line
+    Draw,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub struct Board {
-    cells: Cells,
+pub enum LineOrientation {
+    Row,
+    Column,
+    Diagonal,
 }
 
 pub struct BoardIterator<'a> {
@@ -41,13 +73,325 @@ pub const WINNING_LINES: [[usize; 3]; 8] = [
     [2, 4, 6], // secondary diagonal
 ];
 
+/// Maximum number of winning lines on a board (a toroidal one has the most), which limits the
+/// board size to 9x9.
+const MAX_LINES: usize = 36;
+const MAX_CELLS: usize = 81;
+/// Every cell lies on one row, one column and at most one diagonal in each direction.
+const MAX_LINES_THROUGH_CELL: usize = 4;
+
+/// Winning lines of an `N` x `N` board, in the same order as `WINNING_LINES`, followed by the
+/// diagonals which only exist on a toroidal board. Only the first `standard_count` entries are
+/// used on a regular board and the first `count` on a toroidal one.
+///
+/// Lines passing through each cell are listed in `through`, in ascending order, so that the
+/// lines of a regular board (the first `through_standard_count` of them) come before the wrapped
+/// diagonals (up to `through_count`).
+struct LineTable<const N: usize> {
+    lines: [[usize; N]; MAX_LINES],
+    standard_count: usize,
+    count: usize,
+    through: [[WinningLineIndex; MAX_LINES_THROUGH_CELL]; MAX_CELLS],
+    through_standard_count: [usize; MAX_CELLS],
+    through_count: [usize; MAX_CELLS],
+}
+
+/// Computes the winning lines of an `N` x `N` board at compile time: columns, rows, the main
+/// diagonal and the secondary diagonal, then the diagonals wrapping around the edges (first
+/// parallel to the main diagonal, then to the secondary one, each by the column they start in).
+const fn generate_lines<const N: usize>() -> LineTable<N> {
+    assert!(4 * N <= MAX_LINES, "Board is too large");
+
+    let mut lines = [[0; N]; MAX_LINES];
+    let mut i = 0;
+    while i < N {
+        let mut j = 0;
+        while j < N {
+            lines[i][j] = j * N + i; // Column i
+            lines[N + i][j] = i * N + j; // Row i
+            j += 1;
+        }
+        lines[2 * N][i] = i * N + i;
+        lines[2 * N + 1][i] = i * N + (N - 1 - i);
+        i += 1;
+    }
+
+    let standard_count = 2 * N + 2;
+    // Wrapped diagonals of smaller boards repeat the standard ones
+    let count = if N < 3 { standard_count } else { 4 * N };
+
+    let mut start = 1;
+    while start < N && N >= 3 {
+        let mut row = 0;
+        while row < N {
+            lines[standard_count + start - 1][row] = row * N + (start + row) % N;
+            lines[standard_count + N - 1 + start - 1][row] =
+                row * N + (N - 1 - start + N - row) % N;
+            row += 1;
+        }
+        start += 1;
+    }
+
+    let mut through = [[0; MAX_LINES_THROUGH_CELL]; MAX_CELLS];
+    let mut through_standard_count = [0; MAX_CELLS];
+    let mut through_count = [0; MAX_CELLS];
+    let mut line = 0;
+    while line < count {
+        let mut i = 0;
+        while i < N {
+            let cell = lines[line][i];
+            through[cell][through_count[cell]] = line;
+            through_count[cell] += 1;
+            if line < standard_count {
+                through_standard_count[cell] += 1;
+            }
+            i += 1;
+        }
+        line += 1;
+    }
+
+    LineTable {
+        lines,
+        standard_count,
+        count,
+        through,
+        through_standard_count,
+        through_count,
+    }
+}
+
+/// Cell indices of a winning run: a full line, or with a shorter win length, that many cells in a
+/// row. Derefs to the indices, and is small enough to be copied around instead of allocated.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Run<const N: usize> {
+    cells: [usize; N],
+    len: usize,
+}
+
+impl<const N: usize> Run<N> {
+    /// Run of `len` cells, starting at `start` and `step` cells apart.
+    fn new(start: usize, step: usize, len: usize) -> Self {
+        let mut cells = [0; N];
+        for (i, cell) in cells.iter_mut().take(len).enumerate() {
+            *cell = start + i * step;
+        }
+
+        Self { cells, len }
+    }
+
+    /// Run of `len` cells going diagonally down from (`row`, `column`), to the right or (unless
+    /// `rightwards`) to the left, wrapping around the edges of the board.
+    fn wrapped(row: usize, column: usize, rightwards: bool, len: usize) -> Self {
+        let mut cells = [0; N];
+        for (i, cell) in cells.iter_mut().take(len).enumerate() {
+            let column = if rightwards {
+                column + i
+            } else {
+                column + N - i
+            };
+            *cell = (row + i) % N * N + column % N;
+        }
+
+        Self { cells, len }
+    }
+}
+
+impl<const N: usize> Deref for Run<N> {
+    type Target = [usize];
+
+    fn deref(&self) -> &[usize] {
+        &self.cells[..self.len]
+    }
+}
+
+impl<const N: usize> IntoIterator for Run<N> {
+    type Item = usize;
+    type IntoIter = std::iter::Take<std::array::IntoIter<usize, N>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.cells.into_iter().take(self.len)
+    }
+}
+
+/// Label of the empty cell with the given index: its number, counting from 1 (continued with
+/// letters on boards larger than 3x3).
+fn empty_cell_label(index: usize) -> char {
+    char::from_digit(index as u32 + 1, 36).unwrap_or('?')
+}
+
+/// Returns the orientation of the full line of the classic board with the given index in
+/// `WINNING_LINES`.
+pub fn line_orientation(index: WinningLineIndex) -> LineOrientation {
+    let line = Board::<3>::line(index);
+
+    if line.iter().all(|cell| cell / 3 == line[0] / 3) {
+        LineOrientation::Row
+    } else if line.iter().all(|cell| cell % 3 == line[0] % 3) {
+        LineOrientation::Column
+    } else {
+        LineOrientation::Diagonal
+    }
+}
+
+/// Returns a human-readable name of the full line of the classic board with the given index,
+/// e.g. "top row". Indices of runs shorter than a line are named with `describe_run`.
+///
+/// # Examples
+///
+/// ```
+/// use tic_tac_terminal::board;
+///
+/// assert_eq!(board::describe_line(6), "main diagonal");
+/// ```
+pub fn describe_line(index: WinningLineIndex) -> &'static str {
+    describe_run(&Board::<3>::line(index))
+}
+
+/// Returns a human-readable name of the line of the classic board holding the cells of a winning
+/// run, e.g. "top row" for cells 0 and 1 when two in a row win. Diagonals other than the two
+/// full ones are just "diagonal", or "wrapped diagonal" if they wrap around the edges.
+///
+/// # Examples
+///
+/// ```
+/// use tic_tac_terminal::board;
+///
+/// assert_eq!(board::describe_run(&[4, 8]), "main diagonal");
+/// assert_eq!(board::describe_run(&[1, 5]), "diagonal");
+/// assert_eq!(board::describe_run(&[1, 5, 6]), "wrapped diagonal");
+/// ```
+pub fn describe_run(cells: &[usize]) -> &'static str {
+    let all_share = |key: fn(usize) -> usize| cells.iter().all(|&cell| key(cell) == key(cells[0]));
+    let all_on = |line: [usize; 3]| cells.iter().all(|cell| line.contains(cell));
+    let is_straight = cells
+        .windows(2)
+        .all(|pair| pair[1] / 3 == pair[0] / 3 + 1 && (pair[1] % 3).abs_diff(pair[0] % 3) == 1);
+
+    if all_share(|cell| cell / 3) {
+        ["top row", "middle row", "bottom row"][cells[0] / 3]
+    } else if all_share(|cell| cell % 3) {
+        ["left column", "middle column", "right column"][cells[0] % 3]
+    } else if all_on(WINNING_LINES[6]) {
+        "main diagonal"
+    } else if all_on(WINNING_LINES[7]) {
+        "anti-diagonal"
+    } else if is_straight {
+        "diagonal"
+    } else {
+        "wrapped diagonal" // Only on a toroidal board
+    }
+}
+
+pub fn rows() -> Vec<[usize; 3]> {
+    lines_by_orientation(LineOrientation::Row)
+}
+
+pub fn columns() -> Vec<[usize; 3]> {
+    lines_by_orientation(LineOrientation::Column)
+}
+
+pub fn diagonals() -> Vec<[usize; 3]> {
+    lines_by_orientation(LineOrientation::Diagonal)
+}
+
+fn lines_by_orientation(orientation: LineOrientation) -> Vec<[usize; 3]> {
+    (0..WINNING_LINES.len())
+        .filter(|&index| line_orientation(index) == orientation)
+        .map(|index| WINNING_LINES[index])
+        .collect()
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CellTaken => write!(f, "This cell is not empty"),
+            Self::NotAMark => write!(f, "Only 'O' and 'X' can be placed on the board"),
+            Self::GameOver => write!(f, "The game is already over"),
+        }
+    }
+}
+
 impl fmt::Display for Cell {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
             Self::Empty(index) => write!(f, "{index}"),
             Self::O => write!(f, "O"),
             Self::X => write!(f, "X"),
+            Self::Blocked => write!(f, "#"),
+        }
+    }
+}
+
+/// Compact, single-line representation: cells row by row, with `.` for empty cells.
+impl<const N: usize> fmt::Display for Board<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for cell in self.iter() {
+            match cell {
+                Cell::Empty(_) => write!(f, ".")?,
+                _ => write!(f, "{cell}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses the compact representation produced by `Display`. Both `O`/`X` and `o`/`x` are accepted.
+///
+/// # Examples
+///
+/// ```
+/// use tic_tac_terminal::board::{Board, Cell};
+///
+/// let board: Board = "O...X...#".parse().unwrap();
+///
+/// assert_eq!(board[0], Cell::O);
+/// assert_eq!(board[8], Cell::Blocked);
+/// assert_eq!(board.to_string(), "O...X...#");
+/// ```
+impl<const N: usize> FromStr for Board<N> {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = s.trim().chars().collect();
+        if chars.len() != N * N {
+            return Err(());
+        }
+
+        let mut board = Self::default();
+        for (index, c) in chars.into_iter().enumerate() {
+            board[index] = match c {
+                'O' | 'o' => Cell::O,
+                'X' | 'x' => Cell::X,
+                '#' => Cell::Blocked,
+                '.' => continue,
+                _ => return Err(()),
+            };
+        }
+
+        Ok(board)
+    }
+}
+
+/// Builds a board from its cells, listed row by row.
+///
+/// # Examples
+///
+/// ```
+/// use tic_tac_terminal::board::{Board, Cell};
+///
+/// let mut cells = [Cell::Blocked; 9];
+/// cells[4] = Cell::O;
+/// let board: Board = cells.into();
+///
+/// assert_eq!(board[4], Cell::O);
+/// ```
+impl From<Cells> for Board {
+    fn from(cells: Cells) -> Self {
+        let mut board = Self::new();
+        for (index, cell) in cells.into_iter().enumerate() {
+            board[index] = cell;
         }
+        board
     }
 }
 
@@ -57,17 +401,18 @@ impl fmt::Display for BoardMove {
     }
 }
 
-impl Index<usize> for Board {
+/// Cells are indexed row by row, starting from 0.
+impl<const N: usize> Index<usize> for Board<N> {
     type Output = Cell;
 
     fn index(&self, index: usize) -> &Self::Output {
-        &self.cells[index]
+        &self.cells[index / N][index % N]
     }
 }
 
-impl IndexMut<usize> for Board {
+impl<const N: usize> IndexMut<usize> for Board<N> {
     fn index_mut(&mut self, index: usize) -> &mut Self::Output {
-        &mut self.cells[index]
+        &mut self.cells[index / N][index % N]
     }
 }
 
@@ -85,13 +430,42 @@ impl Cell {
             Self::O => Self::X,
             Self::X => Self::O,
             Self::Empty(n) => Self::Empty(n),
+            Self::Blocked => Self::Blocked,
+        }
+    }
+
+    /// Returns a stable numeric identity of the mark (0 for 'O', 1 for 'X'), independent of how
+    /// the cell is displayed. Empty cells have no identity.
+    pub fn symbol_id(&self) -> Option<u8> {
+        match *self {
+            Self::O => Some(0),
+            Self::X => Some(1),
+            Self::Empty(_) | Self::Blocked => None,
         }
     }
 }
 
 impl BoardMove {
+    /// Creates a move on the given cell of the classic board, counting from 1.
+    #[allow(clippy::result_unit_err)]
     pub fn try_new(num: usize) -> Result<Self, ()> {
-        if num < 1 || num > 9 {
+        Self::try_new_for::<3>(num)
+    }
+
+    /// Creates a move on the given cell of a board of `N` x `N` cells, counting from 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::board::BoardMove;
+    ///
+    /// assert_eq!(BoardMove::try_new_for::<5>(25).unwrap().index(), 24);
+    /// assert!(BoardMove::try_new_for::<5>(26).is_err());
+    /// assert!(BoardMove::try_new(10).is_err(), "Classic board only has 9 cells");
+    /// ```
+    #[allow(clippy::result_unit_err)]
+    pub fn try_new_for<const N: usize>(num: usize) -> Result<Self, ()> {
+        if !(1..=N * N).contains(&num) {
             return Err(());
         }
 
@@ -103,32 +477,445 @@ impl BoardMove {
     }
 }
 
-impl Board {
-    pub fn new() -> Self {
-        let mut cells = [Cell::Empty('0'); 9];
-        for i in 0..9 {
-            let ascii_num = ((i + 1) as u8 + b'0') as char;
-            cells[i] = Cell::Empty(ascii_num); // These values serve as cell position
+/// Creates an empty board of any size. For the classic board, `Board::new()` does the same.
+///
+/// # Examples
+///
+/// ```
+/// use tic_tac_terminal::board::Board;
+///
+/// let board = Board::<4>::default();
+///
+/// assert_eq!(board.to_string(), "................");
+/// ```
+impl<const N: usize> Default for Board<N> {
+    fn default() -> Self {
+        let mut board = Self {
+            cells: [[Cell::Empty('0'); N]; N],
+            toroidal: false,
+            win_length: N,
+        };
+        for index in 0..N * N {
+            board[index] = Cell::Empty(empty_cell_label(index)); // These values serve as cell position
         }
-        Self { cells }
+        board
     }
+}
+
+impl<const N: usize> Board<N> {
+    const LINES: LineTable<N> = generate_lines::<N>();
+
+    /// Builds a board from a visual layout with one row per line, laid out like the drawn grid:
+    /// `O`, `X`, `#` for a blocked cell and `.` or the cell's number for an empty one. Spaces and
+    /// the grid's `|`, `-`, `+`, `[` and `]` are ignored, as are blank lines. Handy for writing
+    /// test positions which can be read at a glance.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::board::Board;
+    ///
+    /// let board: Board = Board::from_layout(
+    ///     "O | 2 | X
+    ///      . | O | .
+    ///      7 | X | 9",
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(board.to_string(), "O.X.O..X.");
+    /// ```
+    #[allow(clippy::result_unit_err)]
+    pub fn from_layout(layout: &str) -> Result<Self, ()> {
+        let is_decoration = |c: char| c.is_whitespace() || "|-+[]".contains(c);
+        let rows: Vec<String> = layout
+            .lines()
+            .map(|line| {
+                line.chars()
+                    .filter(|&c| !is_decoration(c))
+                    .map(|c| if c.is_ascii_digit() { '.' } else { c })
+                    .collect::<String>()
+            })
+            .filter(|row| !row.is_empty())
+            .collect();
 
-    pub fn from(cells: Cells) -> Self {
-        Self { cells }
+        if rows.len() != N || rows.iter().any(|row| row.chars().count() != N) {
+            return Err(());
+        }
+        rows.concat().parse()
     }
 
-    pub fn iter(&self) -> BoardIterator {
+    pub fn iter(&self) -> BoardIterator<'_> {
         BoardIterator {
-            inner: self.cells.iter(),
+            inner: self.cells.as_flattened().iter(),
         }
     }
 
+    /// Iterates over the rows of the board, from top to bottom.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::board::{Board, Cell};
+    ///
+    /// let board: Board = "OOOXX....".parse().unwrap();
+    ///
+    /// assert_eq!(board.rows().next(), Some([Cell::O; 3]));
+    /// ```
+    pub fn rows(&self) -> impl Iterator<Item = [Cell; N]> + '_ {
+        self.cells.iter().copied()
+    }
+
+    /// Iterates over the columns of the board, from left to right. Cells of each column are listed
+    /// from top to bottom.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::board::{Board, Cell};
+    ///
+    /// let board: Board = "OX.OX.O..".parse().unwrap();
+    ///
+    /// assert_eq!(board.columns().next(), Some([Cell::O; 3]));
+    /// ```
+    pub fn columns(&self) -> impl Iterator<Item = [Cell; N]> + '_ {
+        (0..N).map(|column| self.cells.map(|row| row[column]))
+    }
+
     pub fn is_full(&self) -> bool {
         !self.iter().any(|&cell| matches!(cell, Cell::Empty(_)))
     }
 
     pub fn is_empty(&self) -> bool {
-        self.iter().all(|&cell| matches!(cell, Cell::Empty(_)))
+        // Blocked cells are part of the board layout, so they don't count as marks
+        !self.iter().any(|&cell| matches!(cell, Cell::O | Cell::X))
+    }
+
+    /// Returns indices of the cells holding the given mark. Querying for an empty cell yields
+    /// nothing, since empty cells carry their own labels and never compare equal to each other.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::board::{Board, Cell};
+    ///
+    /// let board: Board = "O.X.O...X".parse().unwrap();
+    ///
+    /// assert_eq!(board.occupied_by(Cell::O), vec![0, 4]);
+    /// assert_eq!(board.occupied_by(Cell::X), vec![2, 8]);
+    /// ```
+    pub fn occupied_by(&self, symbol: Cell) -> Vec<usize> {
+        if matches!(symbol, Cell::Empty(_)) {
+            return vec![];
+        }
+
+        self.iter()
+            .enumerate()
+            .filter(|(_, &cell)| cell == symbol)
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Makes diagonals wrap around the edges of the board, as if it was drawn on a torus, so e.g.
+    /// cells 2, 6 and 7 form a line on the classic board. Rows and columns are unaffected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::board::Board;
+    ///
+    /// let board = Board::new().toroidal(true);
+    ///
+    /// assert!(board.is_toroidal());
+    /// ```
+    pub fn toroidal(mut self, enabled: bool) -> Self {
+        self.toroidal = enabled;
+        self
+    }
+
+    pub fn is_toroidal(&self) -> bool {
+        self.toroidal
+    }
+
+    /// Sets how many marks in a row win, e.g. 4 on a 5x5 board, instead of a full line. Clamped
+    /// between 2 and the board size, which is also the default. On a toroidal board, shorter
+    /// diagonal runs wrap around the edges just like full lines.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::board::Board;
+    ///
+    /// let board: Board<5> = "OOOO.XXX.................".parse().unwrap();
+    ///
+    /// assert_eq!(board.get_winning_line(), None);
+    /// assert!(board.win_length(4).get_winning_line().is_some());
+    /// ```
+    pub fn win_length(mut self, win_length: usize) -> Self {
+        // A single mark would win through every line it's on at once
+        self.win_length = win_length.clamp(2.min(N), N);
+        self
+    }
+
+    /// Returns how many marks in a row win.
+    pub fn get_win_length(&self) -> usize {
+        self.win_length
+    }
+
+    /// Returns cell indices of the winning line with the given index. Lines are ordered like
+    /// `WINNING_LINES`: columns, rows, the main diagonal and the secondary diagonal, followed by
+    /// the wrapped diagonals of a toroidal board. These are always full lines; with a shorter
+    /// win length, use [`Board::run`] for indices returned by the board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::board::Board;
+    ///
+    /// assert_eq!(Board::<3>::line(3), [0, 1, 2]);
+    /// assert_eq!(Board::<4>::line(8), [0, 5, 10, 15]);
+    /// assert_eq!(Board::<3>::line(8), [1, 5, 6]);
+    /// ```
+    pub fn line(index: WinningLineIndex) -> [usize; N] {
+        Self::LINES.lines[..Self::LINES.count][index]
+    }
+
+    /// Returns indices of the winning lines passing through the given cell, in ascending order.
+    /// Only these lines can be completed by a move on that cell, so checking a single move doesn't
+    /// need to scan the whole board. Like [`Board::line`], this covers full lines only, whatever
+    /// the win length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::board::Board;
+    ///
+    /// let board = Board::new();
+    ///
+    /// assert_eq!(board.winning_lines_through(0), &[0, 3, 6]);
+    /// assert_eq!(board.winning_lines_through(1), &[1, 3]);
+    /// assert_eq!(board.toroidal(true).winning_lines_through(1), &[1, 3, 8, 10]);
+    /// ```
+    pub fn winning_lines_through(&self, index: usize) -> &'static [WinningLineIndex] {
+        let count = if self.toroidal {
+            Self::LINES.through_count[index]
+        } else {
+            Self::LINES.through_standard_count[index]
+        };
+        &Self::LINES.through[index][..count]
+    }
+
+    fn line_indices(&self) -> &'static [[usize; N]] {
+        let count = if self.toroidal {
+            Self::LINES.count
+        } else {
+            Self::LINES.standard_count
+        };
+        &Self::LINES.lines[..count]
+    }
+
+    /// Iterates over cell indices of every run which wins when filled with one mark: the full
+    /// lines, or with a shorter win length, every run of that many cells.
+    fn runs(&self) -> impl Iterator<Item = Run<N>> {
+        let (lines, short_runs): (&'static [[usize; N]], _) = if self.win_length == N {
+            (self.line_indices(), None)
+        } else {
+            (&[], Some(Self::short_runs(self.win_length, self.toroidal)))
+        };
+
+        lines
+            .iter()
+            .map(|&cells| Run { cells, len: N })
+            .chain(short_runs.into_iter().flatten())
+    }
+
+    /// Iterates over the runs of `k` cells shorter than a full line, in the order of
+    /// `lines::generate`: columns, rows, then main and secondary diagonals. On a toroidal board,
+    /// these are followed by the diagonal runs which wrap around the edges (first parallel to the
+    /// main diagonal, then to the secondary one, each by the cell they start in). They're computed
+    /// on the fly rather than stored, since every length needs a table of its own.
+    fn short_runs(k: usize, toroidal: bool) -> impl Iterator<Item = Run<N>> {
+        let fits = N + 1 - k; // Starting positions along a line
+        let run = move |row: usize, column: usize, step: usize| Run::new(row * N + column, step, k);
+        let wrapped_runs = move |rightwards: bool| {
+            let cells = if toroidal { 0..N * N } else { 0..0 };
+            cells
+                .map(move |index| (index / N, index % N))
+                .filter(move |&(row, column)| {
+                    let fits_column = if rightwards {
+                        column < fits
+                    } else {
+                        column >= k - 1
+                    };
+                    row >= fits || !fits_column // Runs which fit were listed already
+                })
+                .map(move |(row, column)| Run::wrapped(row, column, rightwards, k))
+        };
+
+        let columns = (0..N).flat_map(move |column| (0..fits).map(move |row| run(row, column, N)));
+        let rows = (0..N).flat_map(move |row| (0..fits).map(move |column| run(row, column, 1)));
+        let diagonals =
+            (0..fits).flat_map(move |row| (0..fits).map(move |column| run(row, column, N + 1)));
+        let secondary_diagonals =
+            (0..fits).flat_map(move |row| (k - 1..N).map(move |column| run(row, column, N - 1)));
+
+        columns
+            .chain(rows)
+            .chain(diagonals)
+            .chain(secondary_diagonals)
+            .chain(wrapped_runs(true))
+            .chain(wrapped_runs(false))
+    }
+
+    /// Returns cell indices of the winning run with the given index, as returned by
+    /// `get_winning_line` and the like. The same as `line` unless the win length is shorter than
+    /// the board, so the UI can highlight the run which won.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::board::Board;
+    ///
+    /// let board: Board<4> = "OOO.XX..........".parse().unwrap();
+    /// let board = board.win_length(3);
+    /// let line = board.get_winning_line().unwrap();
+    ///
+    /// assert_eq!(board.run(line)[..], [0, 1, 2]);
+    /// ```
+    pub fn run(&self, index: WinningLineIndex) -> Run<N> {
+        self.runs()
+            .nth(index)
+            .expect("Winning line index out of range")
+    }
+
+    fn is_complete_run(&self, run: &[usize]) -> bool {
+        matches!(self[run[0]], Cell::O | Cell::X)
+            && run.iter().all(|&index| self[index] == self[run[0]])
+    }
+
+    pub fn get_winning_line(&self) -> Option<WinningLineIndex> {
+        self.runs().position(|run| self.is_complete_run(&run))
+    }
+
+    /// Checks whether the game is over, either won or drawn.
+    pub fn is_terminal(&self) -> bool {
+        self.terminal_outcome().is_some()
+    }
+
+    /// Tells how the game ended: who completed a line, or a draw on a full board. Returns `None`
+    /// while the game is still going.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::board::{Board, Cell, TerminalOutcome};
+    ///
+    /// let won: Board = "XXXOO.O..".parse().unwrap();
+    /// let full: Board = "OXOOXXXOO".parse().unwrap();
+    ///
+    /// assert_eq!(won.terminal_outcome(), Some(TerminalOutcome::Win(Cell::X)));
+    /// assert_eq!(full.terminal_outcome(), Some(TerminalOutcome::Draw));
+    /// assert_eq!(Board::new().terminal_outcome(), None);
+    /// ```
+    pub fn terminal_outcome(&self) -> Option<TerminalOutcome> {
+        if let Some(run) = self.runs().find(|run| self.is_complete_run(run)) {
+            Some(TerminalOutcome::Win(self[run[0]]))
+        } else if self.is_full() {
+            Some(TerminalOutcome::Draw)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the index of a line completed by the given mark, if there is one. Other marks'
+    /// lines are ignored, so this tells whether a specific player has won.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::board::{Board, Cell};
+    ///
+    /// let board: Board = "OOOXX....".parse().unwrap();
+    ///
+    /// assert_eq!(board.winning_line_for(Cell::O), Some(3));
+    /// assert_eq!(board.winning_line_for(Cell::X), None);
+    /// ```
+    pub fn winning_line_for(&self, symbol: Cell) -> Option<WinningLineIndex> {
+        if !matches!(symbol, Cell::O | Cell::X) {
+            return None;
+        }
+
+        self.runs()
+            .position(|run| run.iter().all(|&index| self[index] == symbol))
+    }
+
+    /// Returns indices of all completed lines. A single move can complete two lines at once, e.g.
+    /// a row and a diagonal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::board::Board;
+    ///
+    /// let board: Board = "OOOXOXXXO".parse().unwrap();
+    ///
+    /// assert_eq!(board.winning_lines(), vec![3, 6]);
+    /// ```
+    pub fn winning_lines(&self) -> Vec<WinningLineIndex> {
+        self.runs()
+            .enumerate()
+            .filter(|(_index, run)| self.is_complete_run(run))
+            .map(|(index, _run)| index)
+            .collect()
+    }
+
+    /// Returns indices of the lines which the given mark could complete with a single move: it
+    /// holds all but one of their cells and the remaining one is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::board::{Board, Cell};
+    ///
+    /// let board: Board = "OO.XX.O..".parse().unwrap();
+    ///
+    /// assert_eq!(board.almost_winning_lines(Cell::O), vec![3]);
+    /// assert_eq!(board.almost_winning_lines(Cell::X), vec![4]);
+    /// ```
+    pub fn almost_winning_lines(&self, symbol: Cell) -> Vec<WinningLineIndex> {
+        if !matches!(symbol, Cell::O | Cell::X) {
+            return vec![];
+        }
+
+        self.runs()
+            .enumerate()
+            .filter(|(_index, run)| {
+                let marks = run.iter().filter(|&&index| self[index] == symbol).count();
+                let empty = run
+                    .iter()
+                    .filter(|&&index| matches!(self[index], Cell::Empty(_)))
+                    .count();
+                marks == run.len() - 1 && empty == 1
+            })
+            .map(|(index, _run)| index)
+            .collect()
+    }
+
+    /// Iterates over the cell values of every winning line, in the order of `WINNING_LINES`. Only
+    /// full lines are covered, whatever the win length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::board::{Board, Cell};
+    ///
+    /// let board: Board = "OOOXX....".parse().unwrap();
+    ///
+    /// assert!(board.lines().any(|line| line == [Cell::O; 3]));
+    /// ```
+    pub fn lines(&self) -> impl Iterator<Item = [Cell; N]> + '_ {
+        self.line_indices()
+            .iter()
+            .map(|line| line.map(|index| self[index]))
     }
 
     pub fn get_possible_moves(&self) -> Vec<BoardMove> {
@@ -136,7 +923,7 @@ impl Board {
 
         for (index, cell) in self.iter().enumerate() {
             if let &Cell::Empty(_) = cell {
-                moves.push(BoardMove::try_new(index + 1).unwrap())
+                moves.push(BoardMove { index })
             }
         }
 
@@ -147,30 +934,126 @@ impl Board {
         self.get_possible_moves().contains(board_move)
     }
 
-    pub fn get_winning_line(&self) -> Option<WinningLineIndex> {
-        let b = &self.cells;
-
-        for (index, cell_triplet) in WINNING_LINES.iter().enumerate() {
-            let (c1, c2, c3) = (b[cell_triplet[0]], b[cell_triplet[1]], b[cell_triplet[2]]);
-            if c1 == c2 && c1 == c3 {
-                match c1 {
-                    Cell::O | Cell::X => return Some(index),
-                    _ => continue,
-                }
-            }
+    /// Places the mark at the given move, provided the cell is empty and the mark is 'O' or 'X'.
+    /// The board is left untouched otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::board::{Board, BoardMove, Cell, MoveError};
+    ///
+    /// let mut board = Board::new();
+    /// let center = BoardMove::try_new(5).unwrap();
+    ///
+    /// assert_eq!(board.place(&center, Cell::O), Ok(()));
+    /// assert_eq!(board.place(&center, Cell::X), Err(MoveError::CellTaken));
+    /// assert_eq!(board[4], Cell::O);
+    /// ```
+    pub fn place(&mut self, board_move: &BoardMove, symbol: Cell) -> Result<(), MoveError> {
+        if !matches!(symbol, Cell::O | Cell::X) {
+            return Err(MoveError::NotAMark);
+        }
+        if !matches!(self[board_move.index()], Cell::Empty(_)) {
+            return Err(MoveError::CellTaken);
         }
 
-        None
+        self.place_unchecked(board_move, symbol);
+        Ok(())
+    }
+
+    /// Places the mark at the given move without validating it, for hot paths (such as the CPU's
+    /// search) which only ever generate legal moves. Misuse is caught in debug builds only.
+    pub fn place_unchecked(&mut self, board_move: &BoardMove, symbol: Cell) {
+        debug_assert!(
+            matches!(self[board_move.index()], Cell::Empty(_)),
+            "Cell {} is not empty",
+            board_move.index() + 1
+        );
+        debug_assert!(
+            matches!(symbol, Cell::O | Cell::X),
+            "Only 'O' and 'X' can be placed"
+        );
+
+        self[board_move.index()] = symbol;
+    }
+
+    /// Checks whether placing `symbol` at the given move would complete a line. Only the lines
+    /// passing through the move's cell are inspected.
+    pub fn is_winning_move(&self, symbol: Cell, board_move: &BoardMove) -> bool {
+        let index = board_move.index();
+
+        if !matches!(self[index], Cell::Empty(_)) || !matches!(symbol, Cell::O | Cell::X) {
+            return false;
+        }
+
+        let completes = |run: &[usize]| {
+            run.iter()
+                .filter(|&&cell_index| cell_index != index)
+                .all(|&cell_index| self[cell_index] == symbol)
+        };
+        if self.win_length < N {
+            return self
+                .runs()
+                .filter(|run| run.contains(&index))
+                .any(|run| completes(&run));
+        }
+
+        self.winning_lines_through(index)
+            .iter()
+            .any(|&line| completes(&Self::line(line)))
+    }
+
+    /// Counts the winning lines which `symbol` can still complete, i.e. lines with neither an
+    /// opposing mark nor a blocked cell in them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::board::{Board, Cell};
+    ///
+    /// let board: Board = "X...O....".parse().unwrap();
+    ///
+    /// assert_eq!(board.remaining_winnable_for(Cell::O), 5);
+    /// assert_eq!(board.remaining_winnable_for(Cell::X), 4);
+    /// ```
+    pub fn remaining_winnable_for(&self, symbol: Cell) -> usize {
+        let opponent = symbol.opposite();
+
+        self.runs()
+            .filter(|run| {
+                run.iter()
+                    .all(|&index| self[index] != opponent && self[index] != Cell::Blocked)
+            })
+            .count()
+    }
+
+    /// Checks whether the game can only end in a draw, because every line is already contested by
+    /// both players or contains a blocked cell.
+    pub fn is_drawn(&self) -> bool {
+        self.remaining_winnable_for(Cell::O) == 0 && self.remaining_winnable_for(Cell::X) == 0
+    }
+
+    /// Checks whether the position can be reached in a game where 'O' moves first: players take
+    /// turns and nobody moves after the game has been won.
+    pub fn is_legal_position(&self) -> bool {
+        let count = |symbol| self.iter().filter(|&&cell| cell == symbol).count();
+        let (o_count, x_count) = (count(Cell::O), count(Cell::X));
+
+        let won = |symbol| self.winning_line_for(symbol).is_some();
+
+        match (won(Cell::O), won(Cell::X)) {
+            (true, true) => false,
+            (true, false) => o_count == x_count + 1,
+            (false, true) => o_count == x_count,
+            (false, false) => o_count == x_count || o_count == x_count + 1,
+        }
     }
 
     pub fn current_player_symbol(&self) -> Cell {
         // We always assume that 'O' goes first
         if self
             .iter()
-            .filter(|&cell| match &cell {
-                &Cell::O | Cell::X => true,
-                _ => false,
-            })
+            .filter(|&cell| matches!(cell, Cell::O | Cell::X))
             .count()
             % 2
             == 0
@@ -180,80 +1063,429 @@ impl Board {
             Cell::X
         }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Returns the board rotated by 90 degrees clockwise. Empty cells are relabelled to match their
+    /// new positions.
+    pub fn rotated(&self) -> Self {
+        self.transformed(|row, column| (N - 1 - column) * N + row)
+    }
 
-    #[test]
-    fn proper_move() {
-        for cell in 1..=9 {
-            let m = BoardMove::try_new(cell);
+    /// Returns the board mirrored left to right. Empty cells are relabelled to match their new
+    /// positions.
+    pub fn mirrored(&self) -> Self {
+        self.transformed(|row, column| row * N + (N - 1 - column))
+    }
 
-            assert!(m.is_ok(), "Move should be constructed properly");
+    /// Returns all eight images of the position under rotation and reflection, starting with the
+    /// position itself. Symmetric positions repeat some of the images; empty cells are relabelled,
+    /// so repeated images compare equal.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::board::Board;
+    ///
+    /// let board: Board = "O........".parse().unwrap();
+    /// let images = board.symmetry_group();
+    ///
+    /// assert_eq!(images[0], board);
+    /// assert!(images.contains(&"........O".parse().unwrap()));
+    /// ```
+    pub fn symmetry_group(&self) -> [Self; 8] {
+        let mut images = [*self; 8];
+        let mut board = *self;
+
+        for rotation in 0..4 {
+            images[rotation * 2] = board;
+            images[rotation * 2 + 1] = board.mirrored();
+            board = board.rotated();
+        }
 
-            let m = m.unwrap();
+        images
+    }
 
-            assert_eq!(m.index(), cell - 1);
-        }
+    /// Returns a representative of the position's symmetry class, so that positions which differ
+    /// only by rotation or reflection have equal canonical forms.
+    pub fn canonical(&self) -> Self {
+        self.symmetry_group()
+            .into_iter()
+            .min_by_key(|board| board.id())
+            .unwrap()
     }
 
-    #[test]
-    fn incorrect_move() {
-        for index in [0, 10, 11, 100, 55555] {
-            let m = BoardMove::try_new(index);
+    /// Builds a board whose cell at (row, column) is taken from `source_index(row, column)`.
+    fn transformed(&self, source_index: impl Fn(usize, usize) -> usize) -> Self {
+        let mut board = Self::default()
+            .toroidal(self.toroidal)
+            .win_length(self.win_length);
 
-            assert!(
-                m.is_err(),
-                "Move should fail to construct with move out of 1..=9 range"
-            );
+        for index in 0..N * N {
+            let cell = self[source_index(index / N, index % N)];
+            if !matches!(cell, Cell::Empty(_)) {
+                board[index] = cell;
+            }
         }
+
+        board
     }
 
-    #[test]
-    fn move_validation() {
-        let mut board = Board::new();
+    /// Returns a compact identity of the position, for deduplication and logging. Every cell is a
+    /// digit (empty is 0, 'O' is 1, 'X' is 2 and a blocked cell is 3), read row by row with the
+    /// first cell being the most significant. Labels of empty cells don't matter, so equal
+    /// positions always get equal ids, and different ones never share an id.
+    ///
+    /// The number is split into two words, the more significant one first, so that even the 81
+    /// cells of a 9x9 board fit. Ids compare like the numbers they stand for.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::board::Board;
+    ///
+    /// let board: Board = "........O".parse().unwrap();
+    ///
+    /// assert_eq!(Board::new().id(), [0, 0]);
+    /// assert_eq!(board.id(), [0, 1]);
+    /// ```
+    pub fn id(&self) -> [u128; 2] {
+        // Base 4 rather than 3, so that blocked cells have a digit of their own
+        self.iter().fold([0, 0], |[high, low], cell| {
+            let digit = match cell {
+                Cell::Empty(_) => 0,
+                Cell::Blocked => 3,
+                _ => cell.symbol_id().unwrap() as u128 + 1,
+            };
+            // Shift in the digit, carrying the top digit of the low word over to the high one
+            [high << 2 | low >> 126, low << 2 | digit]
+        })
+    }
+}
 
-        let valid_move = BoardMove::try_new(1).unwrap();
-        let invalid_move1 = BoardMove::try_new(4).unwrap();
-        let invalid_move2 = BoardMove::try_new(5).unwrap();
+impl Board {
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-        assert!(
-            board.is_valid_move(&valid_move),
-            "All moves should be valid when game starts"
-        );
-        assert!(
-            board.is_valid_move(&invalid_move1),
-            "All moves should be valid when game starts"
-        );
-        assert!(
-            board.is_valid_move(&invalid_move2),
-            "All moves should be valid when game starts"
-        );
+    /// Plays the moves in order from the empty board, 'O' first, and returns the resulting board.
+    /// Fails on the first move that is on a taken cell or comes after the game was won.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::board::{Board, BoardMove, MoveError};
+    ///
+    /// let moves = [5, 1, 9].map(|cell| BoardMove::try_new(cell).unwrap());
+    /// assert_eq!(Board::from_moves(&moves).unwrap().to_string(), "X...O...O");
+    ///
+    /// let moves = [5, 5].map(|cell| BoardMove::try_new(cell).unwrap());
+    /// assert_eq!(Board::from_moves(&moves), Err(MoveError::CellTaken));
+    /// ```
+    pub fn from_moves(moves: &[BoardMove]) -> Result<Self, MoveError> {
+        Self::new()
+            .play_moves(moves)
+            .map_err(|(_index, error)| error)
+    }
 
-        board[3] = Cell::X;
-        board[4] = Cell::O;
+    /// Plays the moves in order on this board, starting with the player to move, and returns the
+    /// resulting board. Fails on the first move that is on a taken cell or comes after the game was
+    /// won, giving its index in `moves` together with the reason.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::board::{Board, BoardMove, MoveError};
+    ///
+    /// let board: Board = "O...X....".parse().unwrap();
+    /// let moves = [9, 5].map(|cell| BoardMove::try_new(cell).unwrap());
+    ///
+    /// assert_eq!(board.play_moves(&moves), Err((1, MoveError::CellTaken)));
+    /// ```
+    pub fn play_moves(mut self, moves: &[BoardMove]) -> Result<Self, (usize, MoveError)> {
+        for (index, board_move) in moves.iter().enumerate() {
+            if self.get_winning_line().is_some() {
+                return Err((index, MoveError::GameOver));
+            }
+            self.place(board_move, self.current_player_symbol())
+                .map_err(|error| (index, error))?;
+        }
 
-        assert!(
-            board.is_valid_move(&valid_move),
-            "Move on empty cell should be valid"
-        );
-        assert!(
-            !board.is_valid_move(&invalid_move1),
-            "Move on occupied cell shouldn't be valid"
-        );
-        assert!(
-            !board.is_valid_move(&invalid_move2),
-            "Move on occupied cell shouldn't be valid"
-        );
+        Ok(self)
     }
 
-    #[test]
-    fn full_board_check() {
-        let board = Board::new();
+    /// Returns the board after the first `n` moves of a transcript, e.g. to jump to any point of a
+    /// replay. Only those moves have to be legal. Any `n` past the end gives the final board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::board::{Board, BoardMove};
+    ///
+    /// let moves = [5, 1, 9, 1].map(|cell| BoardMove::try_new(cell).unwrap());
+    ///
+    /// assert_eq!(Board::from_transcript_prefix(&moves, 2).unwrap().to_string(), "X...O....");
+    /// assert!(Board::from_transcript_prefix(&moves, 4).is_err(), "Cell 1 is taken");
+    /// ```
+    pub fn from_transcript_prefix(moves: &[BoardMove], n: usize) -> Result<Self, MoveError> {
+        Self::from_moves(&moves[..n.min(moves.len())])
+    }
 
-        assert!(
+    /// Heuristic evaluation of the position from the point of view of `symbol`. Every winning run
+    /// (see [`Board::win_length`]) that is still open for one of the players counts for that
+    /// player, with runs closer to completion weighing more. Holding the center gives a small
+    /// bonus. Positive scores favour `symbol`, negative ones its opponent.
+    pub fn score_lines_for(&self, symbol: Cell) -> i32 {
+        const LINE_WEIGHTS: [i32; 4] = [0, 1, 10, 100]; // By the number of marks a run is missing
+        const CENTER_BONUS: i32 = 3;
+
+        let opponent = symbol.opposite();
+        let weight = |run: &Run<3>, marks: usize| LINE_WEIGHTS[3 - (run.len() - marks)];
+        let mut score = 0;
+
+        for run in self.runs() {
+            let count = |mark: Cell| run.iter().filter(|&&index| self[index] == mark).count();
+            let own = count(symbol);
+            let opposing = count(opponent);
+            let blocked = run.iter().any(|&index| self[index] == Cell::Blocked);
+
+            if blocked {
+                continue;
+            }
+            if opposing == 0 {
+                score += weight(&run, own);
+            }
+            if own == 0 {
+                score -= weight(&run, opposing);
+            }
+        }
+
+        if self[4] == symbol {
+            score += CENTER_BONUS;
+        } else if self[4] == opponent {
+            score -= CENTER_BONUS;
+        }
+
+        score
+    }
+
+    /// Counts distinct positions which can occur in a game started from the empty board, including
+    /// the empty board itself and finished games. With `by_symmetry`, positions which differ only
+    /// by rotation or reflection are counted once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::board::Board;
+    ///
+    /// assert_eq!(Board::count_reachable_positions(true), 765);
+    /// ```
+    pub fn count_reachable_positions(by_symmetry: bool) -> usize {
+        let mut seen = HashSet::new();
+        let mut stack = vec![Board::new()];
+
+        while let Some(board) = stack.pop() {
+            let board = if by_symmetry {
+                board.canonical()
+            } else {
+                board
+            };
+            if !seen.insert(board.id()) {
+                continue;
+            }
+            debug_assert!(board.is_legal_position(), "{board} isn't reachable");
+
+            if board.get_winning_line().is_some() {
+                continue;
+            }
+
+            let symbol = board.current_player_symbol();
+            for board_move in board.get_possible_moves() {
+                let mut next_board = board;
+                next_board[board_move.index()] = symbol;
+                stack.push(next_board);
+            }
+        }
+
+        seen.len()
+    }
+
+    /// Lists cells which differ between this board and `other`, as `(index, old, new)` tuples in
+    /// order of increasing index. Empty for identical boards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::board::{Board, Cell};
+    ///
+    /// let before = Board::new();
+    /// let after: Board = "....O....".parse().unwrap();
+    ///
+    /// assert_eq!(before.diff(&after), vec![(4, Cell::Empty('5'), Cell::O)]);
+    /// ```
+    pub fn diff(&self, other: &Board) -> Vec<(usize, Cell, Cell)> {
+        self.iter()
+            .zip(other.iter())
+            .enumerate()
+            .filter(|(_index, (old, new))| old != new)
+            .map(|(index, (&old, &new))| (index, old, new))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cell_symbol_ids() {
+        assert_eq!(Cell::O.symbol_id(), Some(0), "'O' should have id 0");
+        assert_eq!(Cell::X.symbol_id(), Some(1), "'X' should have id 1");
+        assert_eq!(
+            Cell::Empty('5').symbol_id(),
+            None,
+            "Empty cell shouldn't have an id"
+        );
+    }
+
+    #[test]
+    fn lines_grouped_by_orientation() {
+        assert_eq!(rows(), vec![[0, 1, 2], [3, 4, 5], [6, 7, 8]]);
+        assert_eq!(columns(), vec![[0, 3, 6], [1, 4, 7], [2, 5, 8]]);
+        assert_eq!(diagonals(), vec![[0, 4, 8], [2, 4, 6]]);
+
+        assert_eq!(
+            rows().len() + columns().len() + diagonals().len(),
+            WINNING_LINES.len(),
+            "Every winning line should belong to exactly one group"
+        );
+
+        assert_eq!(line_orientation(0), LineOrientation::Column);
+        assert_eq!(line_orientation(3), LineOrientation::Row);
+        assert_eq!(line_orientation(7), LineOrientation::Diagonal);
+    }
+
+    #[test]
+    fn line_descriptions() {
+        let descriptions: Vec<_> = (0..WINNING_LINES.len()).map(describe_line).collect();
+
+        assert_eq!(
+            descriptions,
+            vec![
+                "left column",
+                "middle column",
+                "right column",
+                "top row",
+                "middle row",
+                "bottom row",
+                "main diagonal",
+                "anti-diagonal",
+            ],
+            "Every line should be described in the order of WINNING_LINES"
+        );
+    }
+
+    #[test]
+    fn run_descriptions() {
+        let describe = |position: &str| {
+            let board = position.parse::<Board>().unwrap().win_length(2);
+            describe_run(&board.run(board.get_winning_line().unwrap()))
+        };
+
+        assert_eq!(describe("OO.X.X..."), "top row");
+        assert_eq!(describe("X...OX..O"), "main diagonal");
+        assert_eq!(describe("X.O.O..X."), "anti-diagonal");
+        assert_eq!(describe(".O...O.X."), "diagonal");
+    }
+
+    #[test]
+    fn compact_display() {
+        let mut board = Board::new();
+
+        assert_eq!(board.to_string(), ".........");
+
+        board[0] = Cell::O;
+        board[4] = Cell::X;
+        board[8] = Cell::Blocked;
+
+        assert_eq!(board.to_string(), "O...X...#");
+    }
+
+    #[test]
+    fn proper_move() {
+        for cell in 1..=9 {
+            let m = BoardMove::try_new(cell);
+
+            assert!(m.is_ok(), "Move should be constructed properly");
+
+            let m = m.unwrap();
+
+            assert_eq!(m.index(), cell - 1);
+        }
+    }
+
+    #[test]
+    fn incorrect_move() {
+        for index in [0, 10, 11, 100, 55555] {
+            let m = BoardMove::try_new(index);
+
+            assert!(
+                m.is_err(),
+                "Move should fail to construct with move out of 1..=9 range"
+            );
+        }
+    }
+
+    #[test]
+    fn move_validation() {
+        let mut board = Board::new();
+
+        let valid_move = BoardMove::try_new(1).unwrap();
+        let invalid_move1 = BoardMove::try_new(4).unwrap();
+        let invalid_move2 = BoardMove::try_new(5).unwrap();
+
+        assert!(
+            board.is_valid_move(&valid_move),
+            "All moves should be valid when game starts"
+        );
+        assert!(
+            board.is_valid_move(&invalid_move1),
+            "All moves should be valid when game starts"
+        );
+        assert!(
+            board.is_valid_move(&invalid_move2),
+            "All moves should be valid when game starts"
+        );
+
+        board[3] = Cell::X;
+        board[4] = Cell::O;
+
+        assert!(
+            board.is_valid_move(&valid_move),
+            "Move on empty cell should be valid"
+        );
+        assert!(
+            !board.is_valid_move(&invalid_move1),
+            "Move on occupied cell shouldn't be valid"
+        );
+        assert!(
+            !board.is_valid_move(&invalid_move2),
+            "Move on occupied cell shouldn't be valid"
+        );
+    }
+
+    #[test]
+    fn default_board() {
+        assert_eq!(
+            Board::default(),
+            Board::new(),
+            "Default board should be a new, empty board"
+        );
+    }
+
+    #[test]
+    fn full_board_check() {
+        let board = Board::new();
+
+        assert!(
             !board.is_full(),
             "Board is empty - method should return false"
         );
@@ -270,6 +1502,43 @@ mod tests {
         );
     }
 
+    #[test]
+    fn terminal_positions() {
+        let board: Board = "OOOXX....".parse().unwrap();
+        assert!(board.is_terminal());
+        assert_eq!(
+            board.terminal_outcome(),
+            Some(TerminalOutcome::Win(Cell::O))
+        );
+
+        let board: Board = "OXOXOXXOX".parse().unwrap();
+        assert!(board.is_terminal());
+        assert_eq!(
+            board.terminal_outcome(),
+            Some(TerminalOutcome::Draw),
+            "Full board without a line is a draw"
+        );
+
+        let board: Board = "OXOXOXXO.".parse().unwrap();
+        assert!(!board.is_terminal());
+        assert_eq!(board.terminal_outcome(), None);
+
+        let board: Board = "OXOOXXXOO".parse().unwrap();
+        assert_eq!(
+            board.terminal_outcome(),
+            Some(TerminalOutcome::Draw),
+            "Last cell taken without completing a line"
+        );
+
+        let board: Board = "XOXOXOOOX".parse().unwrap();
+        assert_eq!(
+            board.terminal_outcome(),
+            Some(TerminalOutcome::Win(Cell::X)),
+            "Win on a full board isn't a draw"
+        );
+        assert!(!Board::new().is_terminal());
+    }
+
     #[test]
     fn empty_board_check() {
         let mut board = Board::new();
@@ -381,6 +1650,67 @@ mod tests {
         );
     }
 
+    #[test]
+    fn blocked_cells_are_not_playable() {
+        let mut board = Board::new();
+        board[0] = Cell::Blocked;
+        board[4] = Cell::Blocked;
+
+        assert!(
+            board.is_empty(),
+            "Blocked cells alone shouldn't make the board non-empty"
+        );
+        assert_eq!(
+            board.get_possible_moves().len(),
+            7,
+            "Blocked cells shouldn't be available as moves"
+        );
+        assert!(
+            !board.is_valid_move(&BoardMove::try_new(1).unwrap()),
+            "Move on blocked cell shouldn't be valid"
+        );
+        assert!(
+            !board.is_valid_move(&BoardMove::try_new(5).unwrap()),
+            "Move on blocked cell shouldn't be valid"
+        );
+
+        let board = Board::from([Cell::Blocked; 9]);
+
+        assert!(
+            board.is_full(),
+            "Board with only blocked cells has no moves left"
+        );
+    }
+
+    #[test]
+    fn blocked_cells_never_win() {
+        let board = Board::from([Cell::Blocked; 9]);
+
+        assert_eq!(
+            board.get_winning_line(),
+            None,
+            "Blocked cells should never form a winning line"
+        );
+
+        let board = Board::from([
+            Cell::O,
+            Cell::Blocked,
+            Cell::O,
+            Cell::X,
+            Cell::Blocked,
+            Cell::X,
+            Cell::Empty('7'),
+            Cell::Blocked,
+            Cell::Empty('9'),
+        ]);
+
+        assert_eq!(
+            board.get_winning_line(),
+            None,
+            "Blocked cells shouldn't complete a line for any player"
+        );
+    }
+
     #[test]
     fn winning_lines() {
         let board = Board::new();
@@ -411,9 +1741,1067 @@ mod tests {
 
         board[6] = Cell::O;
 
-        if let None = board.get_winning_line() {
+        if board.get_winning_line().is_none() {
             panic!("There should be winnig line - three 'O's in column 1");
         }
+        assert_eq!(board.winning_lines(), vec![0], "Only column 1 is complete");
+
+        let board: Board = "OOOXOXXXO".parse().unwrap();
+        assert_eq!(
+            board.winning_lines(),
+            vec![3, 6],
+            "Top row and main diagonal are both complete"
+        );
+    }
+
+    #[test]
+    fn board_ids() {
+        assert_eq!(Board::new().id(), [0, 0], "Empty board has id 0");
+
+        let mut relabelled = Board::new();
+        relabelled[0] = Cell::Empty('a');
+        assert_eq!(
+            relabelled.id(),
+            [0, 0],
+            "Labels of empty cells don't matter"
+        );
+
+        let board: Board = "OX.......".parse().unwrap();
+        let same_board: Board = "OX.......".parse().unwrap();
+        assert_eq!(board.id(), same_board.id());
+
+        let mut seen = HashSet::new();
+        for position in [
+            "O........",
+            "X........",
+            "#........",
+            "........O",
+            "OX.......",
+            "XO.......",
+        ] {
+            let board: Board = position.parse().unwrap();
+            assert!(seen.insert(board.id()), "{position} should get a unique id");
+        }
+    }
+
+    #[test]
+    fn occupied_cells() {
+        let board: Board = "OX.XO#..X".parse().unwrap();
+
+        assert_eq!(board.occupied_by(Cell::O), vec![0, 4]);
+        assert_eq!(board.occupied_by(Cell::X), vec![1, 3, 8]);
+        assert_eq!(board.occupied_by(Cell::Blocked), vec![5]);
+        assert!(
+            board.occupied_by(Cell::Empty('3')).is_empty(),
+            "Empty cells are never reported as occupied"
+        );
+        assert!(Board::new().occupied_by(Cell::X).is_empty());
+    }
+
+    #[test]
+    fn lines_match_board_size() {
+        let lines: Vec<_> = (0..WINNING_LINES.len()).map(Board::<3>::line).collect();
+        assert_eq!(
+            lines, WINNING_LINES,
+            "Generated lines of the classic board should match WINNING_LINES"
+        );
+
+        assert_eq!(Board::<4>::default().lines().count(), 10);
+        assert_eq!(Board::<4>::line(0), [0, 4, 8, 12], "1st column");
+        assert_eq!(Board::<4>::line(7), [12, 13, 14, 15], "4th row");
+        assert_eq!(Board::<4>::line(9), [3, 6, 9, 12], "Secondary diagonal");
+    }
+
+    #[test]
+    fn winning_lines_on_larger_board() {
+        let mut board: Board<4> = "OOO.XXX.........".parse().unwrap();
+
+        assert_eq!(
+            board.get_winning_line(),
+            None,
+            "Three in a row isn't enough on a 4x4 board"
+        );
+
+        board[3] = Cell::O;
+        assert_eq!(
+            board.get_winning_line(),
+            Some(4),
+            "'O' completes the 1st row"
+        );
+
+        let board: Board<4> = "X...OX..O.X.O..X".parse().unwrap();
+        assert_eq!(
+            board.winning_lines(),
+            vec![8],
+            "'X' completes the main diagonal"
+        );
+        assert!(!board.is_full());
+        assert_eq!(
+            "O".repeat(16)
+                .parse::<Board<4>>()
+                .unwrap()
+                .winning_lines()
+                .len(),
+            10,
+            "Every line is complete on a board full of 'O's"
+        );
+        assert_eq!(
+            "O...X....".parse::<Board<4>>(),
+            Err(()),
+            "4x4 board has 16 cells"
+        );
+    }
+
+    #[test]
+    fn symmetry_on_larger_board() {
+        let board: Board<4> = "O..X............".parse().unwrap();
+
+        assert_eq!(board.rotated().to_string(), "...O...........X");
+        assert_eq!(board.mirrored().to_string(), "X..O............");
+        assert_eq!(
+            board.rotated().rotated().rotated().rotated(),
+            board,
+            "Four quarter turns should give the same board"
+        );
+        assert_eq!(
+            board.canonical(),
+            "............X..O".parse::<Board<4>>().unwrap().canonical(),
+            "Position turned around should have the same canonical form"
+        );
+
+        let corner: Board<5> = "........................O".parse().unwrap();
+        assert_ne!(
+            corner.id(),
+            Board::<5>::default().id(),
+            "Cells past the 16th should count towards the id"
+        );
+
+        let first_cell = format!("O{}", ".".repeat(80));
+        let last_cell = format!("{}O", ".".repeat(80));
+        let (first_cell, last_cell) = (
+            first_cell.parse::<Board<9>>().unwrap(),
+            last_cell.parse::<Board<9>>().unwrap(),
+        );
+        assert_ne!(
+            first_cell.id(),
+            Board::<9>::default().id(),
+            "First cell of a 9x9 board shouldn't be shifted out of the id"
+        );
+        assert!(
+            first_cell.id() > last_cell.id(),
+            "First cell is the most significant"
+        );
+    }
+
+    #[test]
+    fn moves_on_larger_board() {
+        let mut board = Board::<5>::default();
+
+        let moves = board.get_possible_moves();
+        assert_eq!(moves.len(), 25, "Every cell of a 5x5 board is free");
+        assert_eq!(moves[24].index(), 24);
+
+        assert!(
+            board.place(&moves[24], Cell::O).is_ok(),
+            "Moves past the 9th cell should be placed"
+        );
+        assert_eq!(board.current_player_symbol(), Cell::X);
+
+        for board_move in moves.iter().step_by(5) {
+            board.place(board_move, Cell::O).unwrap();
+        }
+        assert_eq!(
+            board.get_winning_line(),
+            Some(0),
+            "Marks on cells 0, 5, 10, 15 and 20 fill the 1st column"
+        );
+        assert!(!board.is_winning_move(Cell::X, &moves[12]));
+        assert_eq!(board.get_possible_moves().len(), 19);
+        assert!(!board.is_valid_move(&moves[10]), "Cell 11 is taken");
+        assert!(board.is_valid_move(&moves[11]));
+        assert!(!board.is_full());
+        assert!(
+            !board.is_legal_position(),
+            "'O' can't have moved six times in a row"
+        );
+    }
+
+    #[test]
+    fn shorter_win_length() {
+        assert_eq!(
+            Board::new().get_win_length(),
+            3,
+            "A full line wins by default"
+        );
+        assert_eq!(Board::<5>::default().win_length(9).get_win_length(), 5);
+        assert_eq!(
+            Board::new().win_length(1).get_win_length(),
+            2,
+            "Single mark can't win"
+        );
+        assert_eq!(
+            "O........"
+                .parse::<Board>()
+                .unwrap()
+                .win_length(0)
+                .get_winning_line(),
+            None
+        );
+
+        for k in 2..5 {
+            let runs: Vec<_> = Board::<5>::short_runs(k, false)
+                .map(|run| run.to_vec())
+                .collect();
+            assert_eq!(
+                runs,
+                lines::generate(5, 5, k),
+                "Runs of {k} should match the generated lines"
+            );
+        }
+
+        let board: Board<4> = "OO..XX..........".parse().unwrap();
+        assert_eq!(board.remaining_winnable_for(Cell::O), 6);
+        let board = board.win_length(3);
+        assert_eq!(
+            board.remaining_winnable_for(Cell::X),
+            18,
+            "Runs through 'O' marks can't be completed by 'X'"
+        );
+
+        let almost_winning: Vec<_> = board
+            .almost_winning_lines(Cell::O)
+            .into_iter()
+            .map(|line| board.run(line).to_vec())
+            .collect();
+        assert_eq!(almost_winning, vec![vec![0, 1, 2]]);
+        let cell = |number| BoardMove::try_new_for::<4>(number).unwrap();
+        assert!(board.is_winning_move(Cell::O, &cell(3)));
+        assert!(board.is_winning_move(Cell::X, &cell(7)));
+        assert!(!board.is_winning_move(Cell::X, &cell(8)));
+
+        let board: Board<4> = "X....XO..O..O...".parse().unwrap();
+        assert_eq!(board.terminal_outcome(), None);
+        let board = board.win_length(3);
+        assert_eq!(
+            board.terminal_outcome(),
+            Some(TerminalOutcome::Win(Cell::O))
+        );
+        assert!(board.is_legal_position());
+        let line = board.get_winning_line().expect("'O' holds a diagonal run");
+        let mut run = board.run(line).to_vec();
+        run.sort();
+        assert_eq!(
+            run,
+            vec![6, 9, 12],
+            "The run should be highlighted, not a full line"
+        );
+    }
+
+    /// Checks the board's own line tables, rather than the generator's copy of them: the runs of
+    /// every win length are validated, and the wrapped diagonals of a toroidal board have one cell
+    /// in every row and column.
+    fn assert_valid_lines<const N: usize>() {
+        for k in 2..=N {
+            let runs: Vec<_> = Board::<N>::default()
+                .win_length(k)
+                .runs()
+                .map(|run| run.to_vec())
+                .collect();
+            assert!(
+                lines::is_valid(&runs, N, N, k),
+                "Runs of {k} on a {N}x{N} board are invalid: {runs:?}"
+            );
+        }
+
+        let toroidal = Board::<N>::default().toroidal(true);
+        let wrapped = &toroidal.line_indices()[Board::<N>::default().line_indices().len()..];
+        for line in wrapped {
+            let rows: HashSet<_> = line.iter().map(|index| index / N).collect();
+            let columns: HashSet<_> = line.iter().map(|index| index % N).collect();
+            assert!(
+                rows.len() == N && columns.len() == N,
+                "Wrapped diagonal {line:?} on a {N}x{N} board is invalid"
+            );
+        }
+        let unique: HashSet<_> = toroidal.line_indices().iter().collect();
+        assert_eq!(unique.len(), toroidal.line_indices().len(), "Lines repeat");
+    }
+
+    #[test]
+    fn board_lines_are_valid() {
+        assert_valid_lines::<2>();
+        assert_valid_lines::<3>();
+        assert_valid_lines::<4>();
+        assert_valid_lines::<5>();
+        assert_valid_lines::<6>();
+        assert_valid_lines::<7>();
+        assert_valid_lines::<8>();
+        assert_valid_lines::<9>();
+    }
+
+    #[test]
+    fn wrapped_short_runs() {
+        let board: Board = "..OO.....".parse().unwrap();
+        assert_eq!(
+            board.win_length(2).get_winning_line(),
+            None,
+            "Runs only wrap on a toroidal board"
+        );
+
+        let board = board.toroidal(true).win_length(2);
+        let line = board
+            .get_winning_line()
+            .expect("Cells 3 and 4 are diagonal neighbours across the edge");
+        assert_eq!(board.run(line)[..], [2, 3]);
+        assert_eq!(describe_run(&board.run(line)), "wrapped diagonal");
+        assert_eq!(
+            board.runs().count(),
+            30,
+            "20 runs which fit and 5 wrapped ones in each diagonal direction"
+        );
+        let almost: Board = "..O......".parse::<Board>().unwrap().toroidal(true);
+        assert!(almost
+            .win_length(2)
+            .is_winning_move(Cell::O, &BoardMove::try_new(4).unwrap()));
+
+        let board: Board<4> = ".O.........OO...".parse().unwrap();
+        let board = board.toroidal(true).win_length(3);
+        let line = board
+            .get_winning_line()
+            .expect("Diagonal run 12, 13, 2 wraps around both edges");
+        assert_eq!(board.run(line)[..], [11, 12, 1]);
+        assert_eq!(
+            board.terminal_outcome(),
+            Some(TerminalOutcome::Win(Cell::O))
+        );
+
+        let runs: HashSet<_> = board.runs().map(|run| run.to_vec()).collect();
+        assert_eq!(runs.len(), board.runs().count(), "Runs shouldn't repeat");
+    }
+
+    #[test]
+    fn full_lines_ignore_win_length() {
+        let board: Board = "OO.X.X...".parse().unwrap();
+        let short = board.win_length(2);
+
+        assert_eq!(short.lines().count(), 8, "Only full lines are listed");
+        assert!(!short.lines().any(|line| line == [Cell::O; 3]));
+        assert_eq!(
+            short.winning_lines_through(0),
+            board.winning_lines_through(0)
+        );
+        assert_eq!(Board::<3>::line(3), [0, 1, 2]);
+        assert_ne!(
+            short.run(short.get_winning_line().unwrap())[..],
+            Board::<3>::line(short.get_winning_line().unwrap())[..],
+            "Run indices don't index full lines"
+        );
+        assert!(
+            short.score_lines_for(Cell::O) > board.score_lines_for(Cell::O),
+            "The heuristic should count short runs, where 'O' has already won"
+        );
+    }
+
+    #[test]
+    fn place_marks() {
+        let mut board: Board = "O...X...#".parse().unwrap();
+
+        assert_eq!(
+            board.place(&BoardMove::try_new(2).unwrap(), Cell::X),
+            Ok(())
+        );
+        assert_eq!(board.to_string(), "OX..X...#");
+        assert_eq!(
+            board.place(&BoardMove::try_new(1).unwrap(), Cell::X),
+            Err(MoveError::CellTaken),
+            "Occupied cell can't be overwritten"
+        );
+        assert_eq!(
+            board.place(&BoardMove::try_new(9).unwrap(), Cell::O),
+            Err(MoveError::CellTaken),
+            "Blocked cell can't be used"
+        );
+        assert_eq!(
+            board.place(&BoardMove::try_new(3).unwrap(), Cell::Blocked),
+            Err(MoveError::NotAMark)
+        );
+        assert_eq!(
+            board.to_string(),
+            "OX..X...#",
+            "Rejected moves change nothing"
+        );
+
+        board.place_unchecked(&BoardMove::try_new(3).unwrap(), Cell::O);
+        assert_eq!(board.to_string(), "OXO.X...#");
+    }
+
+    #[test]
+    fn board_from_layout() {
+        let board = Board::from_layout(
+            "
+            O | X | 3
+            4 | O | #
+            X | . | 9
+            ",
+        );
+        assert_eq!(
+            board,
+            Ok(Board::from([
+                Cell::O,
+                Cell::X,
+                Cell::Empty('3'),
+                Cell::Empty('4'),
+                Cell::O,
+                Cell::Blocked,
+                Cell::X,
+                Cell::Empty('8'),
+                Cell::Empty('9'),
+            ]))
+        );
+
+        let rendered = "
+               +-----+-----+-----+
+               |  O  | [2] |  X  |
+               +-----+-----+-----+
+               | [4] |  O  | [6] |
+               +-----+-----+-----+
+               | [7] | [8] |  X  |
+               +-----+-----+-----+";
+        assert_eq!(
+            Board::<3>::from_layout(rendered),
+            "O.X.O...X".parse(),
+            "Drawn grid should be read back"
+        );
+
+        assert_eq!(
+            Board::<4>::from_layout("O . . .\n. X . .\n. . . .\n. . . #"),
+            "O....X.........#".parse()
+        );
+        assert_eq!(
+            Board::<3>::from_layout("O X .\n. O ."),
+            Err(()),
+            "Row is missing"
+        );
+        assert_eq!(
+            Board::<3>::from_layout("O X . .\n. O .\n. . ."),
+            Err(()),
+            "Rows must have the same length"
+        );
+        assert_eq!(Board::<3>::from_layout("O X .\n. Y .\n. . ."), Err(()));
+    }
+
+    #[test]
+    fn transcript_prefixes() {
+        let moves: Vec<_> = [5, 1, 9, 3, 2, 8, 7]
+            .into_iter()
+            .map(|cell| BoardMove::try_new(cell).unwrap())
+            .collect();
+
+        assert_eq!(Board::from_transcript_prefix(&moves, 0), Ok(Board::new()));
+        assert_eq!(
+            Board::from_transcript_prefix(&moves, 3)
+                .unwrap()
+                .to_string(),
+            "X...O...O"
+        );
+        assert_eq!(
+            Board::from_transcript_prefix(&moves, moves.len())
+                .unwrap()
+                .to_string(),
+            "XOX.O.OXO"
+        );
+        assert_eq!(
+            Board::from_transcript_prefix(&moves, 100),
+            Board::from_moves(&moves),
+            "Prefix longer than the transcript is the whole game"
+        );
+
+        let mut illegal = moves.clone();
+        illegal[4] = BoardMove::try_new(1).unwrap();
+        assert!(
+            Board::from_transcript_prefix(&illegal, 4).is_ok(),
+            "Moves after the prefix aren't checked"
+        );
+        assert_eq!(
+            Board::from_transcript_prefix(&illegal, 5),
+            Err(MoveError::CellTaken)
+        );
+
+        let past_win: Vec<_> = [1, 4, 2, 5, 3, 6]
+            .into_iter()
+            .map(|cell| BoardMove::try_new(cell).unwrap())
+            .collect();
+        assert_eq!(
+            Board::from_transcript_prefix(&past_win, 6),
+            Err(MoveError::GameOver)
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "is not empty")]
+    fn place_unchecked_on_taken_cell() {
+        let mut board: Board = "O........".parse().unwrap();
+
+        board.place_unchecked(&BoardMove::try_new(1).unwrap(), Cell::X);
+    }
+
+    #[test]
+    fn winning_line_for_symbol() {
+        let o_wins: Board = "OXXOX.O..".parse().unwrap();
+        assert_eq!(
+            o_wins.winning_line_for(Cell::O),
+            Some(0),
+            "'O' has the left column"
+        );
+        assert_eq!(o_wins.winning_line_for(Cell::X), None);
+
+        let x_wins: Board = "OO.XXXO..".parse().unwrap();
+        assert_eq!(
+            x_wins.winning_line_for(Cell::X),
+            Some(4),
+            "'X' has the middle row"
+        );
+        assert_eq!(x_wins.winning_line_for(Cell::O), None);
+
+        assert_eq!(
+            "#########"
+                .parse::<Board>()
+                .unwrap()
+                .winning_line_for(Cell::Blocked),
+            None,
+            "Blocked cells never win"
+        );
+        assert_eq!(Board::new().winning_line_for(Cell::Empty('1')), None);
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let board: Board = "OX.#O...X".parse().unwrap();
+        let rows: Vec<_> = board.rows().collect();
+        let columns: Vec<_> = board.columns().collect();
+
+        assert_eq!(
+            rows,
+            vec![
+                [Cell::O, Cell::X, Cell::Empty('3')],
+                [Cell::Blocked, Cell::O, Cell::Empty('6')],
+                [Cell::Empty('7'), Cell::Empty('8'), Cell::X],
+            ]
+        );
+        assert_eq!(
+            columns,
+            vec![
+                [Cell::O, Cell::Blocked, Cell::Empty('7')],
+                [Cell::X, Cell::O, Cell::Empty('8')],
+                [Cell::Empty('3'), Cell::Empty('6'), Cell::X],
+            ]
+        );
+
+        let board: Board<4> = "O..X............".parse().unwrap();
+        assert_eq!(board.rows().count(), 4);
+        assert_eq!(
+            board.columns().last(),
+            Some([
+                Cell::X,
+                Cell::Empty('8'),
+                Cell::Empty('c'),
+                Cell::Empty('g')
+            ]),
+            "Columns should follow the board width"
+        );
+    }
+
+    #[test]
+    fn almost_winning_lines() {
+        let single: Board = "O...O..X.".parse().unwrap();
+        assert_eq!(
+            single.almost_winning_lines(Cell::O),
+            vec![6],
+            "'O' threatens the main diagonal"
+        );
+        assert_eq!(single.almost_winning_lines(Cell::X), vec![]);
+
+        let fork: Board = "O.O.X.O.X".parse().unwrap();
+        assert_eq!(
+            fork.almost_winning_lines(Cell::O),
+            vec![0, 3],
+            "'O' threatens both the left column and the top row"
+        );
+
+        let none: Board = "OX.XO...X".parse().unwrap();
+        assert_eq!(
+            none.almost_winning_lines(Cell::O),
+            vec![],
+            "Blocked diagonal isn't a threat"
+        );
+        assert_eq!(none.almost_winning_lines(Cell::Blocked), vec![]);
+
+        let blocked: Board = "OO#......".parse().unwrap();
+        assert_eq!(
+            blocked.almost_winning_lines(Cell::O),
+            vec![],
+            "Blocked cell can't complete a line"
+        );
+
+        let mut large = Board::<4>::default();
+        for index in [0, 1, 2] {
+            large[index] = Cell::X;
+        }
+        assert_eq!(large.almost_winning_lines(Cell::X), vec![4]);
+    }
+
+    #[test]
+    fn winning_lines_through_cells() {
+        let board = Board::new();
+
+        assert_eq!(
+            board.winning_lines_through(4),
+            &[1, 4, 6, 7],
+            "Center lies on the middle column, the middle row and both diagonals"
+        );
+        assert_eq!(
+            board.winning_lines_through(8),
+            &[2, 5, 6],
+            "Corner lies on a column, a row and the main diagonal"
+        );
+
+        fn check<const N: usize>(board: Board<N>) {
+            for cell in 0..N * N {
+                let expected: Vec<_> = (0..board.line_indices().len())
+                    .filter(|&line| Board::<N>::line(line).contains(&cell))
+                    .collect();
+                assert_eq!(
+                    board.winning_lines_through(cell),
+                    expected,
+                    "Cell {cell} of {N}x{N} board (toroidal: {})",
+                    board.is_toroidal()
+                );
+            }
+        }
+        for toroidal in [false, true] {
+            check(Board::<2>::default().toroidal(toroidal));
+            check(Board::<3>::default().toroidal(toroidal));
+            check(Board::<5>::default().toroidal(toroidal));
+            check(Board::<9>::default().toroidal(toroidal));
+        }
+    }
+
+    #[test]
+    fn toroidal_lines() {
+        let standard: Board = "..OO...O.".parse().unwrap();
+        let toroidal = standard.toroidal(true);
+
+        assert_eq!(
+            standard.get_winning_line(),
+            None,
+            "Cells 3, 4 and 8 don't form a line on a regular board"
+        );
+        let line = toroidal
+            .get_winning_line()
+            .expect("Diagonal wraps around on a toroidal board");
+        assert_eq!(Board::<3>::line(line), [2, 3, 7]);
+        assert_eq!(describe_line(line), "wrapped diagonal");
+
+        let almost: Board = "..O....O.".parse().unwrap();
+        let board_move = BoardMove::try_new(4).unwrap();
+        assert!(!almost.is_winning_move(Cell::O, &board_move));
+        assert!(almost.toroidal(true).is_winning_move(Cell::O, &board_move));
+
+        assert!(
+            toroidal.rotated().is_toroidal(),
+            "Transformed board should stay toroidal"
+        );
+
+        let mut board = Board::<4>::default().toroidal(true);
+        for index in [1, 6, 11, 12] {
+            board[index] = Cell::X;
+        }
+        assert_eq!(board.winning_lines().len(), 1);
+        assert_eq!(
+            Board::<4>::default().toroidal(true).lines().count(),
+            16,
+            "Every cell starts a row, a column and two diagonals on a torus"
+        );
+    }
+
+    #[test]
+    fn winning_move() {
+        let board = Board::from([
+            Cell::O,
+            Cell::X,
+            Cell::Empty('3'),
+            Cell::O,
+            Cell::X,
+            Cell::Empty('6'),
+            Cell::Empty('7'),
+            Cell::Empty('8'),
+            Cell::Empty('9'),
+        ]);
+
+        assert!(
+            board.is_winning_move(Cell::O, &BoardMove::try_new(7).unwrap()),
+            "'O' in cell 7 completes the first column"
+        );
+        assert!(
+            board.is_winning_move(Cell::X, &BoardMove::try_new(8).unwrap()),
+            "'X' in cell 8 completes the second column"
+        );
+        assert!(
+            !board.is_winning_move(Cell::X, &BoardMove::try_new(7).unwrap()),
+            "'X' in cell 7 doesn't complete any line"
+        );
+        assert!(
+            !board.is_winning_move(Cell::O, &BoardMove::try_new(9).unwrap()),
+            "'O' in cell 9 doesn't complete any line"
+        );
+        assert!(
+            !board.is_winning_move(Cell::O, &BoardMove::try_new(1).unwrap()),
+            "Move on occupied cell can't be a winning move"
+        );
+    }
+
+    #[test]
+    fn parse_board() {
+        let board: Board = "o...X...#".parse().unwrap();
+
+        assert_eq!(board[0], Cell::O);
+        assert_eq!(board[4], Cell::X);
+        assert_eq!(board[8], Cell::Blocked);
+        assert_eq!(
+            board[1],
+            Cell::Empty('2'),
+            "Empty cells should keep their labels"
+        );
+        assert_eq!(board.to_string(), "O...X...#");
+
+        assert_eq!("".parse::<Board>(), Err(()), "Board must have 9 cells");
+        assert_eq!(
+            "O...X...".parse::<Board>(),
+            Err(()),
+            "Board must have 9 cells"
+        );
+        assert_eq!("O...X...?".parse::<Board>(), Err(()), "Unknown cell symbol");
+    }
+
+    #[test]
+    fn board_from_cells() {
+        let mut cells: [Cell; 9] = std::array::from_fn(|index| Board::new()[index]);
+        cells[0] = Cell::O;
+        cells[4] = Cell::X;
+        cells[8] = Cell::Blocked;
+
+        let board: Board = cells.into();
+
+        assert_eq!(board, Board::from(cells), "Both conversions should agree");
+        assert_eq!(
+            board,
+            "O...X...#".parse().unwrap(),
+            "Conversion from cells should match the parsed board"
+        );
+    }
+
+    #[test]
+    fn drawn_positions() {
+        assert!(!Board::new().is_drawn(), "Empty board isn't drawn");
+        assert!(
+            "XOX.XOOXO".parse::<Board>().unwrap().is_drawn(),
+            "Every line is contested by both players"
+        );
+        assert!(
+            !"XOX.XO.XO".parse::<Board>().unwrap().is_drawn(),
+            "'X' can still complete the left column"
+        );
+        assert!(
+            "#.#.#.#.#".parse::<Board>().unwrap().is_drawn(),
+            "Every line contains a blocked cell"
+        );
+    }
+
+    #[test]
+    fn board_diff() {
+        let board: Board = "O...X....".parse().unwrap();
+
+        assert!(
+            board.diff(&board).is_empty(),
+            "Identical boards shouldn't differ"
+        );
+
+        let mut next_board = board;
+        next_board[8] = Cell::O;
+        assert_eq!(
+            board.diff(&next_board),
+            vec![(8, Cell::Empty('9'), Cell::O)],
+            "Only the played cell should change"
+        );
+
+        let other_board: Board = "X...O...#".parse().unwrap();
+        assert_eq!(
+            board.diff(&other_board),
+            vec![
+                (0, Cell::O, Cell::X),
+                (4, Cell::X, Cell::O),
+                (8, Cell::Empty('9'), Cell::Blocked),
+            ],
+            "Every changed cell should be listed with old and new values"
+        );
+    }
+
+    #[test]
+    fn remaining_winnable_lines() {
+        let board = Board::new();
+        assert_eq!(
+            board.remaining_winnable_for(Cell::O),
+            8,
+            "Every line is open on an empty board"
+        );
+        assert_eq!(board.remaining_winnable_for(Cell::X), 8);
+
+        let board: Board = "X...O...#".parse().unwrap();
+        assert_eq!(
+            board.remaining_winnable_for(Cell::O),
+            3,
+            "Lines through 'X' or the blocked cell are lost for 'O'"
+        );
+        assert_eq!(
+            board.remaining_winnable_for(Cell::X),
+            2,
+            "Lines through 'O' or the blocked cell are lost for 'X'"
+        );
+
+        let board: Board = "XOX.XOOXO".parse().unwrap();
+        assert_eq!(
+            board.remaining_winnable_for(Cell::O),
+            0,
+            "Every line is contested on a drawn board"
+        );
+        assert_eq!(board.remaining_winnable_for(Cell::X), 0);
+    }
+
+    #[test]
+    fn legal_positions() {
+        let legal = [
+            ".........",
+            "O........",
+            "O...X....",
+            "OOOXX....",
+            "OO.XXX.O.",
+        ];
+        let illegal = [
+            "X........", // 'O' always goes first
+            "OO.......", // 'X' skipped a turn
+            "OOOXXX...", // Both players have won
+            "OOOXXX.O.", // Both players have won
+            "OOOXXX..O", // Both players have won
+            "OOOXX.X..", // 'X' moved after 'O' had won
+            "XXXOO.OO.", // 'O' moved after 'X' had won
+        ];
+
+        for board in legal {
+            assert!(
+                board.parse::<Board>().unwrap().is_legal_position(),
+                "{board} should be legal"
+            );
+        }
+        for board in illegal {
+            assert!(
+                !board.parse::<Board>().unwrap().is_legal_position(),
+                "{board} should be illegal"
+            );
+        }
+    }
+
+    #[test]
+    fn line_cell_values() {
+        let board: Board = "OX.#O..XO".parse().unwrap();
+        let e = Cell::Empty;
+
+        let expected = vec![
+            [Cell::O, Cell::Blocked, e('7')], // 1st column
+            [Cell::X, Cell::O, Cell::X],      // 2nd column
+            [e('3'), e('6'), Cell::O],        // 3rd column
+            [Cell::O, Cell::X, e('3')],       // 1st row
+            [Cell::Blocked, Cell::O, e('6')], // 2nd row
+            [e('7'), Cell::X, Cell::O],       // 3rd row
+            [Cell::O, Cell::O, Cell::O],      // main diagonal
+            [e('3'), Cell::O, e('7')],        // secondary diagonal
+        ];
+
+        assert_eq!(board.lines().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn line_scores() {
+        let board = Board::new();
+
+        assert_eq!(board.score_lines_for(Cell::O), 0, "Empty board is neutral");
+        assert_eq!(board.score_lines_for(Cell::X), 0, "Empty board is neutral");
+
+        let mut board = Board::new();
+        board[0] = Cell::O;
+        board[8] = Cell::X;
+
+        assert_eq!(
+            board.score_lines_for(Cell::O),
+            0,
+            "Symmetric position should be neutral"
+        );
+
+        let mut center = Board::new();
+        center[4] = Cell::O;
+        let mut corner = Board::new();
+        corner[0] = Cell::O;
+
+        assert!(
+            center.score_lines_for(Cell::O) > corner.score_lines_for(Cell::O),
+            "Center should be worth more than a corner"
+        );
+        assert_eq!(
+            center.score_lines_for(Cell::X),
+            -center.score_lines_for(Cell::O),
+            "Score should be symmetric between players"
+        );
+
+        let threat = Board::from([
+            Cell::O,
+            Cell::O,
+            Cell::Empty('3'),
+            Cell::X,
+            Cell::Empty('5'),
+            Cell::Empty('6'),
+            Cell::Empty('7'),
+            Cell::Empty('8'),
+            Cell::Empty('9'),
+        ]);
+
+        assert!(
+            threat.score_lines_for(Cell::O) > 0,
+            "Two in an open row should be good for 'O'"
+        );
+        assert!(
+            threat.score_lines_for(Cell::O) > board.score_lines_for(Cell::O),
+            "Threatening position should score better than a neutral one"
+        );
+    }
+
+    #[test]
+    fn rotation_and_reflection() {
+        let mut board = Board::new();
+        board[0] = Cell::O;
+        board[1] = Cell::X;
+
+        let rotated = board.rotated();
+
+        assert_eq!(
+            rotated[2],
+            Cell::O,
+            "Top left corner should become top right"
+        );
+        assert_eq!(rotated[5], Cell::X, "Top edge should become right edge");
+        assert_eq!(
+            rotated[0],
+            Cell::Empty('1'),
+            "Empty cells should be labelled by their new position"
+        );
+        assert_eq!(
+            board.rotated().rotated().rotated().rotated(),
+            board,
+            "Four rotations should give the original board"
+        );
+
+        let mirrored = board.mirrored();
+
+        assert_eq!(
+            mirrored[2],
+            Cell::O,
+            "Top left corner should become top right"
+        );
+        assert_eq!(mirrored[1], Cell::X, "Middle column should stay in place");
+        assert_eq!(
+            board.mirrored().mirrored(),
+            board,
+            "Mirroring twice should give the original board"
+        );
+    }
+
+    #[test]
+    fn symmetry_group() {
+        let distinct_images = |board: Board| {
+            board
+                .symmetry_group()
+                .iter()
+                .map(|image| image.id())
+                .collect::<HashSet<_>>()
+                .len()
+        };
+
+        assert_eq!(
+            distinct_images("O........".parse().unwrap()),
+            4,
+            "A corner can go to any of the four corners"
+        );
+        assert_eq!(
+            distinct_images(Board::new()),
+            1,
+            "Empty board looks the same from every side"
+        );
+        assert_eq!(
+            distinct_images("OX.......".parse().unwrap()),
+            8,
+            "Corner and edge together break every symmetry"
+        );
+        assert!(
+            Board::new()
+                .symmetry_group()
+                .iter()
+                .all(|&image| image == Board::new()),
+            "Images of the empty board should have their cells relabelled"
+        );
+    }
+
+    #[test]
+    fn canonical_form() {
+        let corners: Vec<_> = [0, 2, 6, 8]
+            .into_iter()
+            .map(|index| {
+                let mut board = Board::new();
+                board[index] = Cell::O;
+                board.canonical()
+            })
+            .collect();
+
+        assert!(
+            corners.iter().all(|&board| board == corners[0]),
+            "All single-corner positions should share one canonical form"
+        );
+
+        let mut center = Board::new();
+        center[4] = Cell::O;
+
+        assert_ne!(
+            center.canonical(),
+            corners[0],
+            "Center and corner positions aren't symmetric"
+        );
+        assert_eq!(
+            Board::new().canonical(),
+            Board::new(),
+            "Empty board is its own canonical form"
+        );
+    }
+
+    #[test]
+    fn reachable_position_counts() {
+        assert_eq!(
+            Board::count_reachable_positions(false),
+            5478,
+            "There are 5478 legal positions in Tic-Tac-Toe"
+        );
+        assert_eq!(
+            Board::count_reachable_positions(true),
+            765,
+            "There are 765 positions up to rotation and reflection"
+        );
     }
 
     #[test]
@@ -0,0 +1,169 @@
+//! This module contains generation of winning lines for boards of any size, where a line of `k`
+//! marks in a row, column or diagonal wins. Also provides a validator, which guards against
+//! generation bugs that would silently break win detection.
+
+use std::collections::HashSet;
+
+/// Generates all winning lines of length `k` on a `width` x `height` board. Cells are numbered row
+/// by row, starting from 0. Lines are ordered like `WINNING_LINES`: columns first, then rows, then
+/// main diagonals and finally secondary diagonals. There are no lines shorter than 2 marks, since a
+/// single mark would lie on every direction at once.
+///
+/// # Examples
+///
+/// ```
+/// use tic_tac_terminal::board::{lines, WINNING_LINES};
+///
+/// let lines = lines::generate(3, 3, 3);
+///
+/// assert_eq!(lines.len(), 8);
+/// assert!(lines.iter().zip(WINNING_LINES).all(|(line, expected)| line[..] == expected));
+/// ```
+pub fn generate(width: usize, height: usize, k: usize) -> Vec<Vec<usize>> {
+    if k < 2 || k > width || k > height {
+        return vec![];
+    }
+
+    let index = |row: usize, column: usize| row * width + column;
+    let mut lines = vec![];
+
+    for column in 0..width {
+        for row in 0..=height - k {
+            lines.push((0..k).map(|i| index(row + i, column)).collect());
+        }
+    }
+    for row in 0..height {
+        for column in 0..=width - k {
+            lines.push((0..k).map(|i| index(row, column + i)).collect());
+        }
+    }
+    for row in 0..=height - k {
+        for column in 0..=width - k {
+            lines.push((0..k).map(|i| index(row + i, column + i)).collect());
+        }
+    }
+    for row in 0..=height - k {
+        for column in k - 1..width {
+            lines.push((0..k).map(|i| index(row + i, column - i)).collect());
+        }
+    }
+
+    debug_assert!(
+        is_valid(&lines, width, height, k),
+        "Generated winning lines are invalid"
+    );
+    lines
+}
+
+/// Returns the number of winning lines of length `k` on a `width` x `height` board.
+pub fn expected_count(width: usize, height: usize, k: usize) -> usize {
+    if k < 2 || k > width || k > height {
+        return 0;
+    }
+
+    let (horizontal_fits, vertical_fits) = (width - k + 1, height - k + 1);
+    width * vertical_fits + height * horizontal_fits + 2 * horizontal_fits * vertical_fits
+}
+
+/// Checks that every line has `k` distinct cells within the board, which lie next to each other
+/// in a single row, column or diagonal, that no line is repeated and that the number of lines
+/// matches `expected_count`.
+pub fn is_valid(lines: &[Vec<usize>], width: usize, height: usize, k: usize) -> bool {
+    let cell_count = width * height;
+    let position = |index: usize| ((index / width) as isize, (index % width) as isize);
+
+    let line_is_valid = |line: &Vec<usize>| {
+        if k < 2 || line.len() != k || line.iter().any(|&index| index >= cell_count) {
+            return false;
+        }
+
+        let (first_row, first_column) = position(line[0]);
+        let (second_row, second_column) = position(line[1]);
+        let step = (second_row - first_row, second_column - first_column);
+        if ![(0, 1), (1, 0), (1, 1), (1, -1)].contains(&step) {
+            return false;
+        }
+
+        line.windows(2).all(|pair| {
+            let (row, column) = position(pair[0]);
+            let (next_row, next_column) = position(pair[1]);
+            (next_row - row, next_column - column) == step
+        })
+    };
+
+    let unique_lines: HashSet<_> = lines.iter().collect();
+
+    lines.len() == expected_count(width, height, k)
+        && unique_lines.len() == lines.len()
+        && lines.iter().all(line_is_valid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::WINNING_LINES;
+
+    #[test]
+    fn classic_board_lines() {
+        let lines = generate(3, 3, 3);
+
+        assert_eq!(lines.len(), 8, "3x3 board has 8 winning lines");
+        assert_eq!(
+            lines,
+            WINNING_LINES.map(Vec::from),
+            "Generated lines should match the classic ones"
+        );
+        assert!(is_valid(&lines, 3, 3, 3));
+    }
+
+    #[test]
+    fn larger_board_lines() {
+        let lines = generate(4, 4, 4);
+
+        assert_eq!(
+            lines.len(),
+            10,
+            "4x4 board with 4 in a row has 4 rows, 4 columns and 2 diagonals"
+        );
+        assert!(is_valid(&lines, 4, 4, 4));
+
+        assert_eq!(generate(4, 4, 3).len(), 24);
+        assert_eq!(
+            generate(5, 4, 4).len(),
+            17,
+            "5 columns, 8 rows and 4 diagonals"
+        );
+        assert!(is_valid(&generate(5, 4, 4), 5, 4, 4));
+        assert!(
+            generate(3, 3, 4).is_empty(),
+            "Line can't be longer than the board"
+        );
+        assert!(generate(3, 3, 1).is_empty(), "Single cell isn't a line");
+        assert_eq!(expected_count(3, 3, 1), 0);
+    }
+
+    #[test]
+    fn invalid_lines_are_detected() {
+        let valid = generate(3, 3, 3);
+
+        let mut wrong_count = valid.clone();
+        wrong_count.pop();
+        assert!(!is_valid(&wrong_count, 3, 3, 3), "Line is missing");
+
+        let mut out_of_bounds = valid.clone();
+        out_of_bounds[0] = vec![0, 3, 9];
+        assert!(!is_valid(&out_of_bounds, 3, 3, 3), "Cell is out of bounds");
+
+        let mut wrapping = valid.clone();
+        wrapping[0] = vec![1, 2, 3];
+        assert!(!is_valid(&wrapping, 3, 3, 3), "Row can't wrap around");
+
+        let mut too_short = valid.clone();
+        too_short[0] = vec![0, 3];
+        assert!(!is_valid(&too_short, 3, 3, 3), "Line is too short");
+
+        let mut duplicated = valid;
+        duplicated[0] = duplicated[1].clone();
+        assert!(!is_valid(&duplicated, 3, 3, 3), "Line is repeated");
+    }
+}
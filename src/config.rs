@@ -0,0 +1,415 @@
+//! This module contains the config file, which sets default options for the terminal UI and the
+//! game. The file uses a small subset of TOML: one `key = value` pair per line, where values are
+//! `true`/`false`, whole numbers or quoted strings. Lines starting with `#` are comments.
+//! Lists of cells are quoted strings of cell numbers, counting from 1 like moves do.
+//!
+//! ```text
+//! # Show coordinates and keep a log of results
+//! coordinates = true
+//! results_log = "results.log"
+//! ```
+
+use crate::player::Difficulty;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Default options read from a config file. Options which are not set keep their usual defaults.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Config {
+    // Terminal UI
+    pub styling: Option<bool>,
+    pub coordinates: Option<bool>,
+    pub hide_labels: Option<bool>,
+    pub spotlight_win: Option<bool>,
+    pub legend: Option<bool>,
+    pub status_line: Option<bool>,
+    pub scroll: Option<bool>,
+    pub alternate_screen: Option<bool>,
+    pub narration: Option<bool>,
+    pub explain: Option<bool>,
+    pub line_editing: Option<bool>,
+    pub retry_limit: Option<u32>,
+    // Game
+    pub toroidal: Option<bool>,
+    pub win_length: Option<usize>, // Marks in a row which win, 2 or 3
+    pub kibitz: Option<bool>,
+    pub review: Option<bool>,
+    pub first_to: Option<i32>,
+    pub entertainer: Option<bool>,
+    pub cpu_preference: Option<Vec<usize>>, // Cell indices, most preferred first
+    pub blitz: Option<u64>,                 // Seconds in each player's time bank
+    pub seed: Option<u64>,
+    pub faster_wins: Option<bool>,
+    pub draw_points: Option<i32>,
+    pub draw_is_loss: Option<bool>,
+    pub results_log: Option<PathBuf>,
+    pub state_dump: Option<PathBuf>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ConfigError {
+    Unreadable(String),
+    Syntax(usize),               // Line number, counting from 1
+    UnknownKey(usize, String),   // Line number and the key
+    InvalidValue(usize, String), // Line number and the key
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unreadable(reason) => write!(f, "Couldn't read the config file: {reason}"),
+            Self::Syntax(line) => write!(f, "Line {line} should look like `key = value`"),
+            Self::UnknownKey(line, key) => write!(f, "Unknown option `{key}` on line {line}"),
+            Self::InvalidValue(line, key) => {
+                write!(f, "Invalid value of option `{key}` on line {line}")
+            }
+        }
+    }
+}
+
+impl Config {
+    /// Reads the config from a file.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tic_tac_terminal::{Config, TerminalUi, TicTacToe};
+    ///
+    /// let config = Config::load("tic-tac-terminal.toml").unwrap();
+    /// let ui = TerminalUi::with_config(&config);
+    /// let game = TicTacToe::with_config(&ui, &config);
+    /// ```
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        fs::read_to_string(path)
+            .map_err(|error| ConfigError::Unreadable(error.to_string()))?
+            .parse()
+    }
+
+    /// Parses the cells the CPU prefers, e.g. "5, 1, 3, 7, 9" for center, then corners. Cells are
+    /// numbered from 1 like moves, but come back as cell indices. Returns `None` unless every
+    /// cell is on the board and listed once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::Config;
+    ///
+    /// assert_eq!(Config::parse_cpu_preference("[5, 1, 9]"), Some(vec![4, 0, 8]));
+    /// assert_eq!(Config::parse_cpu_preference("5 5"), None);
+    /// ```
+    pub fn parse_cpu_preference(cells: &str) -> Option<Vec<usize>> {
+        let is_separator = |c: char| c == ',' || c == '[' || c == ']' || c.is_whitespace();
+        let preference = cells
+            .split(is_separator)
+            .filter(|cell| !cell.is_empty())
+            .map(|cell| cell.parse::<usize>().ok()?.checked_sub(1))
+            .collect::<Option<Vec<_>>>()?;
+
+        Difficulty::is_valid_preference(&preference).then_some(preference)
+    }
+
+    /// Parses how many marks in a row win. Returns `None` unless it's a length the board allows,
+    /// from 2 to a full line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::Config;
+    ///
+    /// assert_eq!(Config::parse_win_length("2"), Some(2));
+    /// assert_eq!(Config::parse_win_length("1"), None);
+    /// ```
+    pub fn parse_win_length(value: &str) -> Option<usize> {
+        let win_length = value.parse().ok()?;
+
+        (2..=3).contains(&win_length).then_some(win_length)
+    }
+}
+
+/// Parses the contents of a config file.
+///
+/// # Examples
+///
+/// ```
+/// use tic_tac_terminal::Config;
+///
+/// let config: Config = "scroll = true\nretry_limit = 3".parse().unwrap();
+///
+/// assert_eq!(config.scroll, Some(true));
+/// assert_eq!(config.retry_limit, Some(3));
+/// assert_eq!(config.coordinates, None);
+/// ```
+impl FromStr for Config {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut config = Config::default();
+
+        for (number, line) in (1..).zip(s.lines()) {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line.split_once('=').ok_or(ConfigError::Syntax(number))?;
+            let (key, value) = (key.trim(), value.trim());
+            let invalid = || ConfigError::InvalidValue(number, key.to_string());
+
+            match key {
+                "styling" => config.styling = Some(parse_bool(value).ok_or_else(invalid)?),
+                "coordinates" => config.coordinates = Some(parse_bool(value).ok_or_else(invalid)?),
+                "hide_labels" => config.hide_labels = Some(parse_bool(value).ok_or_else(invalid)?),
+                "spotlight_win" => {
+                    config.spotlight_win = Some(parse_bool(value).ok_or_else(invalid)?)
+                }
+                "legend" => config.legend = Some(parse_bool(value).ok_or_else(invalid)?),
+                "status_line" => config.status_line = Some(parse_bool(value).ok_or_else(invalid)?),
+                "scroll" => config.scroll = Some(parse_bool(value).ok_or_else(invalid)?),
+                "alternate_screen" => {
+                    config.alternate_screen = Some(parse_bool(value).ok_or_else(invalid)?)
+                }
+                "narration" => config.narration = Some(parse_bool(value).ok_or_else(invalid)?),
+                "explain" => config.explain = Some(parse_bool(value).ok_or_else(invalid)?),
+                "line_editing" => {
+                    config.line_editing = Some(parse_bool(value).ok_or_else(invalid)?)
+                }
+                "retry_limit" => config.retry_limit = Some(value.parse().map_err(|_| invalid())?),
+                "toroidal" => config.toroidal = Some(parse_bool(value).ok_or_else(invalid)?),
+                "win_length" => {
+                    config.win_length = Some(Self::parse_win_length(value).ok_or_else(invalid)?)
+                }
+                "kibitz" => config.kibitz = Some(parse_bool(value).ok_or_else(invalid)?),
+                "review" => config.review = Some(parse_bool(value).ok_or_else(invalid)?),
+                "first_to" => config.first_to = Some(value.parse().map_err(|_| invalid())?),
+                "entertainer" => config.entertainer = Some(parse_bool(value).ok_or_else(invalid)?),
+                "cpu_preference" => {
+                    let cells = parse_string(value).ok_or_else(invalid)?;
+                    config.cpu_preference =
+                        Some(Self::parse_cpu_preference(&cells).ok_or_else(invalid)?);
+                }
+                "blitz" => config.blitz = Some(value.parse().map_err(|_| invalid())?),
+                "seed" => config.seed = Some(value.parse().map_err(|_| invalid())?),
+                "faster_wins" => config.faster_wins = Some(parse_bool(value).ok_or_else(invalid)?),
+                "draw_points" => config.draw_points = Some(value.parse().map_err(|_| invalid())?),
+                "draw_is_loss" => {
+                    config.draw_is_loss = Some(parse_bool(value).ok_or_else(invalid)?)
+                }
+                "results_log" => {
+                    config.results_log = Some(parse_string(value).ok_or_else(invalid)?.into())
+                }
+                "state_dump" => {
+                    config.state_dump = Some(parse_string(value).ok_or_else(invalid)?.into())
+                }
+                _ => return Err(ConfigError::UnknownKey(number, key.to_string())),
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+/// Writes the options which are set in the config file format, so that the output can be parsed
+/// back into the same config.
+impl fmt::Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flags = [
+            ("styling", self.styling),
+            ("coordinates", self.coordinates),
+            ("hide_labels", self.hide_labels),
+            ("spotlight_win", self.spotlight_win),
+            ("legend", self.legend),
+            ("status_line", self.status_line),
+            ("scroll", self.scroll),
+            ("alternate_screen", self.alternate_screen),
+            ("narration", self.narration),
+            ("explain", self.explain),
+            ("line_editing", self.line_editing),
+        ];
+        for (key, value) in flags {
+            if let Some(value) = value {
+                writeln!(f, "{key} = {value}")?;
+            }
+        }
+        if let Some(retry_limit) = self.retry_limit {
+            writeln!(f, "retry_limit = {retry_limit}")?;
+        }
+        if let Some(toroidal) = self.toroidal {
+            writeln!(f, "toroidal = {toroidal}")?;
+        }
+        if let Some(win_length) = self.win_length {
+            writeln!(f, "win_length = {win_length}")?;
+        }
+        if let Some(kibitz) = self.kibitz {
+            writeln!(f, "kibitz = {kibitz}")?;
+        }
+        if let Some(review) = self.review {
+            writeln!(f, "review = {review}")?;
+        }
+        if let Some(first_to) = self.first_to {
+            writeln!(f, "first_to = {first_to}")?;
+        }
+        if let Some(entertainer) = self.entertainer {
+            writeln!(f, "entertainer = {entertainer}")?;
+        }
+        if let Some(preference) = &self.cpu_preference {
+            let cells: Vec<_> = preference.iter().map(|index| index + 1).collect();
+            writeln!(f, "cpu_preference = {}", quote(&format!("{cells:?}")))?;
+        }
+        if let Some(blitz) = self.blitz {
+            writeln!(f, "blitz = {blitz}")?;
+        }
+        if let Some(seed) = self.seed {
+            writeln!(f, "seed = {seed}")?;
+        }
+        if let Some(faster_wins) = self.faster_wins {
+            writeln!(f, "faster_wins = {faster_wins}")?;
+        }
+        if let Some(draw_points) = self.draw_points {
+            writeln!(f, "draw_points = {draw_points}")?;
+        }
+        if let Some(draw_is_loss) = self.draw_is_loss {
+            writeln!(f, "draw_is_loss = {draw_is_loss}")?;
+        }
+
+        let paths = [
+            ("results_log", &self.results_log),
+            ("state_dump", &self.state_dump),
+        ];
+        for (key, path) in paths {
+            if let Some(path) = path {
+                writeln!(f, "{key} = {}", quote(&path.to_string_lossy()))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parses a double-quoted string, in which `\"` and `\\` stand for a quote and a backslash.
+fn parse_string(value: &str) -> Option<String> {
+    let inner = value.strip_prefix('"')?.strip_suffix('"')?;
+
+    let mut parsed = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next()? {
+                escaped @ ('"' | '\\') => parsed.push(escaped),
+                _ => return None,
+            },
+            '"' => return None, // Unescaped quote ends the string early
+            _ => parsed.push(c),
+        }
+    }
+
+    Some(parsed)
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let config = Config {
+            styling: Some(false),
+            coordinates: Some(true),
+            spotlight_win: Some(true),
+            legend: Some(false),
+            status_line: Some(false),
+            alternate_screen: Some(true),
+            retry_limit: Some(3),
+            toroidal: Some(true),
+            win_length: Some(2),
+            kibitz: Some(false),
+            review: Some(true),
+            first_to: Some(3),
+            entertainer: Some(true),
+            cpu_preference: Some(vec![4, 0, 2, 6, 8]),
+            blitz: Some(90),
+            seed: Some(956),
+            draw_points: Some(-1),
+            draw_is_loss: Some(true),
+            results_log: Some(PathBuf::from(r#"C:\logs\"quoted" results.log"#)),
+            ..Default::default()
+        };
+
+        let written = config.to_string();
+        assert_eq!(
+            written.parse::<Config>(),
+            Ok(config),
+            "Written config should parse back into the same options:\n{written}"
+        );
+        assert_eq!(Config::default().to_string(), "", "Nothing is set");
+    }
+
+    #[test]
+    fn comments_and_missing_options() {
+        let config: Config = "\n# Comment\n  scroll = false  \nstate_dump = \"state.json\"\n"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            config,
+            Config {
+                scroll: Some(false),
+                state_dump: Some(PathBuf::from("state.json")),
+                ..Default::default()
+            },
+            "Options which aren't listed should stay unset"
+        );
+    }
+
+    #[test]
+    fn invalid_configs() {
+        assert_eq!(
+            "scroll = true\ncoordinates".parse::<Config>(),
+            Err(ConfigError::Syntax(2))
+        );
+        assert_eq!(
+            "colour = true".parse::<Config>(),
+            Err(ConfigError::UnknownKey(1, String::from("colour")))
+        );
+        assert_eq!(
+            "scroll = yes".parse::<Config>(),
+            Err(ConfigError::InvalidValue(1, String::from("scroll")))
+        );
+        assert_eq!(
+            "retry_limit = -3".parse::<Config>(),
+            Err(ConfigError::InvalidValue(1, String::from("retry_limit")))
+        );
+        assert_eq!(
+            "win_length = 4".parse::<Config>(),
+            Err(ConfigError::InvalidValue(1, String::from("win_length"))),
+            "Runs can't be longer than a line"
+        );
+        assert_eq!(
+            "cpu_preference = \"5 1 5\"".parse::<Config>(),
+            Err(ConfigError::InvalidValue(1, String::from("cpu_preference"))),
+            "Cell can't be preferred twice"
+        );
+        assert_eq!(
+            "results_log = results.log".parse::<Config>(),
+            Err(ConfigError::InvalidValue(1, String::from("results_log"))),
+            "Strings have to be quoted"
+        );
+        assert!(matches!(
+            Config::load("no/such/config.toml"),
+            Err(ConfigError::Unreadable(_))
+        ));
+    }
+}
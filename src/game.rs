@@ -1,16 +1,75 @@
 //! This module contains representation of single Tic-Tac-Toe game, as well as its result and
 //! current state.
 
-use crate::board::{Board, BoardMove, Cell, WINNING_LINES};
-use crate::player::Player;
+use crate::analysis;
+use crate::board::{self, Board, BoardMove, Cell, TerminalOutcome};
+use crate::player::{minimax, Player};
 use crate::ui::Ui;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::cell::RefCell;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::thread;
+use std::time::{Duration, Instant};
 
 pub type WinningLineIndex = usize;
 
+pub const DEFAULT_MOVE_DELAY: Duration = Duration::from_millis(200);
+
+/// Callback invoked after every move with the board after the move, the move itself and the
+/// number of moves played in the game so far. Shared, so that cloned games report to it too.
+pub type MoveHook<'a> = Rc<RefCell<dyn FnMut(&Board, BoardMove, usize) + 'a>>;
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum GameResult {
-    PlayerWon(usize, String, WinningLineIndex),
-    Draw,
+    /// Winner's index, name and every line completed by the winning move (usually just one, none
+    /// if the opponent forfeited)
+    PlayerWon(usize, String, Vec<WinningLineIndex>),
+    Draw(DrawReason),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DrawReason {
+    /// There are no empty cells left
+    BoardFull,
+    /// There are empty cells left, but no line can be completed anymore
+    Forced,
+}
+
+/// Reason for rejecting a move entered by the player.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InvalidMove {
+    NotAMove, // Neither a cell number nor coordinates
+    OutOfRange,
+    CellTaken,
+    NothingToUndo,
+}
+
+/// What the player decided to do on their turn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PlayerAction {
+    Move(BoardMove),
+    /// Takes back the player's last move (and the CPU's answer to it)
+    Undo,
+    Restart,
+    /// Gives up the game, so the opponent wins
+    Forfeit,
+    /// Asks for the best move in the current position
+    Hint,
+    OpenMenu,
+    /// Abandons the game and goes back to the main menu
+    Quit,
+}
+
+/// Time banks of both players in a blitz game. A player's bank only runs down during their own
+/// turns, and running out of time loses the game.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Clocks {
+    bank: Duration,
+    remaining: [Duration; 2],
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -18,99 +77,550 @@ enum GameState {
     NotStarted,
     Ongoing,
     Finished(GameResult),
+    Aborted,
 }
 
 pub struct Game<'a, T: Ui> {
     board: Board,
+    starting_board: Board,
     players: [&'a Player; 2],
     current_player: usize,
     game_state: GameState,
     ui: &'a T,
+    rng: StdRng,
+    move_delay: Duration,
+    move_count: usize,
+    history: Vec<BoardMove>,
+    state_dump: Option<PathBuf>,
+    kibitz: bool,
+    review: bool,
+    clocks: Option<Clocks>,
+    on_move: Option<MoveHook<'a>>,
+}
+
+impl GameResult {
+    /// Describes the result in words, naming the lines the game was won on. The final `board`
+    /// tells which cells the line indices stand for, as they depend on its win length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::{board::Board, GameResult};
+    ///
+    /// let board = Board::new();
+    ///
+    /// let result = GameResult::PlayerWon(0, String::from("Steve"), vec![3]);
+    /// assert_eq!(result.description(&board), "Steve won on the top row");
+    ///
+    /// let result = GameResult::PlayerWon(0, String::from("Steve"), vec![3, 6]);
+    /// assert_eq!(
+    ///     result.description(&board),
+    ///     "Steve won on the top row and the main diagonal"
+    /// );
+    /// ```
+    pub fn description(&self, board: &Board) -> String {
+        match self {
+            Self::PlayerWon(_winner_index, winner_name, winning_lines) => {
+                format!(
+                    "{winner_name} won on {}",
+                    describe_lines(board, winning_lines)
+                )
+            }
+            Self::Draw(_) => String::from("draw"),
+        }
+    }
+}
+
+/// Joins names of the lines the winning runs of the board lie on, e.g. "the top row and the main
+/// diagonal".
+pub fn describe_lines(board: &Board, winning_lines: &[WinningLineIndex]) -> String {
+    winning_lines
+        .iter()
+        .map(|&index| format!("the {}", board::describe_run(&board.run(index))))
+        .collect::<Vec<_>>()
+        .join(" and ")
+}
+
+/// Quotes the text as a JSON string literal.
+fn json_string(text: &str) -> String {
+    let mut quoted = String::from("\"");
+
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+impl Clocks {
+    fn new(bank: Duration) -> Self {
+        Self {
+            bank,
+            remaining: [bank; 2],
+        }
+    }
+
+    fn reset(&mut self) {
+        self.remaining = [self.bank; 2];
+    }
+
+    /// Deducts the time the player spent on their turn. Returns `false` if their time ran out.
+    fn charge(&mut self, player: usize, elapsed: Duration) -> bool {
+        self.remaining[player] = self.remaining[player].saturating_sub(elapsed);
+        !self.remaining[player].is_zero()
+    }
+}
+
+impl fmt::Display for InvalidMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            Self::NotAMove => "Your input must be a cell number or coordinates like b2",
+            Self::OutOfRange => "Your input must be a cell of the board",
+            Self::CellTaken => "This cell is not empty",
+            Self::NothingToUndo => "There is no move to undo",
+        };
+        write!(f, "{message}")
+    }
+}
+
+// Implemented manually, because deriving would require the UI itself to be `Clone`, while the
+// game only holds a shared reference to it
+impl<T: Ui> Clone for Game<'_, T> {
+    fn clone(&self) -> Self {
+        Self {
+            board: self.board,
+            starting_board: self.starting_board,
+            players: self.players,
+            current_player: self.current_player,
+            game_state: self.game_state.clone(),
+            ui: self.ui,
+            rng: self.rng.clone(),
+            move_delay: self.move_delay,
+            move_count: self.move_count,
+            history: self.history.clone(),
+            state_dump: self.state_dump.clone(),
+            kibitz: self.kibitz,
+            review: self.review,
+            clocks: self.clocks,
+            on_move: self.on_move.clone(),
+        }
+    }
 }
 
 impl<'a, T: Ui> Game<'a, T> {
     pub fn new(player1: &'a Player, player2: &'a Player, ui_backend: &'a T) -> Self {
         Self {
             board: Board::new(),
+            starting_board: Board::new(),
             players: [player1, player2],
             current_player: 0,
             game_state: GameState::NotStarted,
             ui: ui_backend,
+            rng: StdRng::from_entropy(),
+            move_delay: DEFAULT_MOVE_DELAY,
+            move_count: 0,
+            history: vec![],
+            state_dump: None,
+            kibitz: false,
+            review: false,
+            clocks: None,
+            on_move: None,
         }
     }
 
+    /// Makes the CPU's choices among equally good moves reproducible.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    /// Sets the pause before each CPU move, so the game can be followed on screen.
+    pub fn move_delay(mut self, move_delay: Duration) -> Self {
+        self.move_delay = move_delay;
+        self
+    }
+
+    /// Writes the current state of the game as JSON to the given file after every move, replacing
+    /// the previous state, so that external tools (e.g. stream overlays) can follow the game.
+    pub fn state_dump(mut self, path: impl AsRef<Path>) -> Self {
+        self.state_dump = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Judges every human move against the engine's, showing whether it was optimal and how it
+    /// changed the value of the position.
+    pub fn kibitz(mut self, kibitz: bool) -> Self {
+        self.kibitz = kibitz;
+        self
+    }
+
+    /// Reviews the game once it's over, listing every move of both players which wasn't one of
+    /// the best, together with better alternatives.
+    pub fn review(mut self, review: bool) -> Self {
+        self.review = review;
+        self
+    }
+
+    /// Plays blitz: each player gets the given time for the whole game, which runs down only
+    /// during their turns. Whoever runs out of time loses. Games are untimed by default.
+    pub fn time_bank(mut self, bank: Duration) -> Self {
+        self.clocks = Some(Clocks::new(bank));
+        self
+    }
+
+    /// Calls `hook` after every move with the board after the move, the move itself and the number
+    /// of moves played in the game so far, e.g. for logging or scripting.
+    pub fn on_move(mut self, hook: impl FnMut(&Board, BoardMove, usize) + 'a) -> Self {
+        self.on_move = Some(Rc::new(RefCell::new(hook)));
+        self
+    }
+
+    /// Starts the game from a given position instead of the empty board. The player to move is
+    /// inferred from the number of marks on the board.
+    pub fn starting_position(mut self, board: Board) -> Self {
+        self.starting_board = board;
+        self.restart();
+        self
+    }
+
     pub fn start(&mut self) -> Result<GameResult, ()> {
         if self.game_state == GameState::NotStarted {
             self.game_state = GameState::Ongoing;
+            self.ui
+                .notify_players(self.players[0].get_name(), self.players[1].get_name());
+            self.check_if_over(); // The starting position might already be decided
 
             while self.game_state == GameState::Ongoing {
-                self.take_turn();
-                self.check_if_over();
-
-                self.current_player = if self.current_player == 0 { 1 } else { 0 };
+                self.check_turn_limit();
+
+                let player = self.current_player;
+                let turn_start = Instant::now();
+                let moved = self.take_turn();
+                // The delay only paces the game for watchers, so it's not the CPU's thinking time
+                let thinking_time = turn_start.elapsed().saturating_sub(self.cpu_delay(player));
+
+                if self.game_state == GameState::Ongoing
+                    && !self.charge_clock(player, thinking_time)
+                {
+                    self.flag_fall(player);
+                } else if moved {
+                    self.check_if_over();
+
+                    self.current_player = if self.current_player == 0 { 1 } else { 0 };
+                }
             }
 
             self.announce_result();
         }
 
-        if let GameState::Finished(result) = &self.game_state {
-            Ok(result.clone())
+        self.result().ok_or(())
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Returns moves which the current player can make. Empty once the game is over.
+    pub fn available_moves(&self) -> Vec<BoardMove> {
+        if self.is_finished() || self.game_state == GameState::Aborted {
+            vec![]
         } else {
-            Err(())
+            self.board.get_possible_moves()
+        }
+    }
+
+    /// Checks whether the game has ended with a result. Games which were quit are not finished.
+    pub fn is_finished(&self) -> bool {
+        matches!(self.game_state, GameState::Finished(_))
+    }
+
+    /// Returns the result of the game, or `None` if it hasn't finished yet.
+    pub fn result(&self) -> Option<GameResult> {
+        match &self.game_state {
+            GameState::Finished(result) => Some(result.clone()),
+            _ => None,
         }
     }
 
-    fn take_turn(&mut self) {
-        let current_player = &self.players[self.current_player];
+    /// Returns the number of moves made in this game (not counting the starting position).
+    pub fn move_count(&self) -> usize {
+        self.move_count
+    }
+
+    /// Returns the number of turns the game can last at most, i.e. the number of free cells in
+    /// the starting position.
+    pub fn max_turns(&self) -> usize {
+        self.starting_board.get_possible_moves().len()
+    }
+
+    /// Guards against a game that never ends. Every turn fills a cell, so an ongoing game that has
+    /// used up all of them means the end of the game went undetected.
+    fn check_turn_limit(&self) {
+        assert!(
+            self.move_count < self.max_turns(),
+            "Game is still ongoing after {} moves, but the board only had {} free cells",
+            self.move_count,
+            self.max_turns()
+        );
+    }
+
+    /// Lets the current player act. Returns `true` if a move was made, or `false` if the turn
+    /// ended otherwise (undo, restart, forfeit or quitting the game).
+    fn take_turn(&mut self) -> bool {
+        let current_player = self.players[self.current_player];
 
+        self.ui.clear();
         self.ui.update_board(&self.board);
+        self.ui.notify_turn(self.move_count + 1, self.max_turns());
+        if let Some(clocks) = &self.clocks {
+            let [player1_time, player2_time] = clocks.remaining;
+            self.ui.notify_clocks(
+                self.players[0].get_name(),
+                player1_time,
+                self.players[1].get_name(),
+                player2_time,
+            );
+        }
 
-        let mut error_message = None;
+        thread::sleep(self.cpu_delay(self.current_player));
 
+        let mut menu_action = None;
         let board_move = loop {
-            let board_move = current_player.get_move(&self.board, self.ui, error_message);
-            if self.board.is_valid_move(&board_move) {
-                break board_move;
-            } else {
-                error_message = Some("this cell is not empty");
+            let action = menu_action
+                .take()
+                .unwrap_or_else(|| current_player.get_move(&self.board, self.ui, &mut self.rng));
+
+            match action {
+                PlayerAction::Move(board_move) if self.available_moves().contains(&board_move) => {
+                    break board_move
+                }
+                PlayerAction::Move(_) => self.ui.notify_invalid_move(InvalidMove::CellTaken),
+                PlayerAction::Undo if self.undo() => return false,
+                PlayerAction::Undo => self.ui.notify_invalid_move(InvalidMove::NothingToUndo),
+                PlayerAction::Restart => {
+                    self.restart();
+                    return false;
+                }
+                PlayerAction::Forfeit => {
+                    let winner = 1 - self.current_player;
+                    let winner_name = self.players[winner].get_name().to_owned();
+                    self.game_state =
+                        GameState::Finished(GameResult::PlayerWon(winner, winner_name, vec![]));
+                    return false;
+                }
+                PlayerAction::Hint => {
+                    if let Some(hint) = minimax::calculate_best_move(&self.board, &mut self.rng) {
+                        self.ui.notify_hint(&hint);
+                    }
+                }
+                PlayerAction::OpenMenu => {
+                    menu_action = self.ui.open_menu();
+                    if menu_action.is_none() {
+                        // Resumed, so bring the board back on screen
+                        self.ui.clear();
+                        self.ui.update_board(&self.board);
+                    }
+                }
+                PlayerAction::Quit => {
+                    self.game_state = GameState::Aborted;
+                    return false;
+                }
             }
         };
 
+        debug_event!(
+            "event=move player={} cell={} move_number={}",
+            current_player.get_name(),
+            board_move.index() + 1,
+            self.move_count + 1
+        );
+        let reason = if current_player.is_cpu() {
+            analysis::explain_move(&self.board, &board_move)
+        } else {
+            None
+        };
+        let judgement = (self.kibitz && current_player.is_human())
+            .then(|| analysis::judge_move(&self.board, &board_move));
         self.current_player_make_move(board_move);
+        // Not called from `current_player_make_move`, which also replays moves after an undo
+        if let Some(hook) = &self.on_move {
+            (hook.borrow_mut())(&self.board, board_move, self.move_count);
+        }
+        self.ui.notify_move(current_player.get_name(), &board_move);
+        if let Some(reason) = reason {
+            self.ui.notify_cpu_reasoning(reason);
+        }
+        if let Some(judgement) = judgement {
+            self.ui.notify_move_judgement(judgement);
+        }
+        self.dump_state(board_move);
+        true
+    }
+
+    /// Returns how long to wait before the player's move: the move delay for a CPU, so its moves
+    /// can be followed, and none for a human or in tests.
+    fn cpu_delay(&self, player: usize) -> Duration {
+        if self.players[player].is_cpu() && !cfg!(test) {
+            self.move_delay
+        } else {
+            Duration::ZERO
+        }
+    }
+
+    fn dump_state(&self, last_move: BoardMove) {
+        let Some(path) = &self.state_dump else {
+            return;
+        };
+
+        let state = self.format_state(last_move);
+
+        // The dump is only for spectators, so a failure shouldn't stop the game
+        if let Err(error) = fs::write(path, state) {
+            eprintln!(
+                "Couldn't dump the game state to {}: {error}",
+                path.display()
+            );
+        }
+    }
+
+    /// Formats the state right after `last_move` was made by the current player as a JSON object.
+    fn format_state(&self, last_move: BoardMove) -> String {
+        let player = self.players[self.current_player].get_name();
+        let next_player = self.players[1 - self.current_player].get_name();
+
+        format!(
+            "{{\"board\":\"{}\",\"move_number\":{},\"last_move\":{},\"player\":{},\"next_player\":{}}}",
+            self.board,
+            self.move_count,
+            last_move.index() + 1,
+            json_string(player),
+            json_string(next_player)
+        )
+    }
+
+    /// Takes back the current player's last move, together with the CPU's answer to it, so that
+    /// the same player is to move again. Against a human opponent only the opponent's last move is
+    /// taken back. Returns `false` if there aren't enough moves to undo.
+    fn undo(&mut self) -> bool {
+        let undone_moves = if self.players[1 - self.current_player].is_cpu() {
+            2
+        } else {
+            1
+        };
+        if self.history.len() < undone_moves {
+            return false;
+        }
+
+        let mut history = std::mem::take(&mut self.history);
+        history.truncate(history.len() - undone_moves);
+
+        self.rewind();
+        for board_move in history {
+            self.current_player_make_move(board_move);
+        }
+        self.current_player = self.player_to_move();
+
+        true
+    }
+
+    /// Charges the time spent on a turn to the player's clock, if the game is timed. Returns
+    /// `false` if the player ran out of time.
+    fn charge_clock(&mut self, player: usize, elapsed: Duration) -> bool {
+        self.clocks
+            .as_mut()
+            .is_none_or(|clocks| clocks.charge(player, elapsed))
+    }
+
+    /// Ends the game with a loss for the player whose time ran out.
+    fn flag_fall(&mut self, player: usize) {
+        let winner = 1 - player;
+        let winner_name = self.players[winner].get_name().to_owned();
+
+        debug_event!("event=flag_fall player={}", self.players[player].get_name());
+        self.ui.notify_flag_fall(self.players[player].get_name());
+        self.game_state = GameState::Finished(GameResult::PlayerWon(winner, winner_name, vec![]));
+    }
+
+    /// Brings the game back to its starting position, with full clocks.
+    fn restart(&mut self) {
+        if let Some(clocks) = &mut self.clocks {
+            clocks.reset();
+        }
+        self.rewind();
+    }
+
+    /// Brings the board back to the starting position. Unlike `restart`, keeps the clocks as they
+    /// are, since undoing moves doesn't give back the time spent on them.
+    fn rewind(&mut self) {
+        self.board = self.starting_board;
+        self.move_count = 0;
+        self.history.clear();
+        self.current_player = self.player_to_move();
+    }
+
+    /// Infers whose turn it is from the marks on the board.
+    fn player_to_move(&self) -> usize {
+        match self.board.current_player_symbol() {
+            Cell::X => 1,
+            _ => 0,
+        }
     }
 
     fn current_player_make_move(&mut self, board_move: BoardMove) {
-        self.board[board_move.index()] = self.board.current_player_symbol();
+        let symbol = self.board.current_player_symbol();
+        self.board
+            .place(&board_move, symbol)
+            .expect("Only available moves should be played");
+        self.move_count += 1;
+        self.history.push(board_move);
     }
 
     fn check_if_over(&mut self) {
-        if let Some(winning_line_index) = self.board.get_winning_line() {
-            let winner = match self.board[WINNING_LINES[winning_line_index][0]] {
-                Cell::O => 0,
-                Cell::X => 1,
-                Cell::Empty(_) => panic!("Winning line cannot be empty"),
-            };
-
-            let winner_name = self.players[winner].get_name().to_owned();
-
-            self.game_state = GameState::Finished(GameResult::PlayerWon(
-                winner,
-                winner_name,
-                winning_line_index,
-            ));
-        } else if self.board.is_full() {
-            self.game_state = GameState::Finished(GameResult::Draw);
+        match self.board.terminal_outcome() {
+            Some(TerminalOutcome::Win(symbol)) => {
+                let winner = symbol.symbol_id().expect("Winning line cannot be empty") as usize;
+                let winner_name = self.players[winner].get_name().to_owned();
+                // A single move can complete several lines, all of them count
+                let winning_lines = self.board.winning_lines();
+
+                self.game_state =
+                    GameState::Finished(GameResult::PlayerWon(winner, winner_name, winning_lines));
+            }
+            Some(TerminalOutcome::Draw) => {
+                self.game_state = GameState::Finished(GameResult::Draw(DrawReason::BoardFull));
+            }
+            None if self.board.is_drawn() => {
+                self.game_state = GameState::Finished(GameResult::Draw(DrawReason::Forced));
+            }
+            None => (),
+        }
+
+        if let GameState::Finished(result) = &self.game_state {
+            debug_event!(
+                "event=game_over result={:?} moves={}",
+                result,
+                self.move_count
+            );
         }
     }
 
     fn announce_result(&self) {
+        self.ui.clear();
         self.ui.update_board(&self.board);
 
-        match &self.game_state {
-            GameState::Finished(result) => {
-                self.ui.notify_result(&result);
+        if let GameState::Finished(result) = &self.game_state {
+            self.ui.notify_result(result);
+
+            if self.review {
+                let mistakes = analysis::review_game(&self.starting_board, &self.history);
+                self.ui.notify_review(&mistakes);
             }
-            _ => (),
         }
     }
 }
@@ -118,8 +628,137 @@ impl<'a, T: Ui> Game<'a, T> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::analysis::{MoveQuality, MoveReason};
+    use crate::player::Difficulty;
     use crate::ui::tests::MockUi;
 
+    #[test]
+    fn clock_deduction() {
+        let mut clocks = Clocks::new(Duration::from_secs(10));
+
+        assert!(clocks.charge(0, Duration::from_secs(4)));
+        assert!(clocks.charge(1, Duration::from_secs(1)));
+        assert!(clocks.charge(0, Duration::from_millis(5999)));
+        assert_eq!(
+            clocks.remaining,
+            [Duration::from_millis(1), Duration::from_secs(9)],
+            "Each turn should only be charged to the player on move"
+        );
+
+        assert!(
+            !clocks.charge(0, Duration::from_secs(2)),
+            "Flag should fall once the time runs out"
+        );
+        assert_eq!(clocks.remaining[0], Duration::ZERO, "Clock stops at zero");
+
+        clocks.reset();
+        assert_eq!(clocks.remaining, [Duration::from_secs(10); 2]);
+    }
+
+    #[test]
+    fn flag_fall_loses_the_game() {
+        let mock_ui = MockUi::builder()
+            .expected_moves(vec![BoardMove::try_new(5).unwrap()])
+            .build();
+        let p1 = Player::Human(String::from("Steve"));
+        let p2 = Player::Human(String::from("Another Steve"));
+
+        // Without any time, the flag falls on the first turn, however quick
+        let result = Game::new(&p1, &p2, &mock_ui)
+            .time_bank(Duration::ZERO)
+            .start();
+
+        assert_eq!(
+            result,
+            Ok(GameResult::PlayerWon(
+                1,
+                String::from("Another Steve"),
+                vec![]
+            ))
+        );
+        assert_eq!(mock_ui.flag_falls(), vec![String::from("Steve")]);
+        assert_eq!(
+            mock_ui.clocks(),
+            vec![(Duration::ZERO, Duration::ZERO)],
+            "Clocks should be shown every turn"
+        );
+    }
+
+    #[test]
+    fn blitz_game_within_time() {
+        let mock_ui = MockUi::builder()
+            .expected_moves(vec![
+                BoardMove::try_new(1).unwrap(),
+                BoardMove::try_new(4).unwrap(),
+                BoardMove::try_new(2).unwrap(),
+                BoardMove::try_new(5).unwrap(),
+                BoardMove::try_new(3).unwrap(),
+            ])
+            .build();
+        let p1 = Player::Human(String::from("Steve"));
+        let p2 = Player::Human(String::from("Another Steve"));
+        let bank = Duration::from_secs(3600);
+
+        let mut game = Game::new(&p1, &p2, &mock_ui).time_bank(bank);
+        let result = game.start();
+
+        assert_eq!(
+            result,
+            Ok(GameResult::PlayerWon(0, String::from("Steve"), vec![3]))
+        );
+        assert!(mock_ui.flag_falls().is_empty());
+        let clocks = game.clocks.unwrap();
+        assert!(clocks
+            .remaining
+            .iter()
+            .all(|&time| time < bank && time > bank / 2));
+    }
+
+    #[test]
+    fn undo_keeps_clocks_running() {
+        let mock_ui = MockUi::builder()
+            .expected_actions(vec![
+                PlayerAction::Move(BoardMove::try_new(1).unwrap()),
+                PlayerAction::Move(BoardMove::try_new(2).unwrap()),
+                PlayerAction::Undo,
+                PlayerAction::Quit,
+            ])
+            .build();
+        let p1 = Player::Human(String::from("Steve"));
+        let p2 = Player::Human(String::from("Another Steve"));
+        let bank = Duration::from_secs(3600);
+
+        let mut game = Game::new(&p1, &p2, &mock_ui).time_bank(bank);
+        assert_eq!(game.start(), Err(()));
+        assert!(
+            game.clocks
+                .unwrap()
+                .remaining
+                .iter()
+                .all(|&time| time < bank),
+            "Undo shouldn't give back the time spent on the undone moves"
+        );
+
+        game.restart();
+        assert_eq!(
+            game.clocks.unwrap().remaining,
+            [bank; 2],
+            "Restart should give both players full clocks"
+        );
+    }
+
+    #[test]
+    fn description_with_shorter_win_length() {
+        let board = "X...OX..O".parse::<Board>().unwrap().win_length(2);
+        let result = GameResult::PlayerWon(0, String::from("Steve"), board.winning_lines());
+
+        assert_eq!(
+            result.description(&board),
+            "Steve won on the main diagonal",
+            "Run indices should be named by the cells they stand for"
+        );
+    }
+
     #[test]
     fn announce_result() {
         let mock_ui = MockUi::builder().build();
@@ -142,7 +781,7 @@ mod tests {
             "Ui shouldn't be notified if game isn't finished"
         );
 
-        game.game_state = GameState::Finished(GameResult::Draw);
+        game.game_state = GameState::Finished(GameResult::Draw(DrawReason::BoardFull));
         game.announce_result();
         assert_eq!(
             mock_ui.notify_result_calls(),
@@ -239,11 +878,41 @@ mod tests {
 
         assert_eq!(
             game.game_state,
-            GameState::Finished(GameResult::Draw),
+            GameState::Finished(GameResult::Draw(DrawReason::BoardFull)),
             "On draw, game state should be Finished with result Draw"
         );
     }
 
+    #[test]
+    fn forced_draw_check() {
+        let mock_ui = MockUi::builder().build();
+        let p1 = Player::Human(String::from("Steve"));
+        let p2 = Player::Human(String::from("Another Steve"));
+        let mut game = Game::new(&p1, &p2, &mock_ui);
+
+        game.game_state = GameState::Ongoing;
+        game.board = "XOX.XOOXO".parse().unwrap(); // Cell 4 is empty, but every line is contested
+
+        game.check_if_over();
+
+        assert_eq!(
+            game.game_state,
+            GameState::Finished(GameResult::Draw(DrawReason::Forced)),
+            "Game should end with a forced draw when no line can be completed"
+        );
+
+        game.game_state = GameState::Ongoing;
+        game.board = "XOX.XO.XO".parse().unwrap(); // 'X' can still complete the left column
+
+        game.check_if_over();
+
+        assert_eq!(
+            game.game_state,
+            GameState::Ongoing,
+            "Game should go on while any line can be completed"
+        );
+    }
+
     #[test]
     fn player_make_move() {
         let mock_ui = MockUi::builder().build();
@@ -376,53 +1045,396 @@ mod tests {
             Cell::O,
             "Cell 4 should contain 'O' after player 1's move"
         );
+
+        assert_eq!(
+            mock_ui.played_moves(),
+            vec![
+                (String::from("Steve"), BoardMove::try_new(1).unwrap()),
+                (
+                    String::from("Another Steve"),
+                    BoardMove::try_new(2).unwrap()
+                ),
+                (String::from("Steve"), BoardMove::try_new(4).unwrap()),
+            ],
+            "UI should be told about every move and who played it"
+        );
     }
 
     #[test]
-    fn start_works_only_if_game_is_not_started() {
-        let mock_ui = MockUi::builder().build();
-        let p1 = Player::CPU;
-        let p2 = Player::CPU;
+    fn occupied_cell_is_rejected() {
+        let mock_ui = MockUi::builder()
+            .expected_moves(vec![
+                BoardMove::try_new(5).unwrap(), // Player1 goes center
+                BoardMove::try_new(5).unwrap(), // Player2 tries the same cell
+                BoardMove::try_new(1).unwrap(), // Player2 goes top left instead
+            ])
+            .build();
+        let p1 = Player::Human(String::from("Steve"));
+        let p2 = Player::Human(String::from("Another Steve"));
         let mut game = Game::new(&p1, &p2, &mock_ui);
 
-        game.game_state = GameState::Ongoing;
-        let result = game.start();
-        assert!(matches!(result, Err(())), "There should be no result");
-
-        game.game_state = GameState::Finished(GameResult::Draw);
-        let result = game.start();
-        assert!(matches!(result, Ok(_)), "There should be a result");
+        game.take_turn();
+        assert!(
+            mock_ui.invalid_moves().is_empty(),
+            "Valid move shouldn't be rejected"
+        );
 
-        game.game_state = GameState::Finished(GameResult::PlayerWon(0, String::from("CPU"), 0));
-        let result = game.start();
-        assert!(matches!(result, Ok(_)), "There should be a result");
-        let result = game.start();
-        assert!(matches!(result, Ok(_)), "There should be a result");
+        game.current_player = 1;
+        game.take_turn();
 
         assert_eq!(
-            mock_ui.get_move_calls(),
-            0,
-            "There should be no effect if start() was called in NotStarted state"
+            mock_ui.invalid_moves(),
+            vec![InvalidMove::CellTaken],
+            "Ui should be notified that the cell is taken"
         );
+        assert_eq!(game.board[0], Cell::X, "Player 2 should move again");
+        assert_eq!(mock_ui.get_move_calls(), 3);
+    }
+
+    #[test]
+    fn double_win() {
+        let mock_ui = MockUi::builder()
+            .expected_moves(vec![BoardMove::try_new(3).unwrap()])
+            .build();
+        let p1 = Player::Human(String::from("Steve"));
+        let p2 = Player::Human(String::from("Another Steve"));
+        let game = Game::new(&p1, &p2, &mock_ui).starting_position("OO.XOXOXX".parse().unwrap());
 
         assert_eq!(
-            mock_ui.notify_result_calls(),
-            0,
-            "UI should only be notified if start() was called in NotStarted state"
+            game.clone().start(),
+            Ok(GameResult::PlayerWon(0, String::from("Steve"), vec![3, 7])),
+            "Cell 3 completes both the top row and the anti-diagonal for 'O'"
         );
+    }
 
-        game.game_state = GameState::NotStarted;
-        let result = game.start();
-        assert!(matches!(result, Ok(_)), "There should be a result");
-
-        assert_eq!(
+    #[test]
+    fn drawn_games_are_cut_short() {
+        let mock_ui = MockUi::builder().build();
+        let p1 = Player::CPU(Difficulty::PERFECT);
+        let p2 = Player::CPU(Difficulty::PERFECT);
+
+        for position in ["OOXXXOOX.", "#.#.#.#.#"] {
+            let board: Board = position.parse().unwrap();
+            let mut game = Game::new(&p1, &p2, &mock_ui)
+                .move_delay(Duration::ZERO)
+                .starting_position(board);
+
+            assert_eq!(
+                game.start(),
+                Ok(GameResult::Draw(DrawReason::Forced)),
+                "{position} should be declared a draw right away"
+            );
+            assert_eq!(game.move_count(), 0, "No moves should be played");
+
+            let mut played_out = board;
+            let mut rng = rand::thread_rng();
+            while !played_out.is_full() {
+                let board_move = minimax::calculate_best_move(&played_out, &mut rng).unwrap();
+                played_out.place_unchecked(&board_move, played_out.current_player_symbol());
+            }
+            assert_eq!(
+                played_out.terminal_outcome(),
+                Some(TerminalOutcome::Draw),
+                "Playing {position} out should end in a draw as well"
+            );
+        }
+    }
+
+    #[test]
+    fn game_from_starting_position() {
+        let mock_ui = MockUi::builder()
+            .expected_moves(vec![BoardMove::try_new(9).unwrap()])
+            .build();
+        let p1 = Player::Human(String::from("Steve"));
+        let p2 = Player::Human(String::from("Another Steve"));
+        let board: Board = "O...X...O".parse().unwrap();
+        let mut game =
+            Game::new(&p1, &p2, &mock_ui).starting_position("O........".parse().unwrap());
+
+        assert_eq!(game.current_player, 1, "Player 2 should move after 'O'");
+
+        game.take_turn();
+        assert_eq!(game.board[8], Cell::X, "Player 2 should play 'X'");
+
+        let game = Game::new(&p1, &p2, &mock_ui).starting_position(board);
+        assert_eq!(
+            game.current_player, 1,
+            "Player 2 should move when 'O' has one more mark"
+        );
+
+        let game = Game::new(&p1, &p2, &mock_ui).starting_position("O...X....".parse().unwrap());
+        assert_eq!(
+            game.current_player, 0,
+            "Player 1 should move when both players have the same number of marks"
+        );
+    }
+
+    #[test]
+    fn decided_starting_position() {
+        let mock_ui = MockUi::builder().build();
+        let p1 = Player::Human(String::from("Steve"));
+        let p2 = Player::Human(String::from("Another Steve"));
+        let mut game =
+            Game::new(&p1, &p2, &mock_ui).starting_position("OOOXX....".parse().unwrap());
+
+        assert!(
+            matches!(game.start(), Ok(GameResult::PlayerWon(0, _, _))),
+            "Game should end immediately if the position is already won"
+        );
+        assert_eq!(
+            mock_ui.get_move_calls(),
+            0,
+            "Nobody should be asked to move"
+        );
+    }
+
+    #[test]
+    fn restart_and_quit_actions() {
+        let mock_ui = MockUi::builder()
+            .expected_actions(vec![
+                PlayerAction::Move(BoardMove::try_new(1).unwrap()),
+                PlayerAction::Move(BoardMove::try_new(2).unwrap()),
+                PlayerAction::Restart,
+                PlayerAction::Move(BoardMove::try_new(5).unwrap()),
+                PlayerAction::Quit,
+            ])
+            .build();
+        let p1 = Player::Human(String::from("Steve"));
+        let p2 = Player::Human(String::from("Another Steve"));
+        let mut game = Game::new(&p1, &p2, &mock_ui);
+
+        assert_eq!(game.start(), Err(()), "Abandoned game has no result");
+        assert_eq!(game.game_state, GameState::Aborted);
+        assert_eq!(
+            game.board.to_string(),
+            "....O....",
+            "Board should be cleared on restart and player 1 should move first again"
+        );
+        assert_eq!(
+            game.move_count(),
+            1,
+            "Move count should be reset on restart"
+        );
+        assert_eq!(
+            mock_ui.notify_result_calls(),
+            0,
+            "Abandoned game shouldn't be announced"
+        );
+    }
+
+    #[test]
+    fn undo_hint_and_forfeit_actions() {
+        let mock_ui = MockUi::builder()
+            .expected_actions(vec![
+                PlayerAction::Undo, // Nothing to undo yet
+                PlayerAction::Move(BoardMove::try_new(1).unwrap()),
+                PlayerAction::Hint,
+                PlayerAction::Move(BoardMove::try_new(2).unwrap()),
+                PlayerAction::Undo, // Player 2's move is taken back, so they move again
+                PlayerAction::OpenMenu,
+                PlayerAction::Move(BoardMove::try_new(5).unwrap()),
+                PlayerAction::OpenMenu, // Player 1 forfeits
+            ])
+            .expected_menu_choices(vec![None, Some(PlayerAction::Forfeit)])
+            .build();
+        let p1 = Player::Human(String::from("Steve"));
+        let p2 = Player::Human(String::from("Another Steve"));
+        let mut game = Game::new(&p1, &p2, &mock_ui);
+
+        assert_eq!(
+            game.start(),
+            Ok(GameResult::PlayerWon(
+                1,
+                String::from("Another Steve"),
+                vec![]
+            )),
+            "Player 2 wins without completing a line"
+        );
+        assert_eq!(game.board.to_string(), "O...X....");
+        assert_eq!(
+            mock_ui.invalid_moves(),
+            vec![InvalidMove::NothingToUndo],
+            "Undo should be rejected on an empty board"
+        );
+        assert_eq!(
+            mock_ui.hints(),
+            vec![BoardMove::try_new(5).unwrap()],
+            "Center is the only move that doesn't lose for 'X'"
+        );
+    }
+
+    #[test]
+    fn undo_against_cpu() {
+        let mock_ui = MockUi::builder()
+            .expected_actions(vec![
+                PlayerAction::Move(BoardMove::try_new(5).unwrap()),
+                PlayerAction::Undo,
+                PlayerAction::Quit,
+            ])
+            .build();
+        let p1 = Player::Human(String::from("Steve"));
+        let p2 = Player::CPU(Difficulty::PERFECT);
+        let mut game = Game::new(&p1, &p2, &mock_ui);
+
+        assert_eq!(game.start(), Err(()));
+        assert_eq!(
+            game.board,
+            Board::new(),
+            "Both the human's move and the CPU's answer should be taken back"
+        );
+        assert_eq!(game.current_player, 0, "Human should be to move again");
+        assert_eq!(game.move_count(), 0);
+    }
+
+    #[test]
+    fn screen_is_cleared_before_every_board_update() {
+        let mock_ui = MockUi::builder()
+            .expected_moves(vec![
+                BoardMove::try_new(1).unwrap(),
+                BoardMove::try_new(4).unwrap(),
+                BoardMove::try_new(2).unwrap(),
+                BoardMove::try_new(5).unwrap(),
+                BoardMove::try_new(3).unwrap(), // Player 1 wins
+            ])
+            .build();
+        let p1 = Player::Human(String::from("Steve"));
+        let p2 = Player::Human(String::from("Another Steve"));
+
+        Game::new(&p1, &p2, &mock_ui).start().unwrap();
+
+        assert_eq!(
+            mock_ui.clear_calls(),
+            6,
+            "Screen should be cleared before each of 5 turns and before the result"
+        );
+        assert_eq!(
+            mock_ui.clear_calls(),
+            mock_ui.board_updates().len() as u32,
+            "Every board update should follow a clear"
+        );
+    }
+
+    #[test]
+    fn available_moves_shrink() {
+        let mock_ui = MockUi::builder()
+            .expected_moves(vec![
+                BoardMove::try_new(1).unwrap(),
+                BoardMove::try_new(5).unwrap(),
+            ])
+            .build();
+        let p1 = Player::Human(String::from("Steve"));
+        let p2 = Player::Human(String::from("Another Steve"));
+        let mut game = Game::new(&p1, &p2, &mock_ui);
+
+        assert_eq!(
+            game.available_moves().len(),
+            9,
+            "Every cell is free at first"
+        );
+
+        game.game_state = GameState::Ongoing;
+        game.take_turn();
+        game.current_player = 1;
+        game.take_turn();
+
+        let moves = game.available_moves();
+        assert_eq!(moves.len(), 7, "Two cells should be taken");
+        assert!(
+            !moves.contains(&BoardMove::try_new(1).unwrap())
+                && !moves.contains(&BoardMove::try_new(5).unwrap()),
+            "Taken cells shouldn't be available"
+        );
+
+        game.game_state = GameState::Finished(GameResult::Draw(DrawReason::Forced));
+        assert!(
+            game.available_moves().is_empty(),
+            "There are no moves once the game is over"
+        );
+    }
+
+    #[test]
+    fn cloned_game_is_independent() {
+        let mock_ui = MockUi::builder()
+            .expected_moves(vec![
+                BoardMove::try_new(1).unwrap(),
+                BoardMove::try_new(5).unwrap(),
+            ])
+            .build();
+        let p1 = Player::Human(String::from("Steve"));
+        let p2 = Player::Human(String::from("Another Steve"));
+        let mut game = Game::new(&p1, &p2, &mock_ui);
+
+        game.game_state = GameState::Ongoing;
+        game.take_turn();
+        game.current_player = 1;
+
+        let mut clone = game.clone();
+        clone.take_turn();
+
+        assert_eq!(
+            clone.board[4],
+            Cell::X,
+            "Move should be played on the cloned game"
+        );
+        assert_eq!(
+            game.board[4],
+            Cell::Empty('5'),
+            "Original game shouldn't be affected by moves on the clone"
+        );
+        assert_eq!(
+            game.board[0],
+            Cell::O,
+            "Clone should keep moves played before cloning"
+        );
+        assert_eq!(clone.board[0], Cell::O);
+    }
+
+    #[test]
+    fn start_works_only_if_game_is_not_started() {
+        let mock_ui = MockUi::builder().build();
+        let p1 = Player::CPU(Difficulty::PERFECT);
+        let p2 = Player::CPU(Difficulty::PERFECT);
+        let mut game = Game::new(&p1, &p2, &mock_ui);
+
+        game.game_state = GameState::Ongoing;
+        let result = game.start();
+        assert!(matches!(result, Err(())), "There should be no result");
+
+        game.game_state = GameState::Finished(GameResult::Draw(DrawReason::BoardFull));
+        let result = game.start();
+        assert!(result.is_ok(), "There should be a result");
+
+        game.game_state =
+            GameState::Finished(GameResult::PlayerWon(0, String::from("CPU"), vec![0]));
+        let result = game.start();
+        assert!(result.is_ok(), "There should be a result");
+        let result = game.start();
+        assert!(result.is_ok(), "There should be a result");
+
+        assert_eq!(
+            mock_ui.get_move_calls(),
+            0,
+            "There should be no effect if start() was called in NotStarted state"
+        );
+
+        assert_eq!(
+            mock_ui.notify_result_calls(),
+            0,
+            "UI should only be notified if start() was called in NotStarted state"
+        );
+
+        game.game_state = GameState::NotStarted;
+        let result = game.start();
+        assert!(result.is_ok(), "There should be a result");
+
+        assert_eq!(
             mock_ui.notify_result_calls(),
             1,
             "UI should only be notified if start() was called in NotStarted state"
         );
 
         let result = game.start();
-        assert!(matches!(result, Ok(_)), "There should still be a result");
+        assert!(result.is_ok(), "There should still be a result");
 
         assert_eq!(
             mock_ui.notify_result_calls(),
@@ -462,6 +1474,189 @@ mod tests {
         );
     }
 
+    #[test]
+    fn turn_counter() {
+        let mock_ui = MockUi::builder()
+            .expected_moves(vec![
+                BoardMove::try_new(1).unwrap(),
+                BoardMove::try_new(7).unwrap(),
+                BoardMove::try_new(9).unwrap(),
+                BoardMove::try_new(5).unwrap(),
+                BoardMove::try_new(3).unwrap(),
+                BoardMove::try_new(6).unwrap(),
+                BoardMove::try_new(2).unwrap(), // Player 1 wins
+            ])
+            .build();
+        let p1 = Player::Human(String::from("Steve"));
+        let p2 = Player::Human(String::from("Another Steve"));
+        let mut game = Game::new(&p1, &p2, &mock_ui);
+
+        assert_eq!(game.max_turns(), 9, "Every cell of the board is free");
+
+        game.start().unwrap();
+
+        assert_eq!(game.move_count(), 7);
+        assert_eq!(
+            mock_ui.players(),
+            Some((String::from("Steve"), String::from("Another Steve"))),
+            "Players should be introduced when the game starts"
+        );
+        assert_eq!(
+            mock_ui.turns(),
+            (1..=7).map(|turn| (turn, 9)).collect::<Vec<_>>(),
+            "Every turn should be shown with the limit"
+        );
+
+        let game = Game::new(&p1, &p2, &mock_ui).starting_position("XO.......".parse().unwrap());
+        assert_eq!(game.max_turns(), 7, "Marked cells can't be played again");
+    }
+
+    #[test]
+    #[should_panic(expected = "still ongoing after 9 moves")]
+    fn undetected_game_end_panics() {
+        let mock_ui = MockUi::builder().build();
+        let p1 = Player::Human(String::from("Steve"));
+        let p2 = Player::Human(String::from("Another Steve"));
+        let mut game = Game::new(&p1, &p2, &mock_ui);
+
+        game.game_state = GameState::Ongoing;
+        game.move_count = 9; // As if the full board wasn't noticed
+
+        game.check_turn_limit();
+    }
+
+    #[test]
+    fn cpu_moves_are_explained() {
+        let mock_ui = MockUi::builder().build();
+        let p1 = Player::CPU(Difficulty::PERFECT);
+        let p2 = Player::Human(String::from("Steve"));
+
+        Game::new(&p1, &p2, &mock_ui)
+            .starting_position("OO.XX....".parse().unwrap())
+            .start()
+            .unwrap();
+
+        assert_eq!(
+            mock_ui.cpu_reasons(),
+            vec![MoveReason::Winning],
+            "CPU should explain that it completed the top row"
+        );
+    }
+
+    #[test]
+    fn kibitz_judges_human_moves() {
+        let moves = [6, 7].map(|cell| PlayerAction::Move(BoardMove::try_new(cell).unwrap()));
+        let mock_ui = MockUi::builder().expected_actions(moves.to_vec()).build();
+        let p1 = Player::Human(String::from("Steve"));
+        let p2 = Player::CPU(Difficulty::PERFECT);
+
+        Game::new(&p1, &p2, &mock_ui)
+            .starting_position("OO.XX....".parse().unwrap())
+            .seed(1)
+            .kibitz(true)
+            .start()
+            .unwrap();
+
+        let qualities: Vec<_> = mock_ui
+            .judgements()
+            .iter()
+            .map(|judgement| judgement.quality)
+            .collect();
+        assert_eq!(
+            qualities,
+            vec![MoveQuality::Blunder, MoveQuality::Optimal],
+            "Only Steve's moves should be judged, starting with the missed win"
+        );
+        assert_eq!(mock_ui.played_moves().len(), 4, "CPU answered both moves");
+    }
+
+    #[test]
+    fn review_after_game() {
+        let mock_ui = MockUi::builder()
+            .expected_moves(
+                [5, 2, 1, 9, 3, 7, 4, 8]
+                    .map(|cell| BoardMove::try_new(cell).unwrap())
+                    .to_vec(),
+            )
+            .build();
+        let p1 = Player::Human(String::from("Steve"));
+        let p2 = Player::Human(String::from("Another Steve"));
+
+        Game::new(&p1, &p2, &mock_ui).review(true).start().unwrap();
+
+        let reviews = mock_ui.reviews();
+        assert_eq!(reviews.len(), 1, "Game should be reviewed once, at the end");
+        assert_eq!(
+            reviews[0]
+                .iter()
+                .map(|mistake| mistake.played.index() + 1)
+                .collect::<Vec<_>>(),
+            vec![2, 3, 4],
+            "Edge answer, missed win and missed block"
+        );
+
+        let mock_ui = MockUi::builder()
+            .expected_moves(vec![BoardMove::try_new(3).unwrap()])
+            .build();
+        Game::new(&p1, &p2, &mock_ui)
+            .starting_position("OO.XX....".parse().unwrap())
+            .start()
+            .unwrap();
+        assert!(mock_ui.reviews().is_empty(), "Reviews are off by default");
+    }
+
+    #[test]
+    fn kibitz_is_off_by_default() {
+        let mock_ui = MockUi::builder()
+            .expected_moves(vec![BoardMove::try_new(3).unwrap()])
+            .build();
+        let p1 = Player::Human(String::from("Steve"));
+        let p2 = Player::Human(String::from("Another Steve"));
+
+        Game::new(&p1, &p2, &mock_ui)
+            .starting_position("OO.XX....".parse().unwrap())
+            .start()
+            .unwrap();
+
+        assert!(mock_ui.judgements().is_empty());
+    }
+
+    #[test]
+    fn finished_state_and_result() {
+        let mock_ui = MockUi::builder().build();
+        let p1 = Player::Human(String::from("Steve"));
+        let p2 = Player::Human(String::from("Another Steve"));
+        let mut game = Game::new(&p1, &p2, &mock_ui);
+
+        assert!(
+            !game.is_finished(),
+            "Game that hasn't started isn't finished"
+        );
+        assert_eq!(game.result(), None);
+
+        game.game_state = GameState::Ongoing;
+        for cell in [1, 4, 2, 5] {
+            game.current_player_make_move(BoardMove::try_new(cell).unwrap());
+            game.check_if_over();
+
+            assert!(!game.is_finished(), "Nobody has won after move {cell}");
+            assert_eq!(game.result(), None);
+        }
+
+        game.current_player_make_move(BoardMove::try_new(3).unwrap());
+        game.check_if_over();
+
+        assert!(game.is_finished(), "'O' completed the top row");
+        assert_eq!(
+            game.result(),
+            Some(GameResult::PlayerWon(0, String::from("Steve"), vec![3]))
+        );
+
+        game.game_state = GameState::Aborted;
+        assert!(!game.is_finished(), "Game that was quit has no result");
+        assert_eq!(game.result(), None);
+    }
+
     #[test]
     fn full_game_draw() {
         let mock_ui = MockUi::builder()
@@ -473,8 +1668,7 @@ mod tests {
                 BoardMove::try_new(2).unwrap(),
                 BoardMove::try_new(1).unwrap(),
                 BoardMove::try_new(6).unwrap(),
-                BoardMove::try_new(3).unwrap(),
-                BoardMove::try_new(4).unwrap(), // Draw
+                BoardMove::try_new(3).unwrap(), // Forced draw, cell 4 can't complete any line
             ])
             .build();
         let p1 = Player::Human(String::from("Steve"));
@@ -483,7 +1677,7 @@ mod tests {
         let result = Game::new(&p1, &p2, &mock_ui).start();
 
         assert!(
-            matches!(result, Ok(GameResult::Draw)),
+            matches!(result, Ok(GameResult::Draw(_))),
             "CPU should always draw CPU"
         );
 
@@ -494,18 +1688,111 @@ mod tests {
         );
     }
 
+    #[test]
+    fn on_move_hook() {
+        let cells = [9, 5, 7, 8, 2, 1, 6, 3];
+        let mock_ui = MockUi::builder()
+            .expected_moves(
+                cells
+                    .iter()
+                    .map(|&cell| BoardMove::try_new(cell).unwrap())
+                    .collect(),
+            )
+            .build();
+        let p1 = Player::Human(String::from("Steve"));
+        let p2 = Player::Human(String::from("Another Steve"));
+
+        let mut played = vec![];
+        Game::new(&p1, &p2, &mock_ui)
+            .on_move(|board, board_move, move_number| {
+                assert!(
+                    !matches!(board[board_move.index()], Cell::Empty(_)),
+                    "Hook should see the board after the move"
+                );
+                played.push((board_move.index() + 1, move_number));
+            })
+            .start()
+            .unwrap();
+
+        let expected: Vec<_> = cells.iter().copied().zip(1..).collect();
+        assert_eq!(played, expected, "Every move should be reported in order");
+    }
+
     #[test]
     fn cpu_vs_cpu_always_draws() {
         let mock_ui = MockUi::builder().build();
-        let p1 = Player::CPU;
-        let p2 = Player::CPU;
+        let p1 = Player::CPU(Difficulty::PERFECT);
+        let p2 = Player::CPU(Difficulty::PERFECT);
 
         for _ in 0..10 {
             let result = Game::new(&p1, &p2, &mock_ui).start();
             assert!(
-                matches!(result, Ok(GameResult::Draw)),
+                matches!(result, Ok(GameResult::Draw(_))),
                 "CPU should always draw CPU"
             );
         }
     }
+
+    #[test]
+    #[cfg(feature = "logging")]
+    fn completed_game_emits_log_events() {
+        let mock_ui = MockUi::builder().build();
+        let p1 = Player::CPU(Difficulty::PERFECT);
+        let p2 = Player::CPU(Difficulty::PERFECT);
+
+        let logs = crate::logging::tests::capture_logs(|| {
+            Game::new(&p1, &p2, &mock_ui).seed(1).start().unwrap();
+        });
+
+        assert!(
+            logs.iter()
+                .any(|log| log.starts_with("event=move player=CPU")
+                    && log.ends_with("move_number=1")),
+            "Moves should be logged, got {logs:?}"
+        );
+        assert!(
+            logs.iter()
+                .any(|log| log.starts_with("event=cpu_move") && log.contains("nodes=")),
+            "CPU decisions should be logged with search statistics, got {logs:?}"
+        );
+        assert_eq!(
+            logs.iter()
+                .filter(|log| log.starts_with("event=game_over"))
+                .count(),
+            1,
+            "Game result should be logged exactly once, got {logs:?}"
+        );
+    }
+
+    #[test]
+    fn json_strings() {
+        assert_eq!(json_string("Steve"), r#""Steve""#);
+        assert_eq!(
+            json_string("\"Big\" \\ Steve\n"),
+            r#""\"Big\" \\ Steve\u000a""#,
+            "Quotes, backslashes and control characters should be escaped"
+        );
+    }
+
+    #[test]
+    fn state_dump_after_move() {
+        let path = std::env::temp_dir().join(format!("ttt-state-{}.json", std::process::id()));
+        let mock_ui = MockUi::builder()
+            .expected_moves(vec![BoardMove::try_new(5).unwrap()])
+            .build();
+        let p1 = Player::Human(String::from("Steve"));
+        let p2 = Player::CPU(Difficulty::PERFECT);
+        let mut game = Game::new(&p1, &p2, &mock_ui).state_dump(&path);
+        game.game_state = GameState::Ongoing;
+
+        game.take_turn();
+        let state = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            state,
+            r#"{"board":"....O....","move_number":1,"last_move":5,"player":"Steve","next_player":"CPU"}"#,
+            "State should describe the board right after the move"
+        );
+    }
 }
@@ -1,8 +1,17 @@
-mod board;
+#[macro_use]
+mod logging;
+
+pub mod analysis;
+pub mod board;
+mod config;
 mod game;
 mod player;
+mod scoreboard;
 mod tictactoe;
 mod ui;
 
-pub use tictactoe::TicTacToe;
+pub use config::{Config, ConfigError};
+pub use game::{DrawReason, GameResult};
+pub use scoreboard::{Record, ScoreBoard, ScoringPolicy};
+pub use tictactoe::{GameConfig, GameMode, PlayerKind, TicTacToe};
 pub use ui::TerminalUi;
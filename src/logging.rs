@@ -0,0 +1,75 @@
+//! This module contains logging macros used for debugging the CPU and game flow.
+//!
+//! With the `logging` feature enabled, events are forwarded to the `log` facade as `key=value`
+//! messages. Without it, the macros expand to code that is never run, so logging costs nothing.
+
+#[cfg(feature = "logging")]
+macro_rules! debug_event {
+    ($($arg:tt)+) => {
+        log::debug!($($arg)+)
+    };
+}
+
+#[cfg(not(feature = "logging"))]
+macro_rules! debug_event {
+    ($($arg:tt)+) => {
+        if false {
+            let _ = format_args!($($arg)+);
+        }
+    };
+}
+
+#[cfg(test)]
+#[cfg(feature = "logging")]
+pub mod tests {
+    use log::{Log, Metadata, Record};
+    use std::sync::{Mutex, Once};
+    use std::thread::{self, ThreadId};
+
+    /// Logger that stores messages along with the thread that emitted them, so that tests
+    /// running in parallel only see their own events.
+    struct CapturingLogger {
+        records: Mutex<Vec<(ThreadId, String)>>,
+    }
+
+    impl Log for CapturingLogger {
+        fn enabled(&self, _metadata: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            self.records
+                .lock()
+                .unwrap()
+                .push((thread::current().id(), record.args().to_string()));
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: CapturingLogger = CapturingLogger {
+        records: Mutex::new(vec![]),
+    };
+    static INIT: Once = Once::new();
+
+    /// Installs the capturing logger (once per test binary) and returns all messages logged so
+    /// far by `f` on the current thread.
+    pub fn capture_logs(f: impl FnOnce()) -> Vec<String> {
+        INIT.call_once(|| {
+            log::set_logger(&LOGGER).unwrap();
+            log::set_max_level(log::LevelFilter::Trace);
+        });
+
+        f();
+
+        let current = thread::current().id();
+        LOGGER
+            .records
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(thread_id, _)| *thread_id == current)
+            .map(|(_, message)| message.clone())
+            .collect()
+    }
+}
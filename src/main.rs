@@ -1,7 +1,174 @@
-use tic_tac_terminal::{TicTacToe, TerminalUi};
+use std::env;
+use std::fs::File;
+use std::io::{self, BufReader, IsTerminal};
+use std::process;
+use tic_tac_terminal::{analysis, board::Board, Config, TerminalUi, TicTacToe};
+
+const PIPED_INPUT_RETRY_LIMIT: u32 = 3;
+
+const USAGE: &str = "Usage: tic-tac-terminal [--config <path>] [--scroll] [--alternate-screen] [--narrate] [--explain] \
+                     [--kibitz] [--review] [--hide-labels] [--spotlight-win] [--no-legend] \
+                     [--no-status-line] [--line-editing] \
+                     [--toroidal] [--win-length <marks>] \
+                     [--entertainer] [--first-to <points>] [--blitz <seconds>] \
+                     [--seed <number>] [--cpu-preference <cells>] \
+                     [--state-dump <path>] \
+                     [--input <path>] \
+                     [--log-results <path> | --export-positions <path> | --position <board>]\n\n\
+                     --config reads default options from <path>; flags override them\n\
+                     --scroll keeps previous boards on screen instead of clearing it\n\
+                     --alternate-screen plays in a separate screen, restoring the terminal on quit\n\
+                     --narrate describes every move below the board\n\
+                     --explain tells why the CPU made its move\n\
+                     --kibitz tells whether your moves were the best ones\n\
+                     --review lists both players' mistakes after every game\n\
+                     --hide-labels draws empty cells without their numbers\n\
+                     --spotlight-win dims the cells outside the winning line\n\
+                     --no-legend hides which player has which mark below the board\n\
+                     --no-status-line hides the turn number and who's to move below the board\n\
+                     --line-editing lets you edit answers with arrow keys and recall previous ones\n\
+                     --toroidal lets diagonals wrap around the edges of the board\n\
+                     --win-length lets <marks> in a row win, 2 or 3 for a full line\n\
+                     --entertainer makes the CPU keep the game going when it can't win\n\
+                     --first-to ends a match once a player scores <points>\n\
+                     --blitz gives each player <seconds> for the whole game; out of time loses\n\
+                     --seed makes the CPU's choices the same every time for the same <number>\n\
+                     --cpu-preference breaks ties between the CPU's best moves by <cells>, e.g. 5,1,3,7,9\n\
+                     --input reads answers from <path>, one per line, instead of the keyboard\n\
+                     --state-dump writes the game state as JSON to <path> after every move\n\
+                     <board> lists cells row by row: O, X or . for an empty cell, e.g. O...X....";
 
 fn main() {
-    let ui = TerminalUi::new();
-    let mut game = TicTacToe::new(&ui);
-    game.start();
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let mut config = match take_value(&mut args, "--config") {
+        Some(path) => Config::load(&path).unwrap_or_else(|error| {
+            eprintln!("{error}");
+            process::exit(2);
+        }),
+        None => Config::default(),
+    };
+
+    let flags = [
+        ("--scroll", &mut config.scroll),
+        ("--alternate-screen", &mut config.alternate_screen),
+        ("--narrate", &mut config.narration),
+        ("--explain", &mut config.explain),
+        ("--kibitz", &mut config.kibitz),
+        ("--review", &mut config.review),
+        ("--hide-labels", &mut config.hide_labels),
+        ("--spotlight-win", &mut config.spotlight_win),
+        ("--toroidal", &mut config.toroidal),
+        ("--entertainer", &mut config.entertainer),
+        ("--line-editing", &mut config.line_editing),
+    ];
+    for (flag, option) in flags {
+        if args.iter().any(|arg| arg == flag) {
+            *option = Some(true);
+            args.retain(|arg| arg != flag);
+        }
+    }
+    if args.iter().any(|arg| arg == "--no-legend") {
+        config.legend = Some(false);
+        args.retain(|arg| arg != "--no-legend");
+    }
+    if args.iter().any(|arg| arg == "--no-status-line") {
+        config.status_line = Some(false);
+        args.retain(|arg| arg != "--no-status-line");
+    }
+    if let Some(path) = take_value(&mut args, "--state-dump") {
+        config.state_dump = Some(path.into());
+    }
+    if let Some(points) = take_value(&mut args, "--first-to") {
+        config.first_to = Some(points.parse().unwrap_or_else(|_| {
+            eprintln!("Invalid number of points: {points}\n\n{USAGE}");
+            process::exit(2);
+        }));
+    }
+
+    if let Some(seconds) = take_value(&mut args, "--blitz") {
+        config.blitz = Some(seconds.parse().unwrap_or_else(|_| {
+            eprintln!("Invalid number of seconds: {seconds}\n\n{USAGE}");
+            process::exit(2);
+        }));
+    }
+
+    if let Some(marks) = take_value(&mut args, "--win-length") {
+        config.win_length = Some(Config::parse_win_length(&marks).unwrap_or_else(|| {
+            eprintln!("Invalid win length: {marks}\n\n{USAGE}");
+            process::exit(2);
+        }));
+    }
+
+    if let Some(seed) = take_value(&mut args, "--seed") {
+        config.seed = Some(seed.parse().unwrap_or_else(|_| {
+            eprintln!("Invalid seed: {seed}\n\n{USAGE}");
+            process::exit(2);
+        }));
+    }
+
+    if let Some(cells) = take_value(&mut args, "--cpu-preference") {
+        config.cpu_preference = Some(Config::parse_cpu_preference(&cells).unwrap_or_else(|| {
+            eprintln!("Invalid CPU preference: {cells}\n\n{USAGE}");
+            process::exit(2);
+        }));
+    }
+
+    let input = take_value(&mut args, "--input").map(|path| {
+        File::open(&path).unwrap_or_else(|error| {
+            eprintln!("Couldn't open {path}: {error}");
+            process::exit(2);
+        })
+    });
+
+    if input.is_some() || !io::stdin().is_terminal() {
+        // Scripted input shouldn't be able to hang the game on a bad answer
+        config.retry_limit.get_or_insert(PIPED_INPUT_RETRY_LIMIT);
+    }
+    let mut ui = TerminalUi::with_config(&config);
+    if let Some(file) = input {
+        ui = ui.input(BufReader::new(file));
+    }
+    let mut game = TicTacToe::with_config(&ui, &config);
+
+    match args.as_slice() {
+        [] => (),
+        [flag, path] if flag == "--log-results" => game = game.results_log(path),
+        [flag, position] if flag == "--position" => match position.parse::<Board>() {
+            Ok(board) if board.is_legal_position() => game = game.starting_position(board),
+            _ => {
+                eprintln!("Invalid starting position: {position}\n\n{USAGE}");
+                process::exit(2);
+            }
+        },
+        [flag, path] if flag == "--export-positions" => {
+            match analysis::export_positions(path) {
+                Ok(count) => println!("Exported {count} positions to {path}"),
+                Err(error) => {
+                    eprintln!("Couldn't export positions: {error}");
+                    process::exit(1);
+                }
+            }
+            return;
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            process::exit(2);
+        }
+    }
+
+    ui.run(|| game.start());
+}
+
+/// Removes a flag together with the value following it from the arguments and returns the value.
+/// Exits with usage if the value is missing.
+fn take_value(args: &mut Vec<String>, flag: &str) -> Option<String> {
+    let flag_index = args.iter().position(|arg| arg == flag)?;
+    if flag_index + 1 >= args.len() {
+        eprintln!("{USAGE}");
+        process::exit(2);
+    }
+
+    let value = args.remove(flag_index + 1);
+    args.remove(flag_index);
+    Some(value)
 }
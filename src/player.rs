@@ -1,40 +1,115 @@
 //! This module contains representation of the player instance (both human player as well as 'AI'
 //! player). Provides interface for getting moves from the player.
 
-mod minimax;
+pub mod minimax;
 
-use crate::board::{Board, BoardMove};
+use crate::board::Board;
+use crate::game::PlayerAction;
 use crate::ui::Ui;
-use std::thread;
-use std::time::Duration;
+use rand::Rng;
 
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, PartialEq)]
 pub enum Player {
     Human(String),
-    CPU,
+    CPU(Difficulty),
+}
+
+/// Describes how strong the CPU is, as a chance (in percent) of playing a random move instead of
+/// the best one, and how it picks among equally good moves.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Difficulty {
+    mistake_chance: u8,
+    preference: Vec<usize>,
+    entertainer: bool,
+}
+
+impl Difficulty {
+    pub const PERFECT: Self = Self {
+        mistake_chance: 0,
+        preference: Vec::new(),
+        entertainer: false,
+    };
+
+    pub fn with_mistake_chance(mistake_chance: u8) -> Self {
+        Self {
+            mistake_chance: mistake_chance.min(100),
+            ..Self::PERFECT
+        }
+    }
+
+    /// Sets the cell indices the CPU prefers when several moves are equally good, most preferred
+    /// first, e.g. `vec![4, 0, 2, 6, 8]` for center, then corners. Ties between unlisted cells (or
+    /// all ties, with the default empty list) are broken at random.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the preference isn't valid (see `is_valid_preference`).
+    pub fn preference(mut self, preference: Vec<usize>) -> Self {
+        assert!(
+            Self::is_valid_preference(&preference),
+            "Invalid CPU preference: {preference:?}"
+        );
+        self.preference = preference;
+        self
+    }
+
+    /// Checks whether the cell indices order some of the cells of the board: each is a cell of
+    /// the board and none is listed twice. Cells can be left out, so a full ordering is a
+    /// permutation of all the cells.
+    pub fn is_valid_preference(preference: &[usize]) -> bool {
+        let mut listed = [false; 9];
+
+        preference
+            .iter()
+            .all(|&index| index < listed.len() && !std::mem::replace(&mut listed[index], true))
+    }
+
+    /// Makes the CPU keep the game alive when it can't force a win, preferring moves which leave
+    /// the most lines open over those heading straight for a draw.
+    pub fn entertainer(mut self, entertainer: bool) -> Self {
+        self.entertainer = entertainer;
+        self
+    }
 }
 
 impl Player {
-    pub fn get_move(
-        &self,
-        board: &Board,
-        ui: &impl Ui,
-        additional_message: Option<&str>,
-    ) -> BoardMove {
+    pub fn get_move(&self, board: &Board, ui: &impl Ui, rng: &mut impl Rng) -> PlayerAction {
         match self {
-            Self::Human(name) => ui.get_move(name, additional_message),
-            Self::CPU => {
-                if !cfg!(test) {
-                    thread::sleep(Duration::from_millis(200));
+            Self::Human(name) => ui.get_move(name),
+            Self::CPU(difficulty) => {
+                // Perfect CPU doesn't touch the RNG here, so seeded games stay reproducible
+                if difficulty.mistake_chance > 0
+                    && rng.gen_range(0..100) < difficulty.mistake_chance
+                {
+                    let possible_moves = board.get_possible_moves();
+                    PlayerAction::Move(possible_moves[rng.gen_range(0..possible_moves.len())])
+                } else {
+                    let board_move = if difficulty.entertainer {
+                        minimax::calculate_entertaining_move(board, &difficulty.preference, rng)
+                    } else {
+                        minimax::calculate_preferred_move(board, &difficulty.preference, rng)
+                    };
+                    PlayerAction::Move(
+                        board_move.expect("CPU is only asked to move on a free cell"),
+                    )
                 }
-                minimax::calculate_best_move(board)
             }
         }
     }
 
+    pub fn is_cpu(&self) -> bool {
+        matches!(self, Self::CPU(_))
+    }
+
+    pub fn is_human(&self) -> bool {
+        matches!(self, Self::Human(_))
+    }
+
     pub fn get_name(&self) -> &str {
         match self {
             Self::Human(name) => name,
-            Self::CPU => "CPU",
+            Self::CPU(_) => "CPU",
         }
     }
 }
@@ -42,29 +117,135 @@ impl Player {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::board::BoardMove;
     use crate::ui::tests::MockUi;
 
     #[test]
     fn get_human_move() {
         let returned_move = BoardMove::try_new(3).unwrap();
         let mock_ui = MockUi::builder()
-            .expected_moves(vec![returned_move.clone()])
+            .expected_moves(vec![returned_move])
             .build();
         let fake_board = Board::new();
         let player = Player::Human(String::from("Steve"));
 
-        let m = player.get_move(&fake_board, &mock_ui, None);
+        let m = player.get_move(&fake_board, &mock_ui, &mut rand::thread_rng());
 
-        assert_eq!(m, returned_move, "Player should return move given by Ui");
+        assert_eq!(
+            m,
+            PlayerAction::Move(returned_move),
+            "Player should return move given by Ui"
+        );
     }
 
     #[test]
     fn get_cpu_move() {
         let mock_ui = MockUi::builder().build();
         let fake_board = Board::new();
-        let cpu = Player::CPU;
+        let cpu = Player::CPU(Difficulty::PERFECT);
+
+        cpu.get_move(&fake_board, &mock_ui, &mut rand::thread_rng());
+    }
+
+    #[test]
+    fn cpu_difficulty() {
+        let mock_ui = MockUi::builder().build();
+        let board = Board::new();
+        let mut rng = rand::thread_rng();
+
+        let hopeless_cpu = Player::CPU(Difficulty::with_mistake_chance(100));
+        for _ in 0..20 {
+            let PlayerAction::Move(m) = hopeless_cpu.get_move(&board, &mock_ui, &mut rng) else {
+                panic!("CPU should always make a move");
+            };
+            assert!(
+                board.is_valid_move(&m),
+                "Random moves should still be valid"
+            );
+        }
+
+        assert_eq!(
+            Difficulty::with_mistake_chance(250).mistake_chance,
+            100,
+            "Mistake chance can't exceed 100%"
+        );
+    }
+
+    #[test]
+    fn cpu_tie_break_preference() {
+        let mock_ui = MockUi::builder().build();
+        let mut rng = rand::thread_rng();
+
+        // Every first move leads to a draw, so the preference decides
+        let corner_cpu = Player::CPU(Difficulty::PERFECT.preference(vec![8, 4, 0]));
+        for _ in 0..10 {
+            assert_eq!(
+                corner_cpu.get_move(&Board::new(), &mock_ui, &mut rng),
+                PlayerAction::Move(BoardMove::try_new(9).unwrap()),
+                "Most preferred of the tied moves should be picked"
+            );
+        }
+
+        // Only the center avoids a loss, so it beats the more preferred corner
+        let board: Board = "O........".parse().unwrap();
+        for _ in 0..10 {
+            assert_eq!(
+                corner_cpu.get_move(&board, &mock_ui, &mut rng),
+                PlayerAction::Move(BoardMove::try_new(5).unwrap()),
+                "Preference shouldn't override better moves"
+            );
+        }
+    }
+
+    #[test]
+    fn valid_preferences() {
+        assert!(Difficulty::is_valid_preference(&[]));
+        assert!(Difficulty::is_valid_preference(&[4, 0, 2]));
+        assert!(
+            Difficulty::is_valid_preference(&[8, 7, 6, 5, 4, 3, 2, 1, 0]),
+            "Every permutation of the cells should be accepted"
+        );
+        assert!(
+            !Difficulty::is_valid_preference(&[4, 0, 4]),
+            "Cell can't be listed twice"
+        );
+        assert!(
+            !Difficulty::is_valid_preference(&[9]),
+            "Only cells of the board can be listed"
+        );
+    }
+
+    #[test]
+    fn player_kind() {
+        let human = Player::Human(String::from("Steve"));
+        let cpu = Player::CPU(Difficulty::PERFECT);
+
+        assert!(human.is_human(), "Human player should be human");
+        assert!(!human.is_cpu(), "Human player shouldn't be CPU");
+        assert!(cpu.is_cpu(), "CPU player should be CPU");
+        assert!(!cpu.is_human(), "CPU player shouldn't be human");
+    }
+
+    #[test]
+    fn player_equality() {
+        let steve = Player::Human(String::from("Steve"));
 
-        cpu.get_move(&fake_board, &mock_ui, None);
+        assert_eq!(steve, Player::Human(String::from("Steve")));
+        assert_ne!(
+            steve,
+            Player::Human(String::from("Elon")),
+            "Humans with different names are different players"
+        );
+        assert_ne!(steve, Player::CPU(Difficulty::PERFECT));
+        assert_eq!(
+            Player::CPU(Difficulty::PERFECT),
+            Player::CPU(Difficulty::with_mistake_chance(0))
+        );
+        assert_ne!(
+            Player::CPU(Difficulty::PERFECT),
+            Player::CPU(Difficulty::with_mistake_chance(50)),
+            "CPUs of different strength are different players"
+        );
     }
 
     #[test]
@@ -73,7 +254,7 @@ mod tests {
 
         assert_eq!(human.get_name(), "Steve");
 
-        let cpu = Player::CPU;
+        let cpu = Player::CPU(Difficulty::PERFECT);
 
         assert_eq!(cpu.get_name(), "CPU");
     }
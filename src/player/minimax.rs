@@ -1,103 +1,480 @@
 //! This module contains minimax algorithm implementation.
 
-use crate::board::{Board, BoardMove, Cell, WINNING_LINES};
+use crate::board::{Board, BoardMove, Cell, TerminalOutcome};
 use rand::Rng;
 use std::cmp;
+use std::time::{Duration, Instant};
 
-pub fn calculate_best_move(board: &Board) -> BoardMove {
-    let maximizing_player_symbol = board.current_player_symbol();
+/// Bound of the heuristic score given to positions at the depth limit, below the score of any
+/// forced result, so that the search never mistakes a guess for a proven win or loss.
+const MAX_HEURISTIC_SCORE: i32 = 50;
 
-    let mut best_moves = vec![];
-    let mut best_eval = -1000;
+/// Statistics of a single search, for tuning and benchmarking the CPU.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SearchStats {
+    /// Positions visited by the search
+    pub nodes: u64,
+    /// Deepest position visited, in moves from the searched position
+    pub max_depth: u32,
+    /// Branches skipped by alpha-beta pruning
+    pub cutoffs: u64,
+}
+
+/// Returns one of the best moves in the position, picked at random, or `None` if the board is full
+/// and there's no move to make.
+pub fn calculate_best_move(board: &Board, rng: &mut impl Rng) -> Option<BoardMove> {
+    calculate_preferred_move(board, &[], rng)
+}
+
+/// Like `calculate_best_move`, but also returns statistics of the search. When the move is found
+/// without searching (an immediate win or block), the statistics are all zeros.
+///
+/// # Examples
+///
+/// ```
+/// use tic_tac_terminal::analysis;
+/// use tic_tac_terminal::board::Board;
+///
+/// let board: Board = "O...X....".parse().unwrap();
+/// let (_board_move, stats) =
+///     analysis::calculate_best_move_with_stats(&board, &mut rand::thread_rng()).unwrap();
+///
+/// assert!(stats.nodes > 0);
+/// ```
+pub fn calculate_best_move_with_stats(
+    board: &Board,
+    rng: &mut impl Rng,
+) -> Option<(BoardMove, SearchStats)> {
+    pick_move(board, &[], false, None, rng)
+}
+
+/// Like `calculate_best_move`, but searches by iterative deepening: one move ahead, then two and
+/// so on, until the result is certain or `time_budget` runs out. Returns one of the best moves of
+/// the deepest search that finished in time; searching one move ahead always finishes.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use tic_tac_terminal::analysis;
+/// use tic_tac_terminal::board::Board;
+///
+/// let board: Board = "O...X....".parse().unwrap();
+/// let board_move =
+///     analysis::calculate_best_move_within(&board, Duration::from_millis(50), &mut rand::thread_rng());
+///
+/// assert!(board_move.is_some_and(|board_move| board.is_valid_move(&board_move)));
+/// ```
+pub fn calculate_best_move_within(
+    board: &Board,
+    time_budget: Duration,
+    rng: &mut impl Rng,
+) -> Option<BoardMove> {
+    pick_move(board, &[], false, Some(time_budget), rng).map(|(board_move, _stats)| board_move)
+}
+
+/// Like `calculate_best_move`, but breaks ties between equally good moves using `preference`: a
+/// list of cell indices, most preferred first. Falls back to a random pick when none of the best
+/// moves is listed.
+pub fn calculate_preferred_move(
+    board: &Board,
+    preference: &[usize],
+    rng: &mut impl Rng,
+) -> Option<BoardMove> {
+    pick_move(board, preference, false, None, rng).map(|(board_move, _stats)| board_move)
+}
+
+/// Like `calculate_preferred_move`, but unless the CPU can force a win, it keeps the game alive:
+/// among equally good moves it only considers those leaving the most lines open for either
+/// player, so the game doesn't fizzle out into an early draw. The preference applies after that.
+pub fn calculate_entertaining_move(
+    board: &Board,
+    preference: &[usize],
+    rng: &mut impl Rng,
+) -> Option<BoardMove> {
+    pick_move(board, preference, true, None, rng).map(|(board_move, _stats)| board_move)
+}
+
+fn pick_move(
+    board: &Board,
+    preference: &[usize],
+    entertain: bool,
+    time_budget: Option<Duration>,
+    rng: &mut impl Rng,
+) -> Option<(BoardMove, SearchStats)> {
+    if board.is_full() {
+        return None;
+    }
+
+    let symbol = board.current_player_symbol();
+    let mut best_moves = immediate_moves(board);
+    let mut can_win = best_moves
+        .first()
+        .is_some_and(|board_move| board.is_winning_move(symbol, board_move));
+    let mut stats = SearchStats::default();
+    if best_moves.is_empty() {
+        let score;
+        (best_moves, score, stats) = match time_budget {
+            Some(time_budget) => iterative_deepening(board, Instant::now() + time_budget),
+            None => {
+                let mut search = Search::new(true);
+                let (best_moves, score) = search.evaluate(board);
+                (best_moves, score, search.stats)
+            }
+        };
+        can_win = score > MAX_HEURISTIC_SCORE;
+    }
 
-    for board_move in board.get_possible_moves() {
-        let mut next_board = board.clone();
-        next_board[board_move.index()] = maximizing_player_symbol;
+    if entertain && !can_win {
+        best_moves = liveliest_moves(board, best_moves);
+    }
 
-        let score = minimax(&next_board, &maximizing_player_symbol, false, 1);
-        if score > best_eval {
-            best_eval = score;
-            best_moves.clear();
-            best_moves.push(board_move);
-        } else if score == best_eval {
-            best_moves.push(board_move);
+    let best_move = preference
+        .iter()
+        .find_map(|&index| best_moves.iter().find(|m| m.index() == index))
+        .copied()
+        .unwrap_or_else(|| best_moves[rng.gen_range(0..best_moves.len())]);
+    debug_event!(
+        "event=cpu_move cell={} candidates={:?} nodes={}",
+        best_move.index() + 1,
+        best_moves.iter().map(|m| m.index() + 1).collect::<Vec<_>>(),
+        stats.nodes
+    );
+    Some((best_move, stats))
+}
+
+/// Searches the whole game tree and returns all moves with the best score, as well as that score.
+/// Positive scores mean the player to move can force a win, negative ones a loss and zero a draw.
+pub fn evaluate(board: &Board) -> (Vec<BoardMove>, i32) {
+    Search::new(true).evaluate(board)
+}
+
+/// Searches one move ahead, then two and so on, until the result is certain or the deadline
+/// passes. Returns the best moves and score of the deepest finished search, together with the
+/// statistics of all of them.
+fn iterative_deepening(board: &Board, deadline: Instant) -> (Vec<BoardMove>, i32, SearchStats) {
+    let empty_cells = board.get_possible_moves().len() as i32;
+    let mut stats = SearchStats::default();
+    let mut result = (vec![], 0);
+
+    for depth_limit in 1..=empty_cells {
+        let mut search = Search::new(true);
+        search.depth_limit = Some(depth_limit);
+        // The shallowest search is cheap and always finishes, so there's always a move
+        if depth_limit > 1 {
+            search.deadline = Some(deadline);
+        }
+
+        let depth_result = search.evaluate(board);
+        stats.nodes += search.stats.nodes;
+        stats.max_depth = stats.max_depth.max(search.stats.max_depth);
+        stats.cutoffs += search.stats.cutoffs;
+        if search.timed_out {
+            break;
+        }
+
+        result = depth_result;
+        if result.1.abs() > MAX_HEURISTIC_SCORE {
+            break; // Forced result, searching deeper won't change it
         }
     }
 
-    let mut rng = rand::thread_rng();
-    let random_index = rng.gen_range(0..best_moves.len());
-    best_moves[random_index]
+    (result.0, result.1, stats)
 }
 
-fn minimax(board: &Board, maximizing_player_symbol: &Cell, is_maximizing: bool, depth: i32) -> i32 {
-    if let Some(winning_line_index) = board.get_winning_line() {
-        let winner_symbol = board[WINNING_LINES[winning_line_index][0]];
+/// Returns moves which win immediately or, if there are none, moves which block the opponent's
+/// immediate win. Empty if neither exists and the full search is needed.
+fn immediate_moves(board: &Board) -> Vec<BoardMove> {
+    let symbol = board.current_player_symbol();
+
+    let winning_moves = threat_moves(board, symbol);
+    if !winning_moves.is_empty() {
+        return winning_moves;
+    }
+
+    threat_moves(board, symbol.opposite())
+}
 
-        return if &winner_symbol == maximizing_player_symbol {
-            100 - depth
+/// Returns the moves completing a line for `symbol`, ordered by cell.
+fn threat_moves(board: &Board, symbol: Cell) -> Vec<BoardMove> {
+    let mut moves: Vec<_> = board
+        .almost_winning_lines(symbol)
+        .into_iter()
+        .flat_map(|line| board.run(line))
+        .filter(|&index| matches!(board[index], Cell::Empty(_)))
+        .map(|index| BoardMove::try_new(index + 1).unwrap())
+        .collect();
+
+    moves.sort_by_key(BoardMove::index);
+    moves.dedup();
+    moves
+}
+
+/// Keeps the moves after which the most lines can still be completed by either player.
+fn liveliest_moves(board: &Board, moves: Vec<BoardMove>) -> Vec<BoardMove> {
+    let symbol = board.current_player_symbol();
+    let open_lines = |board_move: &BoardMove| {
+        let mut next_board = *board;
+        next_board.place_unchecked(board_move, symbol);
+        next_board.remaining_winnable_for(Cell::O) + next_board.remaining_winnable_for(Cell::X)
+    };
+
+    let most_open_lines = moves.iter().map(open_lines).max().unwrap_or_default();
+    moves
+        .into_iter()
+        .filter(|board_move| open_lines(board_move) == most_open_lines)
+        .collect()
+}
+
+/// Orders moves so that the most promising ones are searched first, which lets alpha-beta pruning
+/// cut off more branches: winning moves, then the center, then corners, then by the line-score
+/// heuristic of the resulting position.
+fn ordered_moves(board: &Board, symbol: Cell) -> Vec<BoardMove> {
+    let mut moves = board.get_possible_moves();
+
+    moves.sort_by_cached_key(|board_move| {
+        let index = board_move.index();
+        let rank = if board.is_winning_move(symbol, board_move) {
+            0
+        } else if index == 4 {
+            1
+        } else if [0, 2, 6, 8].contains(&index) {
+            2
         } else {
-            depth - 100
+            3
         };
-    } else if board.is_full() {
-        return 0;
+
+        let mut next_board = *board;
+        next_board.place_unchecked(board_move, symbol);
+        (rank, -next_board.score_lines_for(symbol))
+    });
+
+    moves
+}
+
+/// Minimax search with alpha-beta pruning. Keeps statistics of the search.
+struct Search {
+    order_moves: bool,
+    prune: bool,              // Only turned off to measure what pruning saves
+    depth_limit: Option<i32>, // Positions this deep are scored by the heuristic
+    deadline: Option<Instant>,
+    timed_out: bool, // Result can't be trusted once set
+    stats: SearchStats,
+}
+
+impl Search {
+    fn new(order_moves: bool) -> Self {
+        Self {
+            order_moves,
+            prune: true,
+            depth_limit: None,
+            deadline: None,
+            timed_out: false,
+            stats: SearchStats::default(),
+        }
+    }
+
+    fn evaluate(&mut self, board: &Board) -> (Vec<BoardMove>, i32) {
+        let maximizing_player_symbol = board.current_player_symbol();
+
+        let mut best_moves = vec![];
+        let mut best_eval = -1000;
+
+        for board_move in self.moves(board, maximizing_player_symbol) {
+            let mut next_board = *board;
+            next_board.place_unchecked(&board_move, maximizing_player_symbol);
+
+            // Every move gets a full window, so that all moves sharing the best score are found
+            let score = self.minimax(
+                &next_board,
+                &maximizing_player_symbol,
+                false,
+                1,
+                -1000,
+                1000,
+            );
+            if score > best_eval {
+                best_eval = score;
+                best_moves.clear();
+                best_moves.push(board_move);
+            } else if score == best_eval {
+                best_moves.push(board_move);
+            }
+        }
+
+        best_moves.sort_by_key(BoardMove::index);
+        (best_moves, best_eval)
     }
 
-    let (cmp_function, initial_score, current_player_symbol): (fn(i32, i32) -> i32, i32, Cell) =
-        if is_maximizing {
-            (cmp::max, -1000, *maximizing_player_symbol)
+    fn moves(&self, board: &Board, symbol: Cell) -> Vec<BoardMove> {
+        if self.order_moves {
+            ordered_moves(board, symbol)
         } else {
-            (cmp::min, 1000, maximizing_player_symbol.opposite())
+            board.get_possible_moves()
+        }
+    }
+
+    fn minimax(
+        &mut self,
+        board: &Board,
+        maximizing_player_symbol: &Cell,
+        is_maximizing: bool,
+        depth: i32,
+        mut alpha: i32,
+        mut beta: i32,
+    ) -> i32 {
+        self.stats.nodes += 1;
+        self.stats.max_depth = self.stats.max_depth.max(depth as u32);
+
+        match board.terminal_outcome() {
+            Some(TerminalOutcome::Win(symbol)) if symbol == *maximizing_player_symbol => {
+                return 100 - depth
+            }
+            Some(TerminalOutcome::Win(_)) => return depth - 100,
+            Some(TerminalOutcome::Draw) => return 0,
+            None => (),
+        }
+
+        if self.depth_limit.is_some_and(|limit| depth >= limit) {
+            return board
+                .score_lines_for(*maximizing_player_symbol)
+                .clamp(-MAX_HEURISTIC_SCORE, MAX_HEURISTIC_SCORE);
+        }
+        if self.timed_out
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.timed_out = true;
+            return 0;
+        }
+
+        let current_player_symbol = if is_maximizing {
+            *maximizing_player_symbol
+        } else {
+            maximizing_player_symbol.opposite()
         };
 
-    let mut best_score = initial_score;
-    for board_move in board.get_possible_moves() {
-        let mut next_board = board.clone();
-        next_board[board_move.index()] = current_player_symbol;
-        let value = minimax(
-            &next_board,
-            maximizing_player_symbol,
-            !is_maximizing,
-            depth + 1,
-        );
-        best_score = cmp_function(best_score, value);
+        let mut best_score = if is_maximizing { -1000 } else { 1000 };
+        for board_move in self.moves(board, current_player_symbol) {
+            let mut next_board = *board;
+            next_board.place_unchecked(&board_move, current_player_symbol);
+            let value = self.minimax(
+                &next_board,
+                maximizing_player_symbol,
+                !is_maximizing,
+                depth + 1,
+                alpha,
+                beta,
+            );
+
+            if is_maximizing {
+                best_score = cmp::max(best_score, value);
+                alpha = cmp::max(alpha, value);
+            } else {
+                best_score = cmp::min(best_score, value);
+                beta = cmp::min(beta, value);
+            }
+            if self.prune && alpha >= beta {
+                self.stats.cutoffs += 1;
+                break;
+            }
+        }
+        best_score
     }
-    return best_score;
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::collections::{HashMap, HashSet};
+
+    /// Independent brute-force evaluator used as a reference for the minimax implementation.
+    /// Returns the game-theoretic value of the position for the player to move: 1 for a win,
+    /// 0 for a draw and -1 for a loss. Evaluated positions are cached, keyed by their base-3
+    /// encoding.
+    fn oracle_value(board: &Board, cache: &mut HashMap<u32, i32>) -> i32 {
+        let key = position_key(board);
+        if let Some(&value) = cache.get(&key) {
+            return value;
+        }
+
+        let value = if board.get_winning_line().is_some() {
+            -1 // The previous player completed a line, so the player to move has lost
+        } else if board.is_full() {
+            0
+        } else {
+            let symbol = board.current_player_symbol();
+            let mut best = -1;
+            for index in 0..9 {
+                if let Cell::Empty(_) = board[index] {
+                    let mut next_board = *board;
+                    next_board[index] = symbol;
+                    best = best.max(-oracle_value(&next_board, cache));
+                }
+            }
+            best
+        };
+
+        cache.insert(key, value);
+        value
+    }
+
+    fn position_key(board: &Board) -> u32 {
+        board.iter().fold(0, |key, cell| {
+            key * 3 + cell.symbol_id().map_or(0, |id| u32::from(id) + 1)
+        })
+    }
+
+    fn random_position(rng: &mut StdRng) -> Option<Board> {
+        let mut board = Board::new();
+        let plies = rng.gen_range(0..9);
+
+        for _ in 0..plies {
+            let moves = board.get_possible_moves();
+            let board_move = moves[rng.gen_range(0..moves.len())];
+            board[board_move.index()] = board.current_player_symbol();
+
+            if board.get_winning_line().is_some() {
+                return None;
+            }
+        }
+
+        if board.is_full() {
+            None
+        } else {
+            Some(board)
+        }
+    }
 
     #[test]
     fn cpu_makes_valid_moves() {
         let boards = vec![
             Board::new(),
-            Board::from([
-                Cell::O,
-                Cell::O,
-                Cell::X,
-                Cell::X,
-                Cell::Empty('5'),
-                Cell::Empty('6'),
-                Cell::Empty('7'),
-                Cell::Empty('8'),
-                Cell::Empty('9'),
-            ]),
-            Board::from([
-                Cell::O,
-                Cell::Empty('2'),
-                Cell::Empty('3'),
-                Cell::Empty('4'),
-                Cell::O,
-                Cell::Empty('6'),
-                Cell::Empty('7'),
-                Cell::X,
-                Cell::Empty('9'),
-            ]),
+            Board::from_layout(
+                "O | O | X
+                 X | 5 | 6
+                 7 | 8 | 9",
+            )
+            .unwrap(),
+            Board::from_layout(
+                "O | 2 | 3
+                 4 | O | 6
+                 7 | X | 9",
+            )
+            .unwrap(),
+            Board::from_layout(
+                "# | 2 | #
+                 4 | # | 6
+                 # | 8 | #",
+            )
+            .unwrap(),
         ];
 
         for board in boards {
-            let m = calculate_best_move(&board);
+            let m = calculate_best_move(&board, &mut rand::thread_rng()).unwrap();
 
             assert!(
                 board.is_valid_move(&m),
@@ -110,51 +487,36 @@ mod tests {
     fn cpu_wins_whenever_possible() {
         let boards_and_expected_moves = vec![
             (
-                Board::from([
-                    Cell::O,
-                    Cell::Empty('2'),
-                    Cell::X,
-                    Cell::O,
-                    Cell::O,
-                    Cell::Empty('6'),
-                    Cell::Empty('7'),
-                    Cell::Empty('8'),
-                    Cell::X,
-                ]),
+                Board::from_layout(
+                    "O | 2 | X
+                     O | O | 6
+                     7 | 8 | X",
+                )
+                .unwrap(),
                 BoardMove::try_new(6).unwrap(),
             ),
             (
-                Board::from([
-                    Cell::X,
-                    Cell::Empty('2'),
-                    Cell::X,
-                    Cell::Empty('4'),
-                    Cell::O,
-                    Cell::X,
-                    Cell::O,
-                    Cell::Empty('8'),
-                    Cell::O,
-                ]),
+                Board::from_layout(
+                    "X | 2 | X
+                     4 | O | X
+                     O | 8 | O",
+                )
+                .unwrap(),
                 BoardMove::try_new(8).unwrap(),
             ),
             (
-                Board::from([
-                    Cell::O,
-                    Cell::X,
-                    Cell::Empty('3'),
-                    Cell::O,
-                    Cell::X,
-                    Cell::Empty('6'),
-                    Cell::Empty('7'),
-                    Cell::Empty('8'),
-                    Cell::Empty('9'),
-                ]),
+                Board::from_layout(
+                    "O | X | 3
+                     O | X | 6
+                     7 | 8 | 9",
+                )
+                .unwrap(),
                 BoardMove::try_new(7).unwrap(),
             ),
         ];
 
         for (board, expected_move) in boards_and_expected_moves {
-            let m = calculate_best_move(&board);
+            let m = calculate_best_move(&board, &mut rand::thread_rng()).unwrap();
 
             assert_eq!(
                 m, expected_move,
@@ -169,51 +531,36 @@ mod tests {
             // In this first case CPU is lost nonetheless, but putting an 'X' in cell 7 prolongs
             // the game
             (
-                Board::from([
-                    Cell::O,
-                    Cell::Empty('2'),
-                    Cell::X,
-                    Cell::O,
-                    Cell::Empty('5'),
-                    Cell::Empty('6'),
-                    Cell::Empty('7'),
-                    Cell::Empty('8'),
-                    Cell::Empty('9'),
-                ]),
+                Board::from_layout(
+                    "O | 2 | X
+                     O | 5 | 6
+                     7 | 8 | 9",
+                )
+                .unwrap(),
                 BoardMove::try_new(7).unwrap(),
             ),
             (
-                Board::from([
-                    Cell::X,
-                    Cell::O,
-                    Cell::O,
-                    Cell::Empty('4'),
-                    Cell::X,
-                    Cell::Empty('6'),
-                    Cell::Empty('7'),
-                    Cell::Empty('8'),
-                    Cell::O,
-                ]),
+                Board::from_layout(
+                    "X | O | O
+                     4 | X | 6
+                     7 | 8 | O",
+                )
+                .unwrap(),
                 BoardMove::try_new(6).unwrap(),
             ),
             (
-                Board::from([
-                    Cell::O,
-                    Cell::X,
-                    Cell::O,
-                    Cell::X,
-                    Cell::X,
-                    Cell::Empty('6'),
-                    Cell::Empty('7'),
-                    Cell::O,
-                    Cell::Empty('9'),
-                ]),
+                Board::from_layout(
+                    "O | X | O
+                     X | X | 6
+                     7 | O | 9",
+                )
+                .unwrap(),
                 BoardMove::try_new(6).unwrap(),
             ),
         ];
 
         for (board, expected_move) in boards_and_expected_moves {
-            let m = calculate_best_move(&board);
+            let m = calculate_best_move(&board, &mut rand::thread_rng()).unwrap();
 
             assert_eq!(
                 m, expected_move,
@@ -222,41 +569,72 @@ mod tests {
         }
     }
 
+    #[test]
+    fn immediate_win_and_block_shortcut() {
+        let board = Board::from_layout(
+            "O | X | 3
+             O | X | 6
+             7 | 8 | 9",
+        )
+        .unwrap();
+
+        assert_eq!(
+            immediate_moves(&board),
+            vec![BoardMove::try_new(7).unwrap()],
+            "Winning move should be taken before blocking the opponent's threat in cell 8"
+        );
+
+        let board = Board::from_layout(
+            "X | O | O
+             4 | X | 6
+             7 | 8 | O",
+        )
+        .unwrap();
+
+        assert_eq!(
+            immediate_moves(&board),
+            vec![BoardMove::try_new(6).unwrap()],
+            "Opponent's threat should be blocked when there's no winning move"
+        );
+
+        let board = Board::from_layout(
+            "O | 2 | 3
+             4 | X | 6
+             7 | 8 | 9",
+        )
+        .unwrap();
+
+        assert!(
+            immediate_moves(&board).is_empty(),
+            "Full search should be used when there are no threats"
+        );
+    }
+
     #[test]
     fn cpu_moves_in_last_free_cell() {
         let boards_and_expected_moves = vec![
             (
-                Board::from([
-                    Cell::O,
-                    Cell::Empty('2'),
-                    Cell::X,
-                    Cell::X,
-                    Cell::O,
-                    Cell::O,
-                    Cell::O,
-                    Cell::X,
-                    Cell::X,
-                ]),
+                Board::from_layout(
+                    "O | 2 | X
+                     X | O | O
+                     O | X | X",
+                )
+                .unwrap(),
                 BoardMove::try_new(2).unwrap(),
             ),
             (
-                Board::from([
-                    Cell::O,
-                    Cell::O,
-                    Cell::X,
-                    Cell::X,
-                    Cell::X,
-                    Cell::O,
-                    Cell::O,
-                    Cell::X,
-                    Cell::Empty('9'),
-                ]),
+                Board::from_layout(
+                    "O | O | X
+                     X | X | O
+                     O | X | 9",
+                )
+                .unwrap(),
                 BoardMove::try_new(9).unwrap(),
             ),
         ];
 
         for (board, expected_move) in boards_and_expected_moves {
-            let m = calculate_best_move(&board);
+            let m = calculate_best_move(&board, &mut rand::thread_rng()).unwrap();
 
             assert_eq!(
                 m, expected_move,
@@ -266,22 +644,209 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
-    fn panics_on_full_board() {
-        let full_board = Board::from([
-            Cell::O,
-            Cell::O,
-            Cell::X,
-            Cell::X,
-            Cell::X,
-            Cell::O,
-            Cell::O,
-            Cell::X,
-            Cell::O,
-        ]);
+    fn no_move_on_full_board() {
+        let full_board = Board::from_layout(
+            "O | O | X
+             X | X | O
+             O | X | O",
+        )
+        .unwrap();
 
         assert!(full_board.is_full(), "Board must be full for this test");
 
-        calculate_best_move(&full_board);
+        let mut rng = rand::thread_rng();
+        assert_eq!(calculate_best_move(&full_board, &mut rng), None);
+        assert_eq!(calculate_preferred_move(&full_board, &[4], &mut rng), None);
+        assert_eq!(
+            calculate_entertaining_move(&full_board, &[], &mut rng),
+            None
+        );
+    }
+
+    #[test]
+    fn cpu_move_matches_oracle_value() {
+        let mut rng = StdRng::seed_from_u64(860);
+        let mut cache = HashMap::new();
+        let mut checked_positions = HashSet::new();
+
+        while checked_positions.len() < 300 {
+            let Some(board) = random_position(&mut rng) else {
+                continue;
+            };
+            if !checked_positions.insert(position_key(&board)) {
+                continue;
+            }
+
+            let m = calculate_best_move(&board, &mut rand::thread_rng()).unwrap();
+            let mut next_board = board;
+            next_board[m.index()] = board.current_player_symbol();
+
+            assert_eq!(
+                -oracle_value(&next_board, &mut cache),
+                oracle_value(&board, &mut cache),
+                "CPU move should preserve the game-theoretic value of the position:\n{:?}",
+                board
+            );
+        }
+    }
+
+    #[test]
+    fn move_ordering_reduces_search() {
+        let mut rng = StdRng::seed_from_u64(878);
+        let mut boards = vec![Board::new()];
+        while boards.len() < 20 {
+            if let Some(board) = random_position(&mut rng) {
+                boards.push(board);
+            }
+        }
+
+        let mut natural = Search::new(false);
+        let mut ordered = Search::new(true);
+
+        for board in &boards {
+            assert_eq!(
+                natural.evaluate(board),
+                ordered.evaluate(board),
+                "Move ordering shouldn't change the result:\n{:?}",
+                board
+            );
+        }
+
+        assert!(
+            ordered.stats.nodes < natural.stats.nodes,
+            "Ordered search should visit fewer positions ({} vs {})",
+            ordered.stats.nodes,
+            natural.stats.nodes
+        );
+    }
+
+    #[test]
+    fn search_stats() {
+        let board = Board::new();
+        let (_board_move, stats) =
+            calculate_best_move_with_stats(&board, &mut rand::thread_rng()).unwrap();
+
+        assert!(stats.nodes > 0);
+        assert_eq!(
+            stats.max_depth, 9,
+            "Some lines are played out to a full board"
+        );
+        assert!(stats.cutoffs > 0);
+
+        let mut unpruned = Search::new(true);
+        unpruned.prune = false;
+        assert_eq!(
+            unpruned.evaluate(&board),
+            Search::new(true).evaluate(&board),
+            "Pruning shouldn't change the result"
+        );
+        assert_eq!(unpruned.stats.cutoffs, 0);
+        assert!(
+            stats.nodes < unpruned.stats.nodes,
+            "Pruning should visit fewer positions ({} vs {})",
+            stats.nodes,
+            unpruned.stats.nodes
+        );
+
+        let board: Board = "OO.XX....".parse().unwrap();
+        let (_board_move, stats) =
+            calculate_best_move_with_stats(&board, &mut rand::thread_rng()).unwrap();
+        assert_eq!(
+            stats,
+            SearchStats::default(),
+            "Immediate win is found without searching"
+        );
+    }
+
+    #[test]
+    fn iterative_deepening_matches_full_search() {
+        let mut rng = StdRng::seed_from_u64(955);
+        let mut boards = vec![Board::new()];
+        while boards.len() < 50 {
+            if let Some(board) = random_position(&mut rng) {
+                boards.push(board);
+            }
+        }
+
+        let deadline = Instant::now() + Duration::from_secs(60);
+        for board in &boards {
+            let (best_moves, score, _stats) = iterative_deepening(board, deadline);
+            assert_eq!(
+                (best_moves, score),
+                evaluate(board),
+                "Iterative deepening should find the same moves as the full search:\n{:?}",
+                board
+            );
+        }
+
+        let board = Board::new();
+        let board_move =
+            calculate_best_move_within(&board, Duration::ZERO, &mut rand::thread_rng());
+        assert!(
+            board_move.is_some_and(|board_move| board.is_valid_move(&board_move)),
+            "A move should be found even without any time"
+        );
+        let (_best_moves, _score, stats) = iterative_deepening(&board, Instant::now());
+        assert_eq!(stats.max_depth, 1, "Only the shallowest search should run");
+    }
+
+    #[test]
+    fn search_places_marks_on_empty_cells_only() {
+        // The search places marks unchecked, so debug builds would panic on any taken cell
+        let boards = ["#...O...#", "OX#.#.XO.", "..#######"];
+
+        for board in boards {
+            let board: Board = board.parse().unwrap();
+            for order_moves in [false, true] {
+                let (moves, _) = Search::new(order_moves).evaluate(&board);
+                assert!(
+                    moves
+                        .iter()
+                        .all(|board_move| board.is_valid_move(board_move)),
+                    "Search should only suggest empty cells:\n{board:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn entertainer_keeps_game_alive() {
+        // 'X' draws with either the center or cell 6, but taking the center closes more lines
+        let board: Board = "...O..X.O".parse().unwrap();
+        let (best_moves, score) = evaluate(&board);
+        assert_eq!((best_moves.len(), score), (2, 0), "Both moves should draw");
+
+        for seed in 0..20 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            assert_eq!(
+                calculate_entertaining_move(&board, &[4], &mut rng),
+                BoardMove::try_new(6).ok(),
+                "Entertainer should keep more lines open, even against its preference"
+            );
+        }
+
+        let board: Board = "OO.XX....".parse().unwrap();
+        assert_eq!(
+            calculate_entertaining_move(&board, &[], &mut rand::thread_rng()),
+            BoardMove::try_new(3).ok(),
+            "Entertainer shouldn't pass up a win"
+        );
+    }
+
+    #[test]
+    fn search_with_shorter_win_length() {
+        let board = Board::new().win_length(2);
+
+        let (moves, score) = evaluate(&board);
+        assert_eq!(
+            score, 97,
+            "'O' should win with its second move, whatever 'X' does"
+        );
+        assert_eq!(moves.len(), 9);
+
+        let board: Board = "O..X.....".parse::<Board>().unwrap().win_length(2);
+        let mut rng = StdRng::seed_from_u64(1002);
+        let best_move = calculate_best_move(&board, &mut rng).unwrap();
+        assert!(board.is_winning_move(Cell::O, &best_move));
     }
 }
@@ -0,0 +1,318 @@
+//! This module contains the scoreboard, which keeps track of every player's results across games
+//! and can aggregate them into team totals (e.g. for round-robin tournaments). Also provides
+//! scoring policies, which decide how many points a single game is worth.
+
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Record {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ScoreBoard {
+    records: HashMap<String, Record>,
+}
+
+/// Decides how many points a game is worth. By default every win is worth 1 point and draws are
+/// worth nothing.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ScoringPolicy {
+    faster_wins: bool,
+    draw_points: i32,
+    draw_is_loss: bool,
+}
+
+impl ScoringPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes quicker wins worth more: a win earns one point for every cell left empty, plus one.
+    pub fn faster_wins(mut self, faster_wins: bool) -> Self {
+        self.faster_wins = faster_wins;
+        self
+    }
+
+    /// Sets the number of points each player gets for a draw. Negative points penalize both.
+    pub fn draw_points(mut self, draw_points: i32) -> Self {
+        self.draw_points = draw_points;
+        self
+    }
+
+    /// Treats a draw as a loss for both players: each of them loses the points a win in that game
+    /// would have earned. Overrides `draw_points`.
+    pub fn draw_is_loss(mut self, draw_is_loss: bool) -> Self {
+        self.draw_is_loss = draw_is_loss;
+        self
+    }
+
+    /// Returns the number of points for a win in a game which took `move_count` moves (counting
+    /// both players).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::ScoringPolicy;
+    ///
+    /// let policy = ScoringPolicy::new().faster_wins(true);
+    ///
+    /// assert_eq!(policy.points_for_win(5), 5);
+    /// assert_eq!(policy.points_for_win(9), 1);
+    /// assert_eq!(ScoringPolicy::new().points_for_win(5), 1);
+    /// ```
+    pub fn points_for_win(&self, move_count: usize) -> i32 {
+        if self.faster_wins {
+            (10 - move_count as i32).max(1)
+        } else {
+            1
+        }
+    }
+
+    /// Returns the number of points each player gets for a draw in a game which took `move_count`
+    /// moves (counting both players).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::ScoringPolicy;
+    ///
+    /// let policy = ScoringPolicy::new().faster_wins(true).draw_is_loss(true);
+    ///
+    /// assert_eq!(policy.points_for_draw(7), -3);
+    /// assert_eq!(ScoringPolicy::new().draw_points(1).points_for_draw(7), 1);
+    /// ```
+    pub fn points_for_draw(&self, move_count: usize) -> i32 {
+        if self.draw_is_loss {
+            -self.points_for_win(move_count)
+        } else {
+            self.draw_points
+        }
+    }
+}
+
+impl Record {
+    fn add(&mut self, other: &Record) {
+        self.wins += other.wins;
+        self.draws += other.draws;
+        self.losses += other.losses;
+    }
+}
+
+impl ScoreBoard {
+    /// Creates an empty scoreboard.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::ScoreBoard;
+    ///
+    /// let mut scoreboard = ScoreBoard::new();
+    /// scoreboard.record_win("Steve", "CPU");
+    ///
+    /// assert_eq!(scoreboard.record("Steve").wins, 1);
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_win(&mut self, winner_name: &str, loser_name: &str) {
+        self.entry(winner_name).wins += 1;
+        self.entry(loser_name).losses += 1;
+    }
+
+    pub fn record_draw(&mut self, player1_name: &str, player2_name: &str) {
+        self.entry(player1_name).draws += 1;
+        self.entry(player2_name).draws += 1;
+    }
+
+    /// Returns results of a single player. Players who haven't played yet have an empty record.
+    pub fn record(&self, player_name: &str) -> Record {
+        self.records.get(player_name).copied().unwrap_or_default()
+    }
+
+    /// Returns results of every player who has played, sorted by name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::ScoreBoard;
+    ///
+    /// let mut scoreboard = ScoreBoard::new();
+    /// scoreboard.record_win("Steve", "Elon");
+    ///
+    /// let names: Vec<_> = scoreboard.records().into_iter().map(|(name, _)| name).collect();
+    /// assert_eq!(names, ["Elon", "Steve"]);
+    /// ```
+    pub fn records(&self) -> Vec<(&str, Record)> {
+        let mut records: Vec<_> = self
+            .records
+            .iter()
+            .map(|(player_name, record)| (player_name.as_str(), *record))
+            .collect();
+        records.sort_by_key(|&(player_name, _)| player_name);
+
+        records
+    }
+
+    /// Sums up players' results by team. `teams` maps player names to team names; players without
+    /// a team aren't counted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use tic_tac_terminal::ScoreBoard;
+    ///
+    /// let mut scoreboard = ScoreBoard::new();
+    /// scoreboard.record_win("Steve", "Elon");
+    ///
+    /// let teams = HashMap::from([("Steve", "Blue"), ("Elon", "Green")]);
+    /// let totals = scoreboard.team_totals(&teams);
+    ///
+    /// assert_eq!(totals["Blue"].wins, 1);
+    /// assert_eq!(totals["Green"].losses, 1);
+    /// ```
+    pub fn team_totals(&self, teams: &HashMap<&str, &str>) -> HashMap<String, Record> {
+        let mut totals: HashMap<String, Record> = HashMap::new();
+
+        for (player_name, record) in &self.records {
+            if let Some(team_name) = teams.get(player_name.as_str()) {
+                totals.entry(team_name.to_string()).or_default().add(record);
+            }
+        }
+
+        totals
+    }
+
+    fn entry(&mut self, player_name: &str) -> &mut Record {
+        self.records.entry(player_name.to_string()).or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn individual_records() {
+        let mut scoreboard = ScoreBoard::new();
+
+        scoreboard.record_win("Steve", "Elon");
+        scoreboard.record_win("Steve", "CPU");
+        scoreboard.record_draw("Elon", "CPU");
+
+        assert_eq!(
+            scoreboard.record("Steve"),
+            Record {
+                wins: 2,
+                draws: 0,
+                losses: 0
+            }
+        );
+        assert_eq!(
+            scoreboard.record("Elon"),
+            Record {
+                wins: 0,
+                draws: 1,
+                losses: 1
+            }
+        );
+        assert_eq!(
+            scoreboard.record("Llama"),
+            Record::default(),
+            "Player who hasn't played should have an empty record"
+        );
+    }
+
+    #[test]
+    fn team_totals() {
+        let mut scoreboard = ScoreBoard::new();
+
+        // Round robin between four players forming two teams
+        scoreboard.record_win("Steve", "Elon");
+        scoreboard.record_win("Llama", "Steve");
+        scoreboard.record_draw("Steve", "Alpaca");
+        scoreboard.record_win("Elon", "Llama");
+        scoreboard.record_draw("Elon", "Alpaca");
+        scoreboard.record_win("Alpaca", "Llama");
+        scoreboard.record_win("Steve", "Bystander");
+
+        let teams = HashMap::from([
+            ("Steve", "Red"),
+            ("Llama", "Red"),
+            ("Elon", "Blue"),
+            ("Alpaca", "Blue"),
+        ]);
+        let totals = scoreboard.team_totals(&teams);
+
+        assert_eq!(
+            totals.len(),
+            2,
+            "Only teams should be listed, players without a team are skipped"
+        );
+        assert_eq!(
+            totals["Red"],
+            Record {
+                wins: 3,
+                draws: 1,
+                losses: 3
+            }
+        );
+        assert_eq!(
+            totals["Blue"],
+            Record {
+                wins: 2,
+                draws: 3,
+                losses: 1
+            }
+        );
+    }
+
+    #[test]
+    fn faster_wins_score_more() {
+        let policy = ScoringPolicy::new().faster_wins(true);
+
+        assert!(
+            policy.points_for_win(5) > policy.points_for_win(9),
+            "5-move win should be worth more than a 9-move win"
+        );
+        assert!(
+            policy.points_for_win(9) > 0,
+            "Even the slowest win should be worth something"
+        );
+
+        let classic = ScoringPolicy::new();
+        assert_eq!(
+            classic.points_for_win(5),
+            classic.points_for_win(9),
+            "By default every win is worth the same"
+        );
+        assert_eq!(
+            classic.points_for_draw(9),
+            0,
+            "By default draws are worthless"
+        );
+        assert_eq!(ScoringPolicy::new().draw_points(1).points_for_draw(9), 1);
+    }
+
+    #[test]
+    fn draw_is_loss() {
+        let policy = ScoringPolicy::new().draw_points(1).draw_is_loss(true);
+
+        assert_eq!(
+            policy.points_for_draw(9),
+            -policy.points_for_win(9),
+            "Draw should cost as much as a win earns"
+        );
+        assert_eq!(policy.points_for_win(9), 1, "Wins should score as usual");
+
+        let policy = policy.faster_wins(true);
+        assert!(
+            policy.points_for_draw(7) < policy.points_for_draw(9),
+            "With faster wins, an early draw should cost more"
+        );
+    }
+}
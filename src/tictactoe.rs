@@ -1,153 +1,1863 @@
 //! This module contains main Tic-Tac-Toe application (which has the main game loop).
 
-use crate::game::{Game, GameResult};
-use crate::player::Player;
-use crate::ui::Ui;
+use crate::analysis;
+use crate::board::{Board, BoardMove};
+use crate::config::Config;
+use crate::game::{Game, GameResult, InvalidMove, MoveHook, PlayerAction, DEFAULT_MOVE_DELAY};
+use crate::player::{minimax, Difficulty, Player};
+use crate::scoreboard::{ScoreBoard, ScoringPolicy};
+use crate::ui::{AnnotationStyle, CellAnnotation, Ui};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cell::RefCell;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+/// Seed used by the demo mode, so every demo plays out the same way.
+pub const DEMO_SEED: u64 = 2024;
+const DEMO_MOVE_DELAY: Duration = Duration::from_millis(600);
+/// Practice positions are this many empty cells away from the end of the game.
+const PRACTICE_EMPTY_CELLS: std::ops::RangeInclusive<usize> = 3..=5;
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum GameMode {
     PlayerVsPlayer,
     PlayerVsCpu,
     CpuVsPlayer,
     CpuVsCpu,
+    Demo,
+    Custom(GameConfig),
+    Survival { draws_survive: bool },
+    Practice,
+    Quit,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PlayerKind {
+    Human,
+    Cpu,
+}
+
+/// Full set of options for a match. Every preset game mode is a shorthand for one of these.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameConfig {
+    pub player1: PlayerKind,
+    pub player2: PlayerKind,
+    pub seed: Option<u64>,
+    pub move_delay: Duration,
+    /// Names human players "Player 1" and "Player 2" instead of asking for their names
+    pub auto_names: bool,
+}
+
+/// Choice made on the main menu, shown at startup and after every match.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MainMenuChoice {
+    NewMatch,
+    ContinueMatch, // Only offered when there's an unfinished match
+    ViewStats,
     Quit,
 }
 
-pub struct TicTacToe<'a, T: Ui> {
-    ui: &'a T,
-    mode: Option<GameMode>,
-    scores: (i32, i32),
-}
+pub enum KeepPlaying {
+    Continue,
+    Stop,
+    Reset,                    // Play again, but start counting scores from zero
+    Swap,                     // Play again with players' sides swapped
+    ChangeOpponent(Opponent), // Play again against someone else, keeping the scores
+}
+
+/// What a won game means for the match, announced after the game's result.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Announcement {
+    /// Nothing changes at the top of the match
+    Won(String),
+    /// The winner went ahead of the other player
+    TakesLead(String),
+    /// The winner reached the points needed to win the match
+    Clinched {
+        winner: String,
+        winner_score: i32,
+        loser_score: i32,
+    },
+}
+
+/// Opponent picked between games of a match.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Opponent {
+    Human,
+    /// CPU playing a random move instead of the best one with the given chance, in percent
+    Cpu {
+        mistake_chance: u8,
+    },
+}
+
+impl Announcement {
+    /// Decides what the result of a game means for the match, given the scores from before and
+    /// after the game and the points needed to win the match, if any. Draws aren't announced.
+    fn for_result(
+        result: &GameResult,
+        scores_before: (i32, i32),
+        scores_after: (i32, i32),
+        match_target: Option<i32>,
+    ) -> Option<Self> {
+        let GameResult::PlayerWon(winner_index, winner_name, _winning_lines) = result else {
+            return None;
+        };
+        let ordered = |(player1_score, player2_score)| {
+            if *winner_index == 0 {
+                (player1_score, player2_score)
+            } else {
+                (player2_score, player1_score)
+            }
+        };
+        let (winner_before, loser_before) = ordered(scores_before);
+        let (winner_score, loser_score) = ordered(scores_after);
+        let winner = winner_name.clone();
+
+        let announcement = match match_target {
+            Some(target) if winner_before < target && winner_score >= target => Self::Clinched {
+                winner,
+                winner_score,
+                loser_score,
+            },
+            _ if winner_before <= loser_before && winner_score > loser_score => {
+                Self::TakesLead(winner)
+            }
+            _ => Self::Won(winner),
+        };
+        Some(announcement)
+    }
+}
+
+impl fmt::Display for Announcement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Won(winner) => write!(f, "{winner} won!"),
+            Self::TakesLead(winner) => write!(f, "{winner} takes the lead!"),
+            Self::Clinched {
+                winner,
+                winner_score,
+                loser_score,
+            } => write!(f, "{winner} wins the match {winner_score}-{loser_score}!"),
+        }
+    }
+}
+
+impl GameMode {
+    pub fn config(&self) -> Option<GameConfig> {
+        let (player1, player2) = match self {
+            Self::PlayerVsPlayer => (PlayerKind::Human, PlayerKind::Human),
+            Self::PlayerVsCpu => (PlayerKind::Human, PlayerKind::Cpu),
+            Self::CpuVsPlayer => (PlayerKind::Cpu, PlayerKind::Human),
+            Self::CpuVsCpu => (PlayerKind::Cpu, PlayerKind::Cpu),
+            Self::Demo => {
+                return Some(GameConfig {
+                    player1: PlayerKind::Cpu,
+                    player2: PlayerKind::Cpu,
+                    seed: Some(DEMO_SEED),
+                    move_delay: DEMO_MOVE_DELAY,
+                    auto_names: false,
+                })
+            }
+            Self::Custom(config) => return Some(config.clone()),
+            Self::Survival { .. } | Self::Practice | Self::Quit => return None, // Not a single match
+        };
+
+        Some(GameConfig {
+            player1,
+            player2,
+            seed: None,
+            move_delay: DEFAULT_MOVE_DELAY,
+            auto_names: false,
+        })
+    }
+}
+
+/// Progress of a survival run, in which a human plays a series of games against increasingly
+/// stronger CPUs until they fail to survive a round.
+struct SurvivalRun {
+    rounds_survived: u32,
+    draws_survive: bool,
+}
+
+impl SurvivalRun {
+    fn new(draws_survive: bool) -> Self {
+        Self {
+            rounds_survived: 0,
+            draws_survive,
+        }
+    }
+
+    /// CPU starts out making plenty of mistakes and becomes perfect after a few rounds.
+    fn current_difficulty(&self) -> Difficulty {
+        let mistake_chance = 60u32.saturating_sub(15 * self.rounds_survived);
+        Difficulty::with_mistake_chance(mistake_chance as u8)
+    }
+
+    /// Records the result of a round, in which the human is always player 1. Returns whether the
+    /// run goes on.
+    fn record(&mut self, result: &GameResult) -> bool {
+        let survived = match result {
+            GameResult::PlayerWon(winner_index, _winner_name, _winning_line) => *winner_index == 0,
+            GameResult::Draw(_) => self.draws_survive,
+        };
+
+        if survived {
+            self.rounds_survived += 1;
+        }
+
+        survived
+    }
+}
+
+/// Match left before it was decided, which can be picked up again from the main menu.
+struct SavedMatch {
+    mode: Option<GameMode>,
+    player1: Player,
+    player2: Player,
+    scores: (i32, i32),
+}
+
+pub struct TicTacToe<'a, T: Ui> {
+    ui: &'a T,
+    mode: Option<GameMode>,
+    scores: (i32, i32),
+    results_log: Option<PathBuf>,
+    state_dump: Option<PathBuf>,
+    starting_position: Option<Board>,
+    toroidal: bool,
+    win_length: Option<usize>,
+    kibitz: bool,
+    review: bool,
+    match_target: Option<i32>,
+    scoring: ScoringPolicy,
+    cpu_preference: Vec<usize>,
+    entertaining_cpu: bool,
+    time_bank: Option<Duration>,
+    on_move: Option<MoveHook<'a>>,
+    rng: StdRng, // Seeds every game, so a seeded session is reproducible as a whole
+    scoreboard: ScoreBoard,
+    saved_match: Option<SavedMatch>,
+}
+
+impl<'a, T: Ui> TicTacToe<'a, T> {
+    /// Creates new instance of Tic-Tac-Toe game. Accepts one argument - UI backend.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::{TerminalUi, TicTacToe};
+    ///
+    /// let ui = TerminalUi::new();
+    /// let game = TicTacToe::new(&ui);
+    /// ```
+    pub fn new(ui: &'a T) -> Self {
+        Self {
+            ui,
+            mode: None,
+            scores: (0, 0),
+            results_log: None,
+            state_dump: None,
+            starting_position: None,
+            toroidal: false,
+            win_length: None,
+            kibitz: false,
+            review: false,
+            match_target: None,
+            scoring: ScoringPolicy::default(),
+            cpu_preference: vec![],
+            entertaining_cpu: false,
+            time_bank: None,
+            on_move: None,
+            rng: StdRng::from_entropy(),
+            scoreboard: ScoreBoard::new(),
+            saved_match: None,
+        }
+    }
+
+    /// Creates new instance of Tic-Tac-Toe game with options taken from the config. Options which
+    /// the config doesn't set keep their defaults.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::{Config, TerminalUi, TicTacToe};
+    ///
+    /// let config: Config = "toroidal = true\nfaster_wins = true".parse().unwrap();
+    /// let ui = TerminalUi::with_config(&config);
+    /// let game = TicTacToe::with_config(&ui, &config);
+    /// ```
+    pub fn with_config(ui: &'a T, config: &Config) -> Self {
+        let mut game = Self::new(ui);
+
+        if let Some(path) = &config.results_log {
+            game = game.results_log(path);
+        }
+        if let Some(path) = &config.state_dump {
+            game = game.state_dump(path);
+        }
+        if let Some(enabled) = config.toroidal {
+            game = game.toroidal(enabled);
+        }
+        if let Some(win_length) = config.win_length {
+            game = game.win_length(win_length);
+        }
+        if let Some(seed) = config.seed {
+            game = game.seed(seed);
+        }
+        if let Some(enabled) = config.kibitz {
+            game = game.kibitz(enabled);
+        }
+        if let Some(enabled) = config.review {
+            game = game.review(enabled);
+        }
+        if let Some(points) = config.first_to {
+            game = game.first_to(points);
+        }
+        if let Some(enabled) = config.entertainer {
+            game = game.entertaining_cpu(enabled);
+        }
+        if let Some(preference) = &config.cpu_preference {
+            game = game.cpu_preference(preference.clone());
+        }
+        if let Some(seconds) = config.blitz {
+            game = game.blitz(Duration::from_secs(seconds));
+        }
+
+        let mut scoring = ScoringPolicy::new();
+        if let Some(faster_wins) = config.faster_wins {
+            scoring = scoring.faster_wins(faster_wins);
+        }
+        if let Some(draw_points) = config.draw_points {
+            scoring = scoring.draw_points(draw_points);
+        }
+        if let Some(draw_is_loss) = config.draw_is_loss {
+            scoring = scoring.draw_is_loss(draw_is_loss);
+        }
+
+        game.scoring(scoring)
+    }
+
+    /// Appends a line with the final board and the result of every finished game to the given
+    /// file. Logging is off by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::{TerminalUi, TicTacToe};
+    ///
+    /// let ui = TerminalUi::new();
+    /// let game = TicTacToe::new(&ui).results_log("results.log");
+    /// ```
+    pub fn results_log(mut self, path: impl AsRef<Path>) -> Self {
+        self.results_log = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Writes the state of the current game as JSON to the given file after every move, e.g.
+    /// `{"board":"....O....","move_number":1,"last_move":5,"player":"Steve","next_player":"CPU"}`.
+    /// Meant for spectating tools, such as stream overlays. Off by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::{TerminalUi, TicTacToe};
+    ///
+    /// let ui = TerminalUi::new();
+    /// let game = TicTacToe::new(&ui).state_dump("state.json");
+    /// ```
+    pub fn state_dump(mut self, path: impl AsRef<Path>) -> Self {
+        self.state_dump = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Makes the first game start from the given position instead of the empty board. Following
+    /// games start from scratch.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::{TerminalUi, TicTacToe};
+    ///
+    /// let ui = TerminalUi::new();
+    /// let game = TicTacToe::new(&ui).starting_position("O...X....".parse().unwrap());
+    /// ```
+    pub fn starting_position(mut self, board: Board) -> Self {
+        self.starting_position = Some(board);
+        self
+    }
+
+    /// Plays on a toroidal board, on which diagonals wrap around the edges. Off by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::{TerminalUi, TicTacToe};
+    ///
+    /// let ui = TerminalUi::new();
+    /// let game = TicTacToe::new(&ui).toroidal(true);
+    /// ```
+    pub fn toroidal(mut self, enabled: bool) -> Self {
+        self.toroidal = enabled;
+        self
+    }
+
+    /// Sets how many marks in a row win, so that shorter runs than a full line count. The length
+    /// is clamped to what the board allows, see [`Board::win_length`]. A full line wins by
+    /// default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::{TerminalUi, TicTacToe};
+    ///
+    /// let ui = TerminalUi::new();
+    /// let game = TicTacToe::new(&ui).win_length(2);
+    /// ```
+    pub fn win_length(mut self, win_length: usize) -> Self {
+        self.win_length = Some(win_length);
+        self
+    }
+
+    /// Judges every human move against the engine's, showing whether it was optimal and how much
+    /// it changed the value of the position. CPU moves aren't judged. Off by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::{TerminalUi, TicTacToe};
+    ///
+    /// let ui = TerminalUi::new();
+    /// let game = TicTacToe::new(&ui).kibitz(true);
+    /// ```
+    pub fn kibitz(mut self, enabled: bool) -> Self {
+        self.kibitz = enabled;
+        self
+    }
+
+    /// Reviews every game once it's over, listing the moves which weren't the best ones along
+    /// with better alternatives, so that players can learn from their mistakes. Off by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::{TerminalUi, TicTacToe};
+    ///
+    /// let ui = TerminalUi::new();
+    /// let game = TicTacToe::new(&ui).review(true);
+    /// ```
+    pub fn review(mut self, enabled: bool) -> Self {
+        self.review = enabled;
+        self
+    }
+
+    /// Ends a match once either player reaches the given number of points, announcing that they
+    /// won the match. By default matches go on until the players stop.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::{TerminalUi, TicTacToe};
+    ///
+    /// let ui = TerminalUi::new();
+    /// let game = TicTacToe::new(&ui).first_to(3);
+    /// ```
+    pub fn first_to(mut self, points: i32) -> Self {
+        self.match_target = Some(points);
+        self
+    }
+
+    /// Sets how many points each game is worth. By default a win is worth 1 point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::{ScoringPolicy, TerminalUi, TicTacToe};
+    ///
+    /// let ui = TerminalUi::new();
+    /// let game = TicTacToe::new(&ui).scoring(ScoringPolicy::new().faster_wins(true));
+    /// ```
+    pub fn scoring(mut self, scoring: ScoringPolicy) -> Self {
+        self.scoring = scoring;
+        self
+    }
+
+    /// Sets which cells (by index, most preferred first) the CPU picks when several moves are
+    /// equally good, e.g. `vec![4, 0, 2, 6, 8]` for center, then corners. By default ties are
+    /// broken at random.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a cell is listed twice or isn't a cell of the board.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::{TerminalUi, TicTacToe};
+    ///
+    /// let ui = TerminalUi::new();
+    /// let game = TicTacToe::new(&ui).cpu_preference(vec![4, 0, 2, 6, 8]);
+    /// ```
+    pub fn cpu_preference(mut self, preference: Vec<usize>) -> Self {
+        assert!(
+            Difficulty::is_valid_preference(&preference),
+            "Invalid CPU preference: {preference:?}"
+        );
+        self.cpu_preference = preference;
+        self
+    }
+
+    /// Makes the whole session reproducible: the CPU's choices among equally good moves, its
+    /// mistakes and practice positions all follow from the seed. Without a seed they're random.
+    /// The demo mode always plays the same game regardless.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::{TerminalUi, TicTacToe};
+    ///
+    /// let ui = TerminalUi::new();
+    /// let game = TicTacToe::new(&ui).seed(42);
+    /// ```
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    /// Calls `hook` after every move of every game with the board after the move, the move itself
+    /// and the number of moves played in that game so far. A lightweight way to log or script
+    /// games without implementing a UI.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::{TerminalUi, TicTacToe};
+    ///
+    /// let ui = TerminalUi::new();
+    /// let game = TicTacToe::new(&ui).on_move(|_board, board_move, move_number| {
+    ///     println!("Move {move_number}: {}", board_move.index() + 1);
+    /// });
+    /// ```
+    pub fn on_move(mut self, hook: impl FnMut(&Board, BoardMove, usize) + 'a) -> Self {
+        self.on_move = Some(Rc::new(RefCell::new(hook)));
+        self
+    }
+
+    /// Makes the CPU play to keep the game going when it can't win: among equally good moves it
+    /// picks those leaving the most lines open, instead of heading straight for a draw. Off by
+    /// default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::{TerminalUi, TicTacToe};
+    ///
+    /// let ui = TerminalUi::new();
+    /// let game = TicTacToe::new(&ui).entertaining_cpu(true);
+    /// ```
+    pub fn entertaining_cpu(mut self, enabled: bool) -> Self {
+        self.entertaining_cpu = enabled;
+        self
+    }
+
+    /// Plays blitz games: each player gets the given time for the whole game, running down only
+    /// during their turns. Whoever runs out of time loses. Games are untimed by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use tic_tac_terminal::{TerminalUi, TicTacToe};
+    ///
+    /// let ui = TerminalUi::new();
+    /// let game = TicTacToe::new(&ui).blitz(Duration::from_secs(60));
+    /// ```
+    pub fn blitz(mut self, time_bank: Duration) -> Self {
+        self.time_bank = Some(time_bank);
+        self
+    }
+
+    /// Starts the Tic-Tac-Toe application.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tic_tac_terminal::{TerminalUi, TicTacToe};
+    ///
+    /// let ui = TerminalUi::new();
+    /// let mut game = TicTacToe::new(&ui);
+    /// game.start();
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics at every time the UI panics (pretty much only on stdin and stdout errors).
+    pub fn start(&mut self) {
+        loop {
+            self.ui.clear();
+
+            match self.ui.main_menu(self.saved_match.is_some()) {
+                MainMenuChoice::NewMatch => self.start_new_match(),
+                MainMenuChoice::ContinueMatch => {
+                    if let Some(saved) = self.saved_match.take() {
+                        self.mode = saved.mode;
+                        self.scores = saved.scores;
+                        self.play_match(saved.player1, saved.player2);
+                    }
+                }
+                MainMenuChoice::ViewStats => self.ui.show_stats(&self.scoreboard),
+                MainMenuChoice::Quit => break,
+            }
+        }
+    }
+
+    /// Asks for the game mode and plays it. Quitting the mode selection goes back to the main
+    /// menu.
+    fn start_new_match(&mut self) {
+        self.ui.clear();
+        self.mode = Some(self.ui.select_mode());
+        self.scores = (0, 0);
+
+        if let Some(GameMode::Survival { draws_survive }) = self.mode {
+            self.ui.clear();
+            let human = Player::Human(self.ui.get_player_name("Player1"));
+            self.play_survival(&human, draws_survive);
+        } else if let Some(GameMode::Practice) = self.mode {
+            self.ui.clear();
+            let name = self.ui.get_player_name("Player1");
+            let mut rng = self.rng.clone();
+            self.play_practice(&name, &mut rng);
+            self.rng = rng;
+        } else if let Some((player1, player2)) = self.create_players() {
+            self.play_match(player1, player2);
+        }
+    }
+
+    /// Plays exactly one game of the given mode, without the mode selection menu and without
+    /// asking whether to play again. Human players are still asked for their names.
+    ///
+    /// Returns the result of the game, or `Err(())` if the mode isn't a single match (survival,
+    /// practice, quit) or the game was abandoned.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tic_tac_terminal::{GameMode, GameResult, TerminalUi, TicTacToe};
+    ///
+    /// let ui = TerminalUi::new();
+    /// let mut game = TicTacToe::new(&ui);
+    /// let result = game.run_once(GameMode::CpuVsCpu);
+    ///
+    /// assert!(matches!(result, Ok(GameResult::Draw(_))));
+    /// ```
+    #[allow(clippy::result_unit_err)]
+    pub fn run_once(&mut self, mode: GameMode) -> Result<GameResult, ()> {
+        self.mode = Some(mode);
+        self.scores = (0, 0);
+
+        let (player1, player2) = self.create_players().ok_or(())?;
+        self.play_game(&player1, &player2)
+    }
+
+    /// Returns every player's results from all games played so far, the same ones the stats
+    /// screen shows. Records are kept by name; players sharing a name in the same game (such as
+    /// two CPUs) are told apart by their seats, e.g. "CPU 1" and "CPU 2".
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tic_tac_terminal::{GameMode, TerminalUi, TicTacToe};
+    ///
+    /// let ui = TerminalUi::new();
+    /// let mut game = TicTacToe::new(&ui);
+    /// game.run_once(GameMode::CpuVsCpu).unwrap();
+    ///
+    /// assert_eq!(game.stats().record("CPU 1").draws, 1);
+    /// ```
+    pub fn stats(&self) -> &ScoreBoard {
+        &self.scoreboard
+    }
+
+    fn play_survival(&mut self, human: &Player, draws_survive: bool) -> u32 {
+        debug_assert!(
+            human.is_human(),
+            "Survival run is recorded from the human's side"
+        );
+        let mut run = SurvivalRun::new(draws_survive);
+
+        loop {
+            let cpu = Player::CPU(run.current_difficulty());
+            let mut game = self.build_game(human, &cpu);
+
+            let survived = match game.start() {
+                Ok(result) => {
+                    self.record_result(human, &cpu, &result);
+                    self.log_result(game.board(), &result);
+                    run.record(&result)
+                }
+                Err(()) => false,
+            };
+
+            if !survived || matches!(self.ui.keep_playing(), KeepPlaying::Stop) {
+                break;
+            }
+        }
+
+        self.ui.notify_survival_over(run.rounds_survived);
+        run.rounds_survived
+    }
+
+    /// Gives the human random endgame positions and grades their moves against the best ones.
+    /// Returns the number of correctly solved positions.
+    fn play_practice(&mut self, player_name: &str, rng: &mut impl Rng) -> u32 {
+        let mut solved = 0;
+
+        loop {
+            let empty_cells = rng.gen_range(PRACTICE_EMPTY_CELLS);
+            let board = analysis::random_endgame(rng, empty_cells);
+
+            self.ui.clear();
+            self.ui.update_board(&board);
+
+            let board_move = loop {
+                match self.ui.get_move(player_name) {
+                    PlayerAction::Move(board_move) if board.is_valid_move(&board_move) => {
+                        break board_move
+                    }
+                    PlayerAction::Move(_) => self.ui.notify_invalid_move(InvalidMove::CellTaken),
+                    PlayerAction::Hint => {
+                        let (best_moves, _score) = minimax::evaluate(&board);
+                        self.ui.notify_hint(&best_moves[0]);
+                    }
+                    PlayerAction::OpenMenu => {
+                        if self.ui.open_menu() == Some(PlayerAction::Quit) {
+                            return solved;
+                        }
+                    }
+                    PlayerAction::Quit => return solved,
+                    // Practice positions are single moves, there's nothing to take back or replay
+                    PlayerAction::Undo | PlayerAction::Restart | PlayerAction::Forfeit => (),
+                }
+            };
+
+            let (is_best, best_moves) = analysis::grade_move(&board, &board_move);
+            if is_best {
+                solved += 1;
+            } else {
+                let mut annotations: Vec<_> = best_moves
+                    .iter()
+                    .map(|best_move| {
+                        CellAnnotation::new(best_move.index(), "*", AnnotationStyle::Good)
+                    })
+                    .collect();
+                annotations.push(CellAnnotation::new(
+                    board_move.index(),
+                    "?",
+                    AnnotationStyle::Bad,
+                ));
+
+                self.ui.clear();
+                self.ui.show_board_with_annotations(&board, &annotations);
+            }
+            self.ui.notify_practice_result(is_best, &best_moves);
+
+            if matches!(self.ui.keep_playing(), KeepPlaying::Stop) {
+                return solved;
+            }
+        }
+    }
+
+    /// Plays games between the players until they stop or the match is decided. A match left
+    /// undecided is saved, so that it can be continued from the main menu.
+    fn play_match(&mut self, mut player1: Player, mut player2: Player) {
+        let mut decided = false;
+
+        loop {
+            let scores_before = self.scores;
+            // Game is abandoned only when the player quits to the main menu
+            let Ok(result) = self.play_game(&player1, &player2) else {
+                break;
+            };
+
+            let announcement =
+                Announcement::for_result(&result, scores_before, self.scores, self.match_target);
+            if let Some(announcement) = announcement {
+                self.ui.announce(&announcement);
+                if matches!(announcement, Announcement::Clinched { .. }) {
+                    decided = true;
+                    break;
+                }
+            }
+
+            match self.ui.keep_playing() {
+                KeepPlaying::Continue => (),
+                KeepPlaying::Stop => break,
+                KeepPlaying::Reset => self.scores = (0, 0),
+                KeepPlaying::Swap => {
+                    // Scores follow the players, not the sides
+                    std::mem::swap(&mut player1, &mut player2);
+                    self.scores = (self.scores.1, self.scores.0);
+                }
+                KeepPlaying::ChangeOpponent(opponent) => {
+                    // The new opponent takes over the replaced player's side and score. That's
+                    // the CPU one, or player 2 if it doesn't tell the players apart.
+                    if player1.is_cpu() && player2.is_human() {
+                        player1 = self.create_opponent(opponent, "Player 1");
+                    } else {
+                        player2 = self.create_opponent(opponent, "Player 2");
+                    }
+                }
+            }
+        }
+
+        let (player1_score, player2_score) = self.scores;
+        self.ui.notify_match_summary(
+            player1.get_name(),
+            player1_score,
+            player2.get_name(),
+            player2_score,
+        );
+
+        self.saved_match = (!decided).then(|| SavedMatch {
+            mode: self.mode.clone(),
+            player1,
+            player2,
+            scores: self.scores,
+        });
+    }
+
+    /// Returns the position the next game should start from, if it's not the regular empty board.
+    fn next_starting_position(&mut self) -> Option<Board> {
+        let mut board = self.starting_position.take();
+
+        if self.toroidal {
+            board = Some(board.unwrap_or_default().toroidal(true));
+        }
+        if let Some(win_length) = self.win_length {
+            board = Some(board.unwrap_or_default().win_length(win_length));
+        }
+        board
+    }
+
+    /// Plays a single game configured by the current mode, then records its result.
+    fn play_game(&mut self, player1: &Player, player2: &Player) -> Result<GameResult, ()> {
+        let mut game = self.build_game(player1, player2);
+
+        let result = game.start()?;
+        self.update_scores(player1, player2, &result, game.move_count());
+        self.record_result(player1, player2, &result);
+        self.log_result(game.board(), &result);
+
+        Ok(result)
+    }
+
+    /// Sets up a game between the players with the session's options and the current mode's
+    /// config. Every mode starts its games through here, so they all honour the same options.
+    fn build_game<'g>(&mut self, player1: &'g Player, player2: &'g Player) -> Game<'g, T>
+    where
+        'a: 'g,
+    {
+        let mut game = Game::new(player1, player2, self.ui)
+            .seed(self.rng.gen())
+            .kibitz(self.kibitz)
+            .review(self.review);
+        if let Some(bank) = self.time_bank {
+            game = game.time_bank(bank);
+        }
+        if let Some(board) = self.next_starting_position() {
+            game = game.starting_position(board);
+        }
+        if let Some(path) = &self.state_dump {
+            game = game.state_dump(path);
+        }
+        if let Some(hook) = &self.on_move {
+            let hook = Rc::clone(hook);
+            game = game.on_move(move |board, board_move, move_number| {
+                (hook.borrow_mut())(board, board_move, move_number)
+            });
+        }
+        if let Some(config) = self.mode.as_ref().and_then(GameMode::config) {
+            game = game.move_delay(config.move_delay);
+            if let Some(seed) = config.seed {
+                game = game.seed(seed);
+            }
+        }
+
+        game
+    }
+
+    fn update_scores(
+        &mut self,
+        player1: &Player,
+        player2: &Player,
+        result: &GameResult,
+        move_count: usize,
+    ) {
+        let win_points = self.scoring.points_for_win(move_count);
+        match result {
+            GameResult::PlayerWon(0, _winner_name, _winning_line) => self.scores.0 += win_points,
+            GameResult::PlayerWon(1, _winner_name, _winning_line) => self.scores.1 += win_points,
+            GameResult::PlayerWon(..) => (),
+            GameResult::Draw(_) => {
+                let draw_points = self.scoring.points_for_draw(move_count);
+                self.scores.0 += draw_points;
+                self.scores.1 += draw_points;
+            }
+        }
+
+        let (player1_score, player2_score) = self.scores;
+        let (player1_name, player2_name) = (player1.get_name(), player2.get_name());
+        self.ui
+            .update_scores(player1_name, player1_score, player2_name, player2_score);
+    }
+
+    /// Adds the result to the players' records, shown in the stats.
+    fn record_result(&mut self, player1: &Player, player2: &Player, result: &GameResult) {
+        let (player1_name, player2_name) = Self::record_names(player1, player2);
+        let (player1_name, player2_name) = (player1_name.as_str(), player2_name.as_str());
+
+        match result {
+            GameResult::PlayerWon(0, ..) => self.scoreboard.record_win(player1_name, player2_name),
+            GameResult::PlayerWon(1, ..) => self.scoreboard.record_win(player2_name, player1_name),
+            GameResult::PlayerWon(..) => (),
+            GameResult::Draw(_) => self.scoreboard.record_draw(player1_name, player2_name),
+        }
+    }
+
+    /// Returns the names the players' records are kept under. Players sharing a name get their
+    /// seat numbers added, so that both sides of a game don't end up in a single record.
+    fn record_names(player1: &Player, player2: &Player) -> (String, String) {
+        let (player1_name, player2_name) = (player1.get_name(), player2.get_name());
+
+        if player1_name == player2_name {
+            (format!("{player1_name} 1"), format!("{player2_name} 2"))
+        } else {
+            (player1_name.to_string(), player2_name.to_string())
+        }
+    }
+
+    fn log_result(&self, board: &Board, result: &GameResult) {
+        let Some(path) = &self.results_log else {
+            return;
+        };
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        let line = Self::format_log_line(timestamp, board, result);
+
+        // Logging is a side feature, so a failure shouldn't stop the game
+        if let Err(error) = Self::append_line(path, &line) {
+            eprintln!("Couldn't log the result to {}: {error}", path.display());
+        }
+    }
+
+    fn append_line(path: &Path, line: &str) -> io::Result<()> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{line}")
+    }
+
+    fn format_log_line(timestamp: u64, board: &Board, result: &GameResult) -> String {
+        format!("{timestamp} {board} {}", result.description(board))
+    }
+
+    fn create_players(&self) -> Option<(Player, Player)> {
+        let config = self.mode.as_ref()?.config()?;
+
+        Some((
+            self.create_player(config.player1, "Player 1", config.auto_names),
+            self.create_player(config.player2, "Player 2", config.auto_names),
+        ))
+    }
+
+    /// Creates the player picked as the new opponent in the middle of a match.
+    fn create_opponent(&self, opponent: Opponent, name_placeholder: &str) -> Player {
+        match opponent {
+            Opponent::Human => {
+                let auto_name = self
+                    .mode
+                    .as_ref()
+                    .and_then(GameMode::config)
+                    .is_some_and(|config| config.auto_names);
+                self.create_player(PlayerKind::Human, name_placeholder, auto_name)
+            }
+            Opponent::Cpu { mistake_chance } => Player::CPU(self.cpu_difficulty(mistake_chance)),
+        }
+    }
+
+    /// Creates a player of the given kind. Humans are asked for their name, unless `auto_name` is
+    /// set, in which case the placeholder becomes their name.
+    fn create_player(&self, kind: PlayerKind, name_placeholder: &str, auto_name: bool) -> Player {
+        match kind {
+            PlayerKind::Human if auto_name => Player::Human(name_placeholder.to_string()),
+            PlayerKind::Human => {
+                self.ui.clear();
+                Player::Human(self.ui.get_player_name(name_placeholder))
+            }
+            PlayerKind::Cpu => Player::CPU(self.cpu_difficulty(0)),
+        }
+    }
+
+    /// Returns the difficulty of a CPU making mistakes with the given chance, which plays in the
+    /// style set for this game.
+    fn cpu_difficulty(&self, mistake_chance: u8) -> Difficulty {
+        Difficulty::with_mistake_chance(mistake_chance)
+            .preference(self.cpu_preference.clone())
+            .entertainer(self.entertaining_cpu)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{BoardMove, Cell};
+    use crate::game::DrawReason;
+    use crate::scoreboard::Record;
+    use crate::ui::tests::MockUi;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn update_scores() {
+        let mock_ui = MockUi::builder().build();
+        let human = Player::Human(String::from("Steve"));
+        let cpu = Player::CPU(Difficulty::PERFECT);
+
+        let mut ttt = TicTacToe::new(&mock_ui);
+
+        assert_eq!(ttt.scores, (0, 0), "Initial score should be 0 to 0");
+
+        ttt.update_scores(
+            &human,
+            &cpu,
+            &GameResult::PlayerWon(0, String::from("Steve"), vec![0]),
+            5,
+        );
+
+        assert_eq!(ttt.scores, (1, 0), "Player1's score should be incremented");
+
+        ttt.update_scores(
+            &human,
+            &cpu,
+            &GameResult::PlayerWon(1, String::from("Steve"), vec![0]),
+            9,
+        );
+
+        assert_eq!(ttt.scores, (1, 1), "Player2's score should be incremented");
+
+        ttt.update_scores(&human, &cpu, &GameResult::Draw(DrawReason::BoardFull), 9);
+
+        assert_eq!(ttt.scores, (1, 1), "Draw shouldn't change the score");
+
+        assert_eq!(
+            mock_ui.update_scores_count(),
+            3,
+            "UI should be notified for every score update (even if it's a draw)"
+        );
+    }
+
+    #[test]
+    fn update_scores_with_scoring_policy() {
+        let mock_ui = MockUi::builder().build();
+        let human = Player::Human(String::from("Steve"));
+        let cpu = Player::CPU(Difficulty::PERFECT);
+
+        let mut ttt =
+            TicTacToe::new(&mock_ui).scoring(ScoringPolicy::new().faster_wins(true).draw_points(1));
+
+        ttt.update_scores(
+            &human,
+            &cpu,
+            &GameResult::PlayerWon(0, String::from("Steve"), vec![0]),
+            5,
+        );
+        ttt.update_scores(
+            &human,
+            &cpu,
+            &GameResult::PlayerWon(1, String::from("CPU"), vec![0]),
+            9,
+        );
+
+        assert!(
+            ttt.scores.0 > ttt.scores.1,
+            "5-move win should score higher than a 9-move win"
+        );
+
+        let scores_before_draw = ttt.scores;
+        ttt.update_scores(&human, &cpu, &GameResult::Draw(DrawReason::BoardFull), 9);
+
+        assert_eq!(
+            ttt.scores,
+            (scores_before_draw.0 + 1, scores_before_draw.1 + 1),
+            "Both players should get points for a draw"
+        );
+    }
+
+    #[test]
+    fn update_scores_with_draw_penalty() {
+        let mock_ui = MockUi::builder().build();
+        let human = Player::Human(String::from("Steve"));
+        let cpu = Player::CPU(Difficulty::PERFECT);
+
+        let mut ttt = TicTacToe::new(&mock_ui)
+            .scoring(ScoringPolicy::new().faster_wins(true).draw_is_loss(true));
+
+        ttt.update_scores(
+            &human,
+            &cpu,
+            &GameResult::PlayerWon(0, String::from("Steve"), vec![0]),
+            5,
+        );
+        ttt.update_scores(&human, &cpu, &GameResult::Draw(DrawReason::BoardFull), 9);
+
+        assert_eq!(ttt.scores, (4, -1), "Full-board draw should cost a point");
+
+        ttt.update_scores(&human, &cpu, &GameResult::Draw(DrawReason::Forced), 7);
+
+        assert_eq!(
+            ttt.scores,
+            (1, -4),
+            "Draw after 7 moves should cost as much as a 7-move win earns"
+        );
+    }
+
+    #[test]
+    fn swap_sides_between_games() {
+        let mock_ui = MockUi::builder()
+            .expected_moves(vec![
+                BoardMove::try_new(1).unwrap(),
+                BoardMove::try_new(4).unwrap(),
+                BoardMove::try_new(2).unwrap(),
+                BoardMove::try_new(5).unwrap(),
+                BoardMove::try_new(3).unwrap(), // Steve wins as 'O'
+                BoardMove::try_new(1).unwrap(),
+                BoardMove::try_new(4).unwrap(),
+                BoardMove::try_new(2).unwrap(),
+                BoardMove::try_new(5).unwrap(),
+                BoardMove::try_new(3).unwrap(), // Elon wins as 'O' after the swap
+            ])
+            .expected_keep_playing(vec![KeepPlaying::Swap, KeepPlaying::Stop])
+            .build();
+        let steve = Player::Human(String::from("Steve"));
+        let elon = Player::Human(String::from("Elon"));
+
+        let mut ttt = TicTacToe::new(&mock_ui);
+        ttt.play_match(steve, elon);
+
+        assert_eq!(
+            mock_ui.last_scores(),
+            Some((String::from("Elon"), 1, String::from("Steve"), 1)),
+            "Elon should play first after the swap and scores should follow the players"
+        );
+    }
+
+    #[test]
+    fn change_opponent_between_games() {
+        let mock_ui = MockUi::builder()
+            .expected_moves(vec![
+                BoardMove::try_new(4).unwrap(),
+                BoardMove::try_new(1).unwrap(),
+                BoardMove::try_new(5).unwrap(),
+                BoardMove::try_new(2).unwrap(),
+                BoardMove::try_new(9).unwrap(),
+                BoardMove::try_new(3).unwrap(), // Elon wins
+                BoardMove::try_new(1).unwrap(),
+                BoardMove::try_new(4).unwrap(),
+                BoardMove::try_new(2).unwrap(),
+                BoardMove::try_new(5).unwrap(),
+                BoardMove::try_new(3).unwrap(), // Steve wins against Jeff
+            ])
+            .expected_names(vec![String::from("Jeff")])
+            .expected_keep_playing(vec![
+                KeepPlaying::ChangeOpponent(Opponent::Human),
+                KeepPlaying::Stop,
+            ])
+            .build();
+        let steve = Player::Human(String::from("Steve"));
+        let elon = Player::Human(String::from("Elon"));
+
+        let mut ttt = TicTacToe::new(&mock_ui);
+        ttt.play_match(steve, elon);
+
+        assert_eq!(
+            mock_ui.last_scores(),
+            Some((String::from("Steve"), 1, String::from("Jeff"), 1)),
+            "Jeff should take over Elon's side along with his score"
+        );
+    }
+
+    #[test]
+    fn change_cpu_opponent() {
+        let mock_ui = MockUi::builder()
+            .expected_actions(
+                [1, 4, 2, 5, 3, 1] // Steve wins against Elon, then opens against the CPU
+                    .map(|cell| PlayerAction::Move(BoardMove::try_new(cell).unwrap()))
+                    .into_iter()
+                    .chain([PlayerAction::Quit])
+                    .collect(),
+            )
+            .expected_keep_playing(vec![
+                KeepPlaying::ChangeOpponent(Opponent::Cpu {
+                    mistake_chance: 0,
+                }),
+                KeepPlaying::Stop,
+            ])
+            .build();
+        let steve = Player::Human(String::from("Steve"));
+        let elon = Player::Human(String::from("Elon"));
+
+        let mut ttt = TicTacToe::new(&mock_ui);
+        ttt.play_match(steve, elon);
+
+        assert_eq!(
+            mock_ui.played_moves().last().map(|(name, _)| name.as_str()),
+            Some("CPU"),
+            "CPU should answer Steve's opening move"
+        );
+        assert_eq!(
+            mock_ui.match_summary(),
+            Some((String::from("Steve"), 1, String::from("CPU"), 0)),
+            "Abandoned game shouldn't change the scores kept from the first one"
+        );
+    }
+
+    #[test]
+    fn announcements() {
+        let steve_wins = GameResult::PlayerWon(0, String::from("Steve"), vec![3]);
+        let elon_wins = GameResult::PlayerWon(1, String::from("Elon"), vec![3]);
+        let draw = GameResult::Draw(DrawReason::BoardFull);
+
+        assert_eq!(
+            Announcement::for_result(&steve_wins, (0, 0), (1, 0), Some(3)),
+            Some(Announcement::TakesLead(String::from("Steve")))
+        );
+        assert_eq!(
+            Announcement::for_result(&steve_wins, (1, 0), (2, 0), Some(3)),
+            Some(Announcement::Won(String::from("Steve"))),
+            "Steve was already leading"
+        );
+        assert_eq!(
+            Announcement::for_result(&elon_wins, (2, 0), (2, 1), Some(3)),
+            Some(Announcement::Won(String::from("Elon"))),
+            "Elon is still behind"
+        );
+        assert_eq!(
+            Announcement::for_result(&elon_wins, (2, 2), (2, 3), Some(3)),
+            Some(Announcement::Clinched {
+                winner: String::from("Elon"),
+                winner_score: 3,
+                loser_score: 2,
+            }),
+            "Reaching the target decides the match, which beats taking the lead"
+        );
+        assert_eq!(
+            Announcement::for_result(&steve_wins, (2, 2), (3, 2), None),
+            Some(Announcement::TakesLead(String::from("Steve"))),
+            "Matches without a target are never decided"
+        );
+        assert_eq!(
+            Announcement::for_result(&steve_wins, (3, 1), (4, 1), Some(3)),
+            Some(Announcement::Won(String::from("Steve"))),
+            "Match can only be clinched once"
+        );
+        assert_eq!(
+            Announcement::for_result(&draw, (1, 1), (2, 2), Some(2)),
+            None,
+            "Draws aren't announced, even when they reach the target"
+        );
+        assert_eq!(
+            Announcement::Clinched {
+                winner: String::from("Steve"),
+                winner_score: 3,
+                loser_score: 1
+            }
+            .to_string(),
+            "Steve wins the match 3-1!"
+        );
+    }
+
+    #[test]
+    fn match_ends_when_clinched() {
+        let mock_ui = MockUi::builder()
+            .expected_moves(vec![
+                BoardMove::try_new(1).unwrap(),
+                BoardMove::try_new(4).unwrap(),
+                BoardMove::try_new(2).unwrap(),
+                BoardMove::try_new(5).unwrap(),
+                BoardMove::try_new(3).unwrap(), // Steve wins
+                BoardMove::try_new(1).unwrap(),
+                BoardMove::try_new(4).unwrap(),
+                BoardMove::try_new(2).unwrap(),
+                BoardMove::try_new(5).unwrap(),
+                BoardMove::try_new(3).unwrap(), // Steve wins again
+            ])
+            .expected_keep_playing(vec![KeepPlaying::Continue]) // Asked only once
+            .build();
+        let steve = Player::Human(String::from("Steve"));
+        let elon = Player::Human(String::from("Elon"));
+
+        let mut ttt = TicTacToe::new(&mock_ui).first_to(2);
+        ttt.play_match(steve, elon);
+
+        assert_eq!(
+            mock_ui.announcements(),
+            vec![
+                Announcement::TakesLead(String::from("Steve")),
+                Announcement::Clinched {
+                    winner: String::from("Steve"),
+                    winner_score: 2,
+                    loser_score: 0
+                }
+            ]
+        );
+        assert_eq!(
+            mock_ui.match_summary(),
+            Some((String::from("Steve"), 2, String::from("Elon"), 0))
+        );
+        assert!(
+            ttt.saved_match.is_none(),
+            "Decided match can't be continued"
+        );
+    }
+
+    #[test]
+    fn undecided_match_is_saved() {
+        let mock_ui = MockUi::builder()
+            .expected_moves(vec![
+                BoardMove::try_new(1).unwrap(),
+                BoardMove::try_new(4).unwrap(),
+                BoardMove::try_new(2).unwrap(),
+                BoardMove::try_new(5).unwrap(),
+                BoardMove::try_new(3).unwrap(), // Steve wins
+            ])
+            .expected_keep_playing(vec![KeepPlaying::Stop])
+            .build();
+        let steve = Player::Human(String::from("Steve"));
+        let elon = Player::Human(String::from("Elon"));
+
+        let mut ttt = TicTacToe::new(&mock_ui).first_to(2);
+        ttt.mode = Some(GameMode::PlayerVsPlayer);
+        ttt.play_match(steve, elon);
+
+        let saved = ttt.saved_match.as_ref().expect("Match should be saved");
+        assert_eq!(saved.mode, Some(GameMode::PlayerVsPlayer));
+        assert_eq!(saved.player1, Player::Human(String::from("Steve")));
+        assert_eq!(saved.scores, (1, 0));
+        assert_eq!(
+            ttt.scoreboard.record("Elon"),
+            Record {
+                wins: 0,
+                draws: 0,
+                losses: 1
+            },
+            "Result should be added to the stats"
+        );
+    }
+
+    #[test]
+    fn reset_scores_between_games() {
+        let mock_ui = MockUi::builder()
+            .expected_moves(vec![
+                BoardMove::try_new(1).unwrap(),
+                BoardMove::try_new(4).unwrap(),
+                BoardMove::try_new(2).unwrap(),
+                BoardMove::try_new(5).unwrap(),
+                BoardMove::try_new(3).unwrap(), // Steve wins
+                BoardMove::try_new(4).unwrap(),
+                BoardMove::try_new(1).unwrap(),
+                BoardMove::try_new(5).unwrap(),
+                BoardMove::try_new(2).unwrap(),
+                BoardMove::try_new(9).unwrap(),
+                BoardMove::try_new(3).unwrap(), // Elon wins
+            ])
+            .expected_keep_playing(vec![KeepPlaying::Reset, KeepPlaying::Stop])
+            .build();
+        let steve = Player::Human(String::from("Steve"));
+        let elon = Player::Human(String::from("Elon"));
+
+        let mut ttt = TicTacToe::new(&mock_ui);
+        ttt.play_match(steve, elon);
+
+        assert_eq!(
+            mock_ui.last_scores(),
+            Some((String::from("Steve"), 0, String::from("Elon"), 1)),
+            "Scores should be counted from zero after reset"
+        );
+    }
+
+    #[test]
+    fn stats_after_games() {
+        let mock_ui = MockUi::builder()
+            .expected_names(vec![
+                String::from("Steve"),
+                String::from("Elon"),
+                String::from("Steve"),
+                String::from("Elon"),
+            ])
+            .expected_moves(
+                [1, 4, 2, 5, 3] // Steve wins on the top row
+                    .into_iter()
+                    .chain([9, 5, 7, 8, 2, 1, 6, 3]) // Forced draw
+                    .map(|cell| BoardMove::try_new(cell).unwrap())
+                    .collect(),
+            )
+            .build();
+        let mut ttt = TicTacToe::new(&mock_ui);
+
+        assert!(ttt.stats().records().is_empty(), "No games played yet");
 
-impl<'a, T: Ui> TicTacToe<'a, T> {
-    /// Creates new instance of Tic-Tac-Toe game. Accepts one argument - UI backend.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use tic_tac_terminal::{TerminalUi, TicTacToe};
-    ///
-    /// let ui = TerminalUi::new();
-    /// let game = TicTacToe::new(&ui);
-    /// ```
-    pub fn new(ui: &'a T) -> Self {
-        Self {
-            ui,
-            mode: None,
-            scores: (0, 0),
-        }
+        ttt.run_once(GameMode::PlayerVsPlayer).unwrap();
+        ttt.run_once(GameMode::PlayerVsPlayer).unwrap();
+
+        assert_eq!(
+            ttt.stats().record("Steve"),
+            Record {
+                wins: 1,
+                draws: 1,
+                losses: 0
+            }
+        );
+        assert_eq!(
+            ttt.stats().record("Elon"),
+            Record {
+                wins: 0,
+                draws: 1,
+                losses: 1
+            }
+        );
     }
 
-    /// Starts the Tic-Tac-Toe application.
-    ///
-    /// # Examples
-    ///
-    /// ```no_run
-    /// use tic_tac_terminal::{TerminalUi, TicTacToe};
-    ///
-    /// let ui = TerminalUi::new();
-    /// let mut game = TicTacToe::new(&ui);
-    /// game.start();
-    /// ```
-    ///
-    /// # Panics
-    ///
-    /// Panics at every time the UI panics (pretty much only on stdin and stdout errors).
-    pub fn start(&mut self) {
-        loop {
-            self.mode = Some(self.ui.select_mode());
-            self.scores = (0, 0);
-
-            if let Some((player1, player2)) = self.create_players() {
-                loop {
-                    let result = Game::new(&player1, &player2, self.ui).start();
-                    if let Ok(result) = result {
-                        self.update_scores(&player1, &player2, &result);
-                    }
+    #[test]
+    fn same_names_keep_separate_records() {
+        let mock_ui = MockUi::builder().build();
+        let mut ttt = TicTacToe::new(&mock_ui);
 
-                    if !self.ui.keep_playing() {
-                        break;
-                    }
-                }
-            } else {
-                break;
+        ttt.run_once(GameMode::CpuVsCpu).unwrap();
+        let draw = Record {
+            wins: 0,
+            draws: 1,
+            losses: 0,
+        };
+        assert_eq!(ttt.stats().record("CPU 1"), draw, "Draw counts once");
+        assert_eq!(ttt.stats().record("CPU 2"), draw);
+        assert_eq!(ttt.stats().records().len(), 2);
+
+        let cpu = Player::CPU(Difficulty::PERFECT);
+        ttt.record_result(
+            &cpu,
+            &cpu,
+            &GameResult::PlayerWon(0, String::from("CPU"), vec![0]),
+        );
+        assert_eq!(ttt.stats().record("CPU 1").wins, 1);
+        assert_eq!(
+            ttt.stats().record("CPU 2").losses,
+            1,
+            "Loss belongs to the other seat"
+        );
+    }
+
+    #[test]
+    fn seeded_sessions_are_reproducible() {
+        let play_session = |seed| {
+            let mock_ui = MockUi::builder().build();
+            let mut ttt = TicTacToe::new(&mock_ui).seed(seed);
+            for _ in 0..3 {
+                ttt.run_once(GameMode::CpuVsCpu).unwrap();
             }
-        }
+            mock_ui.board_updates()
+        };
+
+        assert_eq!(
+            play_session(956),
+            play_session(956),
+            "Sessions with the same seed should play the same games"
+        );
+        assert_ne!(
+            play_session(956),
+            play_session(957),
+            "CPU should break ties differently with another seed"
+        );
     }
 
-    fn update_scores(&mut self, player1: &Player, player2: &Player, result: &GameResult) {
-        match result {
-            GameResult::PlayerWon(0, _winner_name, _winning_line) => self.scores.0 += 1,
-            GameResult::PlayerWon(1, _winner_name, _winning_line) => self.scores.1 += 1,
-            _ => (),
+    #[test]
+    fn run_once_plays_single_game() {
+        let mock_ui = MockUi::builder().build();
+        let mut ttt = TicTacToe::new(&mock_ui);
+
+        assert!(
+            matches!(ttt.run_once(GameMode::CpuVsCpu), Ok(GameResult::Draw(_))),
+            "CPU should always draw CPU"
+        );
+        assert_eq!(
+            mock_ui.update_scores_count(),
+            1,
+            "Exactly one game should be played"
+        );
+
+        assert_eq!(
+            ttt.run_once(GameMode::Survival {
+                draws_survive: false
+            }),
+            Err(()),
+            "Survival isn't a single game"
+        );
+    }
+
+    /// Plays random legal moves on a plain board until the game is decided. Returns the moves and
+    /// the final board.
+    fn random_game(rng: &mut impl Rng) -> (Vec<BoardMove>, Board) {
+        let mut board = Board::new();
+        let mut moves = vec![];
+
+        while board.get_winning_line().is_none() && !board.is_full() && !board.is_drawn() {
+            let possible_moves = board.get_possible_moves();
+            let board_move = possible_moves[rng.gen_range(0..possible_moves.len())];
+            board[board_move.index()] = board.current_player_symbol();
+            moves.push(board_move);
         }
 
-        let (player1_score, player2_score) = self.scores;
-        let (player1_name, player2_name) = (player1.get_name(), player2.get_name());
-        self.ui
-            .update_scores(player1_name, player1_score, player2_name, player2_score);
+        (moves, board)
     }
 
-    fn create_players(&self) -> Option<(Player, Player)> {
-        match self.mode {
-            Some(GameMode::PlayerVsPlayer) => {
-                let player1_name = self.ui.get_player_name("Player1");
-                let player2_name = self.ui.get_player_name("Player2");
-                Some((Player::Human(player1_name), Player::Human(player2_name)))
-            }
-            Some(GameMode::PlayerVsCpu) => {
-                let player1_name = self.ui.get_player_name("Player1");
-                Some((Player::Human(player1_name), Player::CPU))
-            }
-            Some(GameMode::CpuVsPlayer) => {
-                let player2_name = self.ui.get_player_name("Player2");
-                Some((Player::CPU, Player::Human(player2_name)))
+    #[test]
+    fn random_legal_games_end_consistently() {
+        let mut rng = StdRng::seed_from_u64(920);
+
+        for _ in 0..2000 {
+            let (moves, final_board) = random_game(&mut rng);
+            let mock_ui = MockUi::builder()
+                .expected_names(vec![String::from("Steve"), String::from("Elon")])
+                .expected_moves(moves.clone())
+                .build();
+
+            let result = TicTacToe::new(&mock_ui)
+                .run_once(GameMode::PlayerVsPlayer)
+                .expect("Every random game should finish");
+
+            let board = *mock_ui.board_updates().last().unwrap();
+            assert_eq!(
+                board, final_board,
+                "Moves {moves:?} should lead to the same board"
+            );
+            assert!(board.is_legal_position(), "{board} isn't reachable");
+
+            match result {
+                GameResult::PlayerWon(winner, name, winning_lines) => {
+                    assert_eq!(winner, (moves.len() + 1) % 2, "Last mover should win");
+                    assert_eq!(name, ["Steve", "Elon"][winner]);
+                    assert_eq!(winning_lines, board.winning_lines());
+                }
+                GameResult::Draw(DrawReason::BoardFull) => {
+                    assert!(board.is_full() && board.get_winning_line().is_none());
+                }
+                GameResult::Draw(DrawReason::Forced) => {
+                    assert!(board.is_drawn() && !board.is_full());
+                }
             }
-            Some(GameMode::CpuVsCpu) => Some((Player::CPU, Player::CPU)),
-            Some(GameMode::Quit) => None,
-            None => None,
+            assert!(
+                mock_ui.invalid_moves().is_empty(),
+                "Legal moves shouldn't be rejected"
+            );
         }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::ui::tests::MockUi;
+    #[test]
+    fn toroidal_games() {
+        let mock_ui = MockUi::builder()
+            .expected_names(vec![String::from("Steve"), String::from("Elon")])
+            .expected_moves(
+                [3, 1, 4, 2, 8] // 'O' completes the wrapped diagonal 3, 4, 8
+                    .map(|cell| BoardMove::try_new(cell).unwrap())
+                    .to_vec(),
+            )
+            .build();
+        let mut ttt = TicTacToe::new(&mock_ui).toroidal(true);
+
+        assert!(
+            matches!(
+                ttt.run_once(GameMode::PlayerVsPlayer),
+                Ok(GameResult::PlayerWon(0, _, _))
+            ),
+            "Wrapped diagonal should win on a toroidal board"
+        );
+        assert!(mock_ui.board_updates().iter().all(Board::is_toroidal));
+    }
 
     #[test]
-    fn update_scores() {
-        let mock_ui = MockUi::builder().build();
-        let human = Player::Human(String::from("Steve"));
-        let cpu = Player::CPU;
+    fn shorter_win_length_games() {
+        let mock_ui = MockUi::builder()
+            .expected_names(vec![String::from("Steve"), String::from("Elon")])
+            .expected_moves(
+                [1, 5, 2] // 'O' wins with two in a row
+                    .map(|cell| BoardMove::try_new(cell).unwrap())
+                    .to_vec(),
+            )
+            .build();
+        let mut ttt = TicTacToe::new(&mock_ui).win_length(2);
+
+        assert!(
+            matches!(
+                ttt.run_once(GameMode::PlayerVsPlayer),
+                Ok(GameResult::PlayerWon(0, _, _))
+            ),
+            "Two marks in a row should win"
+        );
+        assert!(mock_ui
+            .board_updates()
+            .iter()
+            .all(|board| board.get_win_length() == 2));
+    }
+
+    #[test]
+    fn match_summary_on_quit() {
+        let mock_ui = MockUi::builder()
+            .expected_actions(vec![
+                PlayerAction::Move(BoardMove::try_new(1).unwrap()),
+                PlayerAction::Move(BoardMove::try_new(4).unwrap()),
+                PlayerAction::Move(BoardMove::try_new(2).unwrap()),
+                PlayerAction::Move(BoardMove::try_new(5).unwrap()),
+                PlayerAction::Move(BoardMove::try_new(3).unwrap()), // Steve wins
+                PlayerAction::Quit,
+            ])
+            .expected_keep_playing(vec![KeepPlaying::Continue])
+            .build();
+        let steve = Player::Human(String::from("Steve"));
+        let elon = Player::Human(String::from("Elon"));
+
+        TicTacToe::new(&mock_ui).play_match(steve, elon);
+
+        assert_eq!(
+            mock_ui.match_summary(),
+            Some((String::from("Steve"), 1, String::from("Elon"), 0)),
+            "Summary should show the score when the match is quit"
+        );
+
+        let mock_ui = MockUi::builder()
+            .expected_actions(vec![PlayerAction::Quit])
+            .build();
+        let steve = Player::Human(String::from("Steve"));
+        let elon = Player::Human(String::from("Elon"));
+
+        TicTacToe::new(&mock_ui).play_match(steve, elon);
+
+        assert_eq!(
+            mock_ui.match_summary(),
+            Some((String::from("Steve"), 0, String::from("Elon"), 0)),
+            "Quitting before any game was played should end 0-0"
+        );
+    }
+
+    #[test]
+    fn demo_is_reproducible() {
+        let mock_ui = MockUi::builder()
+            .expected_keep_playing(vec![KeepPlaying::Continue, KeepPlaying::Stop])
+            .build();
 
         let mut ttt = TicTacToe::new(&mock_ui);
+        ttt.mode = Some(GameMode::Demo);
+        ttt.play_match(
+            Player::CPU(Difficulty::PERFECT),
+            Player::CPU(Difficulty::PERFECT),
+        );
 
-        assert_eq!(ttt.scores, (0, 0), "Initial score should be 0 to 0");
+        // Every game starts with an empty board, the following updates reveal the moves
+        let boards = mock_ui.board_updates();
+        let games: Vec<Vec<_>> = boards
+            .split(Board::is_empty)
+            .filter(|game| !game.is_empty())
+            .map(|game| {
+                let mut previous = Board::new();
+                game.iter()
+                    .filter_map(|board| {
+                        let index = (0..9).find(|&index| board[index] != previous[index]);
+                        previous = *board;
+                        index.map(|index| index + 1)
+                    })
+                    .collect()
+            })
+            .collect();
 
-        ttt.update_scores(
-            &human,
-            &cpu,
-            &GameResult::PlayerWon(0, String::from("Steve"), 0),
+        assert_eq!(games.len(), 2, "Two games should be played");
+        let (first_game, second_game) = (&games[0], &games[1]);
+        assert_eq!(
+            first_game,
+            &[8, 7, 4, 2, 3, 5, 6, 9, 1],
+            "Demo seed should always produce the same game"
+        );
+        assert_eq!(
+            first_game, second_game,
+            "Every demo game should be the same"
         );
+    }
 
-        assert_eq!(ttt.scores, (1, 0), "Player1's score should be incremented");
+    #[test]
+    fn result_log_lines() {
+        let board = Board::from([
+            Cell::O,
+            Cell::O,
+            Cell::O,
+            Cell::X,
+            Cell::X,
+            Cell::Empty('6'),
+            Cell::Empty('7'),
+            Cell::Empty('8'),
+            Cell::Empty('9'),
+        ]);
 
-        ttt.update_scores(
-            &human,
-            &cpu,
-            &GameResult::PlayerWon(1, String::from("Steve"), 0),
+        assert_eq!(
+            TicTacToe::<MockUi>::format_log_line(
+                1700000000,
+                &board,
+                &GameResult::PlayerWon(0, String::from("Steve"), vec![3])
+            ),
+            "1700000000 OOOXX.... Steve won on the top row"
+        );
+        assert_eq!(
+            TicTacToe::<MockUi>::format_log_line(
+                1700000042,
+                &board,
+                &GameResult::Draw(DrawReason::BoardFull)
+            ),
+            "1700000042 OOOXX.... draw"
         );
+    }
 
-        assert_eq!(ttt.scores, (1, 1), "Player2's score should be incremented");
+    #[test]
+    fn survival_round_progression() {
+        let win = GameResult::PlayerWon(0, String::from("Steve"), vec![0]);
+        let loss = GameResult::PlayerWon(1, String::from("CPU"), vec![0]);
 
-        ttt.update_scores(&human, &cpu, &GameResult::Draw);
+        let mut run = SurvivalRun::new(true);
+        let mut difficulties = vec![run.current_difficulty()];
 
-        assert_eq!(ttt.scores, (1, 1), "Draw shouldn't change the score");
+        for result in [&win, &GameResult::Draw(DrawReason::BoardFull), &win] {
+            assert!(run.record(result), "Run should go on after a win or a draw");
+            difficulties.push(run.current_difficulty());
+        }
 
+        assert!(!run.record(&loss), "Run should end after a loss");
+        assert_eq!(run.rounds_survived, 3);
         assert_eq!(
-            mock_ui.update_scores_count(),
-            3,
-            "UI should be notified for every score update (even if it's a draw)"
+            difficulties,
+            [60, 45, 30, 15].map(Difficulty::with_mistake_chance),
+            "CPU should get stronger every round"
+        );
+
+        run.record(&win);
+        assert_eq!(
+            run.current_difficulty(),
+            Difficulty::PERFECT,
+            "CPU should eventually become perfect"
+        );
+
+        let mut run = SurvivalRun::new(false);
+
+        assert!(run.record(&win));
+        assert!(
+            !run.record(&GameResult::Draw(DrawReason::BoardFull)),
+            "Draw shouldn't count as survival unless enabled"
+        );
+        assert_eq!(run.rounds_survived, 1);
+    }
+
+    #[test]
+    fn survival_reports_rounds() {
+        // CPU's mistakes are random, so the human tries every cell in turn until one is free
+        let mock_ui = MockUi::builder()
+            .expected_moves(
+                (1..=9)
+                    .cycle()
+                    .take(45)
+                    .map(|cell| BoardMove::try_new(cell).unwrap())
+                    .collect(),
+            )
+            .expected_keep_playing(vec![KeepPlaying::Stop])
+            .build();
+        let human = Player::Human(String::from("Steve"));
+        let path = std::env::temp_dir().join(format!("ttt-survival-{}.log", std::process::id()));
+
+        let mut ttt = TicTacToe::new(&mock_ui).results_log(&path);
+        let rounds = ttt.play_survival(&human, true);
+        let log = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(
+            rounds <= 1,
+            "Human can survive at most one round before quitting"
+        );
+        assert_eq!(
+            mock_ui.survival_result(),
+            Some(rounds),
+            "UI should be told how many rounds were survived"
+        );
+        assert_eq!(log.lines().count(), 1, "Survival games should be logged");
+    }
+
+    #[test]
+    fn practice_grades_moves() {
+        // Same seed reproduces the positions the human will be given
+        let mut rng = StdRng::seed_from_u64(891);
+        let mut expected_rng = rng.clone();
+        let mut next_position = || {
+            let empty_cells = expected_rng.gen_range(PRACTICE_EMPTY_CELLS);
+            analysis::random_endgame(&mut expected_rng, empty_cells)
+        };
+
+        let first = next_position();
+        let best_move = analysis::grade_move(&first, &first.get_possible_moves()[0]).1[0];
+        let second = next_position();
+        let worst_move = second
+            .get_possible_moves()
+            .into_iter()
+            .find(|board_move| !analysis::grade_move(&second, board_move).0)
+            .expect("Second position should have a wrong answer");
+
+        let mock_ui = MockUi::builder()
+            .expected_moves(vec![best_move, worst_move])
+            .expected_keep_playing(vec![KeepPlaying::Continue, KeepPlaying::Stop])
+            .build();
+        let mut ttt = TicTacToe::new(&mock_ui);
+
+        let solved = ttt.play_practice("Steve", &mut rng);
+
+        assert_eq!(mock_ui.board_updates(), vec![first, second]);
+        assert_eq!(
+            mock_ui.practice_results(),
+            vec![true, false],
+            "Best move should be accepted and a worse one rejected"
+        );
+        assert_eq!(solved, 1, "Only the best move counts as solved");
+    }
+
+    #[test]
+    fn cpu_uses_preference() {
+        let mock_ui = MockUi::builder().build();
+        let ttt = TicTacToe::new(&mock_ui).cpu_preference(vec![4, 0]);
+
+        assert_eq!(
+            ttt.create_player(PlayerKind::Cpu, "CPU", false),
+            Player::CPU(Difficulty::PERFECT.preference(vec![4, 0])),
+            "Created CPU should break ties by the preference"
+        );
+    }
+
+    #[test]
+    fn entertaining_cpu() {
+        let mock_ui = MockUi::builder().build();
+        let ttt = TicTacToe::new(&mock_ui).entertaining_cpu(true);
+
+        assert_eq!(
+            ttt.create_player(PlayerKind::Cpu, "CPU", false),
+            Player::CPU(Difficulty::PERFECT.entertainer(true))
+        );
+        assert_eq!(
+            ttt.create_opponent(Opponent::Cpu { mistake_chance: 20 }, "Player 2"),
+            Player::CPU(Difficulty::with_mistake_chance(20).entertainer(true)),
+            "New opponent should play in the same style"
         );
     }
 
@@ -166,7 +1876,7 @@ mod tests {
 
         let players = ttt.create_players();
         assert!(
-            matches!(players, None),
+            players.is_none(),
             "create_players should return None for no mode selected"
         );
 
@@ -218,4 +1928,89 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn game_from_config() {
+        let mock_ui = MockUi::builder().build();
+        let config: Config =
+            "toroidal = true\nwin_length = 2\nkibitz = true\ndraw_points = 1\nresults_log = \"results.log\""
+                .parse()
+                .unwrap();
+
+        let ttt = TicTacToe::with_config(&mock_ui, &config);
+
+        assert!(ttt.toroidal);
+        assert_eq!(ttt.win_length, Some(2));
+        assert!(ttt.kibitz);
+        assert_eq!(ttt.results_log, Some(PathBuf::from("results.log")));
+        assert_eq!(ttt.scoring, ScoringPolicy::new().draw_points(1));
+        assert_eq!(
+            ttt.state_dump, None,
+            "Options missing from the config keep defaults"
+        );
+    }
+
+    #[test]
+    fn auto_names() {
+        let mock_ui = MockUi::builder().build(); // No names to give
+        let mut ttt = TicTacToe::new(&mock_ui);
+        ttt.mode = Some(GameMode::Custom(GameConfig {
+            auto_names: true,
+            ..GameMode::PlayerVsPlayer.config().unwrap()
+        }));
+
+        let (p1, p2) = ttt
+            .create_players()
+            .expect("Custom mode should create players");
+
+        assert_eq!(p1, Player::Human(String::from("Player 1")));
+        assert_eq!(p2, Player::Human(String::from("Player 2")));
+        assert_eq!(
+            mock_ui.clear_calls(),
+            0,
+            "Nobody should be asked for a name"
+        );
+    }
+
+    #[test]
+    fn custom_mode() {
+        let mock_ui = MockUi::builder()
+            .expected_names(vec![String::from("Steve")])
+            .build();
+        let config = GameConfig {
+            player1: PlayerKind::Cpu,
+            player2: PlayerKind::Human,
+            seed: Some(DEMO_SEED),
+            move_delay: Duration::ZERO,
+            auto_names: false,
+        };
+
+        let mut ttt = TicTacToe::new(&mock_ui);
+        ttt.mode = Some(GameMode::Custom(config.clone()));
+
+        assert_eq!(
+            ttt.mode.as_ref().and_then(GameMode::config),
+            Some(config),
+            "Custom mode should use the config it carries"
+        );
+
+        let (p1, p2) = ttt
+            .create_players()
+            .expect("Custom mode should create players");
+        assert_eq!(
+            p1,
+            Player::CPU(Difficulty::PERFECT),
+            "Player 1 should be the CPU"
+        );
+        assert_eq!(
+            p2,
+            Player::Human(String::from("Steve")),
+            "Player 2 should be named Steve"
+        );
+        assert_eq!(
+            GameMode::Quit.config(),
+            None,
+            "Quitting shouldn't produce any config"
+        );
+    }
 }
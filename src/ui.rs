@@ -1,21 +1,89 @@
 //! This module contains the Ui trait, which has to be implemented for every UI instance (such as
 //! terminal UI and graphic UI). Also provides a mock UI object which can be helpful in testing.
 
+mod line_editor;
 mod tui;
 
+use crate::analysis::{Mistake, MoveJudgement, MoveReason};
 use crate::board::{Board, BoardMove};
-use crate::game::GameResult;
-use crate::tictactoe::GameMode;
+use crate::game::{GameResult, InvalidMove, PlayerAction};
+use crate::scoreboard::ScoreBoard;
+use crate::tictactoe::{Announcement, GameMode, KeepPlaying, MainMenuChoice};
+use std::time::Duration;
 
 pub use tui::TerminalUi;
 
+/// Extra decoration of a single cell, drawn over its usual content, e.g. a score or a marker of
+/// the best move.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CellAnnotation {
+    pub index: usize,
+    pub text: String, // Shown in place of the cell's content, cut to 3 characters
+    pub style: AnnotationStyle,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AnnotationStyle {
+    Good,
+    Bad,
+    Note,
+}
+
+impl CellAnnotation {
+    pub fn new(index: usize, text: &str, style: AnnotationStyle) -> Self {
+        Self {
+            index,
+            text: text.to_owned(),
+            style,
+        }
+    }
+}
+
 pub trait Ui {
-    fn get_move(&self, player_name: &str, additional_message: Option<&str>) -> BoardMove;
+    fn clear(&self);
+    fn get_move(&self, player_name: &str) -> PlayerAction;
+    fn notify_invalid_move(&self, reason: InvalidMove);
+    fn notify_hint(&self, board_move: &BoardMove);
+    /// Shows the in-game menu. Returns the chosen action, or `None` to resume the game.
+    fn open_menu(&self) -> Option<PlayerAction>;
     fn update_board(&self, board: &Board);
+    /// Shows the board with extra decoration of some cells, e.g. for analysis. Doesn't replace
+    /// the board of the ongoing game.
+    fn show_board_with_annotations(&self, board: &Board, annotations: &[CellAnnotation]);
+    /// Shows which turn is being played, counting from 1, and how many turns the game can last.
+    fn notify_turn(&self, turn: usize, max_turns: usize);
+    /// Tells who plays the game: player 1 has 'O' and player 2 has 'X'. Called once the game
+    /// starts, before any board is shown.
+    fn notify_players(&self, player1_name: &str, player2_name: &str);
+    /// Shows how much time both players have left in a blitz game. Called every turn.
+    fn notify_clocks(
+        &self,
+        player1_name: &str,
+        player1_time: Duration,
+        player2_name: &str,
+        player2_time: Duration,
+    );
+    /// Tells that the player ran out of time. Called right before the result is announced.
+    fn notify_flag_fall(&self, player_name: &str);
+    fn notify_move(&self, player_name: &str, board_move: &BoardMove);
+    /// Explains the CPU's last move. Called right after `notify_move`, only for CPU moves that
+    /// can be explained.
+    fn notify_cpu_reasoning(&self, reason: MoveReason);
+    /// Judges the human player's last move in kibitz mode. Called right after `notify_move`.
+    fn notify_move_judgement(&self, judgement: MoveJudgement);
     fn notify_result(&self, result: &GameResult);
+    /// Lists the moves which weren't the best ones, once the game is over. An empty list means a
+    /// clean game. Called right after `notify_result`, if reviews are on.
+    fn notify_review(&self, mistakes: &[Mistake]);
     fn get_player_name(&self, name_placeholder: &str) -> String;
+    /// Shows the main menu. `can_continue` tells whether there's an unfinished match to offer.
+    fn main_menu(&self, can_continue: bool) -> MainMenuChoice;
+    /// Shows every player's results so far.
+    fn show_stats(&self, scoreboard: &ScoreBoard);
     fn select_mode(&self) -> GameMode;
-    fn keep_playing(&self) -> bool;
+    fn keep_playing(&self) -> KeepPlaying;
+    fn notify_survival_over(&self, rounds_survived: u32);
+    fn notify_practice_result(&self, is_best: bool, best_moves: &[BoardMove]);
     fn update_scores(
         &self,
         player1_name: &str,
@@ -23,40 +91,145 @@ pub trait Ui {
         player2_name: &str,
         player2_score: i32,
     );
+    /// Tells what the last game meant for the match. Called after the scores are updated.
+    fn announce(&self, announcement: &Announcement);
+    fn notify_match_summary(
+        &self,
+        player1_name: &str,
+        player1_score: i32,
+        player2_name: &str,
+        player2_score: i32,
+    );
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
-    use crate::board::BoardMove;
     use std::cell::RefCell;
 
     pub struct MockUiBuilder {
-        expected_moves: RefCell<Vec<BoardMove>>,
+        expected_actions: RefCell<Vec<PlayerAction>>,
         expected_names: RefCell<Vec<String>>,
+        expected_keep_playing: RefCell<Vec<KeepPlaying>>,
+        expected_menu_choices: RefCell<Vec<Option<PlayerAction>>>,
+        board_updates: RefCell<Vec<Board>>,
+        invalid_moves: RefCell<Vec<InvalidMove>>,
+        hints: RefCell<Vec<BoardMove>>,
+        played_moves: RefCell<Vec<(String, BoardMove)>>,
+        survival_result: RefCell<Option<u32>>,
+        practice_results: RefCell<Vec<bool>>,
 
+        clear_calls: RefCell<u32>,
         update_scores_count: RefCell<u32>,
         notify_result_calls: RefCell<u32>,
         get_move_calls: RefCell<u32>,
+        last_scores: RefCell<Option<(String, i32, String, i32)>>,
+        match_summary: RefCell<Option<(String, i32, String, i32)>>,
+        announcements: RefCell<Vec<Announcement>>,
+        clocks: RefCell<Vec<(Duration, Duration)>>,
+        flag_falls: RefCell<Vec<String>>,
+        turns: RefCell<Vec<(usize, usize)>>,
+        players: RefCell<Option<(String, String)>>,
+        cpu_reasons: RefCell<Vec<MoveReason>>,
+        judgements: RefCell<Vec<MoveJudgement>>,
+        reviews: RefCell<Vec<Vec<Mistake>>>,
     }
 
     pub struct MockUi {
-        expected_moves: RefCell<Vec<BoardMove>>,
+        expected_actions: RefCell<Vec<PlayerAction>>,
         expected_names: RefCell<Vec<String>>,
+        expected_keep_playing: RefCell<Vec<KeepPlaying>>,
+        expected_menu_choices: RefCell<Vec<Option<PlayerAction>>>,
+        board_updates: RefCell<Vec<Board>>,
+        invalid_moves: RefCell<Vec<InvalidMove>>,
+        hints: RefCell<Vec<BoardMove>>,
+        played_moves: RefCell<Vec<(String, BoardMove)>>,
+        survival_result: RefCell<Option<u32>>,
+        practice_results: RefCell<Vec<bool>>,
 
+        clear_calls: RefCell<u32>,
         update_scores_count: RefCell<u32>,
         notify_result_calls: RefCell<u32>,
         get_move_calls: RefCell<u32>,
+        last_scores: RefCell<Option<(String, i32, String, i32)>>,
+        match_summary: RefCell<Option<(String, i32, String, i32)>>,
+        announcements: RefCell<Vec<Announcement>>,
+        clocks: RefCell<Vec<(Duration, Duration)>>,
+        flag_falls: RefCell<Vec<String>>,
+        turns: RefCell<Vec<(usize, usize)>>,
+        players: RefCell<Option<(String, String)>>,
+        cpu_reasons: RefCell<Vec<MoveReason>>,
+        judgements: RefCell<Vec<MoveJudgement>>,
+        reviews: RefCell<Vec<Vec<Mistake>>>,
     }
 
     impl Ui for MockUi {
-        fn get_move(&self, _player_name: &str, _additional_message: Option<&str>) -> BoardMove {
+        fn clear(&self) {
+            *self.clear_calls.borrow_mut() += 1;
+        }
+
+        fn get_move(&self, _player_name: &str) -> PlayerAction {
             *self.get_move_calls.borrow_mut() += 1;
-            self.expected_moves.borrow_mut().remove(0) // Make sure there are enough fake moves
+            self.expected_actions.borrow_mut().remove(0) // Make sure there are enough fake moves
         }
 
-        fn update_board(&self, _board: &Board) {
-            // Silently ignore the call
+        fn notify_invalid_move(&self, reason: InvalidMove) {
+            self.invalid_moves.borrow_mut().push(reason);
+        }
+
+        fn notify_hint(&self, board_move: &BoardMove) {
+            self.hints.borrow_mut().push(*board_move);
+        }
+
+        fn open_menu(&self) -> Option<PlayerAction> {
+            self.expected_menu_choices.borrow_mut().remove(0) // Make sure there are enough choices
+        }
+
+        fn update_board(&self, board: &Board) {
+            self.board_updates.borrow_mut().push(*board);
+        }
+
+        fn show_board_with_annotations(&self, _board: &Board, _annotations: &[CellAnnotation]) {}
+
+        fn notify_turn(&self, turn: usize, max_turns: usize) {
+            self.turns.borrow_mut().push((turn, max_turns));
+        }
+
+        fn notify_players(&self, player1_name: &str, player2_name: &str) {
+            self.players
+                .replace(Some((player1_name.to_string(), player2_name.to_string())));
+        }
+
+        fn notify_clocks(
+            &self,
+            _player1_name: &str,
+            player1_time: Duration,
+            _player2_name: &str,
+            player2_time: Duration,
+        ) {
+            self.clocks.borrow_mut().push((player1_time, player2_time));
+        }
+
+        fn notify_flag_fall(&self, player_name: &str) {
+            self.flag_falls.borrow_mut().push(player_name.to_string());
+        }
+
+        fn notify_move(&self, player_name: &str, board_move: &BoardMove) {
+            self.played_moves
+                .borrow_mut()
+                .push((player_name.to_string(), *board_move));
+        }
+
+        fn notify_cpu_reasoning(&self, reason: MoveReason) {
+            self.cpu_reasons.borrow_mut().push(reason);
+        }
+
+        fn notify_move_judgement(&self, judgement: MoveJudgement) {
+            self.judgements.borrow_mut().push(judgement);
+        }
+
+        fn notify_review(&self, mistakes: &[Mistake]) {
+            self.reviews.borrow_mut().push(mistakes.to_vec());
         }
 
         fn notify_result(&self, _result: &GameResult) {
@@ -67,38 +240,102 @@ pub mod tests {
             self.expected_names.borrow_mut().remove(0) // Make sure there are enough fake names
         }
 
+        fn main_menu(&self, _can_continue: bool) -> MainMenuChoice {
+            panic!("Mock method main_menu not used")
+        }
+
+        fn show_stats(&self, _scoreboard: &ScoreBoard) {
+            panic!("Mock method show_stats not used")
+        }
+
         fn select_mode(&self) -> GameMode {
             panic!("Mock method select_mode not used")
         }
 
-        fn keep_playing(&self) -> bool {
-            panic!("Mock method keep_playing not used")
+        fn keep_playing(&self) -> KeepPlaying {
+            self.expected_keep_playing.borrow_mut().remove(0) // Make sure there are enough answers
+        }
+
+        fn notify_survival_over(&self, rounds_survived: u32) {
+            self.survival_result.replace(Some(rounds_survived));
+        }
+
+        fn notify_practice_result(&self, is_best: bool, _best_moves: &[BoardMove]) {
+            self.practice_results.borrow_mut().push(is_best);
         }
 
         fn update_scores(
             &self,
-            _player1_name: &str,
-            _player1_score: i32,
-            _player2_name: &str,
-            _player2_score: i32,
+            player1_name: &str,
+            player1_score: i32,
+            player2_name: &str,
+            player2_score: i32,
         ) {
             *self.update_scores_count.borrow_mut() += 1;
+            self.last_scores.replace(Some((
+                player1_name.to_string(),
+                player1_score,
+                player2_name.to_string(),
+                player2_score,
+            )));
+        }
+
+        fn announce(&self, announcement: &Announcement) {
+            self.announcements.borrow_mut().push(announcement.clone());
+        }
+
+        fn notify_match_summary(
+            &self,
+            player1_name: &str,
+            player1_score: i32,
+            player2_name: &str,
+            player2_score: i32,
+        ) {
+            self.match_summary.replace(Some((
+                player1_name.to_string(),
+                player1_score,
+                player2_name.to_string(),
+                player2_score,
+            )));
         }
     }
 
     impl MockUiBuilder {
         pub fn new() -> Self {
             Self {
-                expected_moves: RefCell::new(vec![]),
+                expected_actions: RefCell::new(vec![]),
                 expected_names: RefCell::new(vec![]),
+                expected_keep_playing: RefCell::new(vec![]),
+                expected_menu_choices: RefCell::new(vec![]),
+                board_updates: RefCell::new(vec![]),
+                invalid_moves: RefCell::new(vec![]),
+                hints: RefCell::new(vec![]),
+                played_moves: RefCell::new(vec![]),
+                survival_result: RefCell::new(None),
+                practice_results: RefCell::new(vec![]),
+                clear_calls: RefCell::new(0),
                 update_scores_count: RefCell::new(0),
                 notify_result_calls: RefCell::new(0),
                 get_move_calls: RefCell::new(0),
+                last_scores: RefCell::new(None),
+                match_summary: RefCell::new(None),
+                announcements: RefCell::new(vec![]),
+                clocks: RefCell::new(vec![]),
+                flag_falls: RefCell::new(vec![]),
+                turns: RefCell::new(vec![]),
+                players: RefCell::new(None),
+                cpu_reasons: RefCell::new(vec![]),
+                judgements: RefCell::new(vec![]),
+                reviews: RefCell::new(vec![]),
             }
         }
 
         pub fn expected_moves(self, expected_moves: Vec<BoardMove>) -> Self {
-            self.expected_moves.replace(expected_moves);
+            self.expected_actions(expected_moves.into_iter().map(PlayerAction::Move).collect())
+        }
+
+        pub fn expected_actions(self, expected_actions: Vec<PlayerAction>) -> Self {
+            self.expected_actions.replace(expected_actions);
             self
         }
 
@@ -107,13 +344,45 @@ pub mod tests {
             self
         }
 
+        pub fn expected_keep_playing(self, expected_keep_playing: Vec<KeepPlaying>) -> Self {
+            self.expected_keep_playing.replace(expected_keep_playing);
+            self
+        }
+
+        pub fn expected_menu_choices(
+            self,
+            expected_menu_choices: Vec<Option<PlayerAction>>,
+        ) -> Self {
+            self.expected_menu_choices.replace(expected_menu_choices);
+            self
+        }
+
         pub fn build(self) -> MockUi {
             MockUi {
-                expected_moves: self.expected_moves,
+                expected_actions: self.expected_actions,
                 expected_names: self.expected_names,
+                expected_keep_playing: self.expected_keep_playing,
+                expected_menu_choices: self.expected_menu_choices,
+                board_updates: self.board_updates,
+                invalid_moves: self.invalid_moves,
+                hints: self.hints,
+                played_moves: self.played_moves,
+                survival_result: self.survival_result,
+                practice_results: self.practice_results,
+                clear_calls: self.clear_calls,
                 update_scores_count: self.update_scores_count,
                 notify_result_calls: self.notify_result_calls,
                 get_move_calls: self.get_move_calls,
+                last_scores: self.last_scores,
+                match_summary: self.match_summary,
+                announcements: self.announcements,
+                clocks: self.clocks,
+                flag_falls: self.flag_falls,
+                turns: self.turns,
+                players: self.players,
+                cpu_reasons: self.cpu_reasons,
+                judgements: self.judgements,
+                reviews: self.reviews,
             }
         }
     }
@@ -123,6 +392,10 @@ pub mod tests {
             MockUiBuilder::new()
         }
 
+        pub fn clear_calls(&self) -> u32 {
+            *self.clear_calls.borrow()
+        }
+
         pub fn update_scores_count(&self) -> u32 {
             *self.update_scores_count.borrow()
         }
@@ -134,5 +407,69 @@ pub mod tests {
         pub fn get_move_calls(&self) -> u32 {
             *self.get_move_calls.borrow()
         }
+
+        pub fn board_updates(&self) -> Vec<Board> {
+            self.board_updates.borrow().clone()
+        }
+
+        pub fn invalid_moves(&self) -> Vec<InvalidMove> {
+            self.invalid_moves.borrow().clone()
+        }
+
+        pub fn hints(&self) -> Vec<BoardMove> {
+            self.hints.borrow().clone()
+        }
+
+        pub fn played_moves(&self) -> Vec<(String, BoardMove)> {
+            self.played_moves.borrow().clone()
+        }
+
+        pub fn survival_result(&self) -> Option<u32> {
+            *self.survival_result.borrow()
+        }
+
+        pub fn practice_results(&self) -> Vec<bool> {
+            self.practice_results.borrow().clone()
+        }
+
+        pub fn last_scores(&self) -> Option<(String, i32, String, i32)> {
+            self.last_scores.borrow().clone()
+        }
+
+        pub fn cpu_reasons(&self) -> Vec<MoveReason> {
+            self.cpu_reasons.borrow().clone()
+        }
+
+        pub fn judgements(&self) -> Vec<MoveJudgement> {
+            self.judgements.borrow().clone()
+        }
+
+        pub fn reviews(&self) -> Vec<Vec<Mistake>> {
+            self.reviews.borrow().clone()
+        }
+
+        pub fn turns(&self) -> Vec<(usize, usize)> {
+            self.turns.borrow().clone()
+        }
+
+        pub fn players(&self) -> Option<(String, String)> {
+            self.players.borrow().clone()
+        }
+
+        pub fn match_summary(&self) -> Option<(String, i32, String, i32)> {
+            self.match_summary.borrow().clone()
+        }
+
+        pub fn announcements(&self) -> Vec<Announcement> {
+            self.announcements.borrow().clone()
+        }
+
+        pub fn clocks(&self) -> Vec<(Duration, Duration)> {
+            self.clocks.borrow().clone()
+        }
+
+        pub fn flag_falls(&self) -> Vec<String> {
+            self.flag_falls.borrow().clone()
+        }
     }
 }
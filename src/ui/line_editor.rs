@@ -0,0 +1,271 @@
+//! This module contains a minimal line editor for interactive terminal input. Supports moving the
+//! cursor, editing in the middle of the line and recalling previously entered lines.
+
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    queue,
+    style::Print,
+    terminal::{self, ClearType},
+};
+use std::{
+    cell::RefCell,
+    io::{self, Write},
+};
+
+#[derive(Debug, Default, PartialEq)]
+pub struct LineBuffer {
+    chars: Vec<char>,
+    cursor: usize,
+}
+
+pub struct LineEditor {
+    history: RefCell<Vec<String>>,
+    interrupted: RefCell<bool>, // Ctrl-C was pressed, so no more lines are read
+}
+
+impl LineBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, c: char) {
+        self.chars.insert(self.cursor, c);
+        self.cursor += 1;
+    }
+
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            self.chars.remove(self.cursor);
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if self.cursor < self.chars.len() {
+            self.chars.remove(self.cursor);
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.chars.len());
+    }
+
+    pub fn move_home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_end(&mut self) {
+        self.cursor = self.chars.len();
+    }
+
+    /// Replaces the whole line (e.g. with a history entry) and moves the cursor to its end.
+    pub fn set(&mut self, text: &str) {
+        self.chars = text.chars().collect();
+        self.cursor = self.chars.len();
+    }
+
+    pub fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+}
+
+impl LineEditor {
+    pub fn new() -> Self {
+        Self {
+            history: RefCell::new(Vec::new()),
+            interrupted: RefCell::new(false),
+        }
+    }
+
+    /// Reads a single line from the terminal in raw mode. Non-empty lines are stored in history,
+    /// which can be browsed with up/down arrows. Returns `None` once Ctrl-C has been pressed, like
+    /// at the end of input, so that callers can quit and clean up the terminal on their way out.
+    pub fn read_line(&self) -> io::Result<Option<String>> {
+        if *self.interrupted.borrow() {
+            return Ok(None);
+        }
+
+        let (start_column, _) = cursor::position()?;
+
+        terminal::enable_raw_mode()?;
+        let result = self.edit_line(start_column);
+        terminal::disable_raw_mode()?;
+        println!();
+
+        let line = result?;
+        match &line {
+            Some(line) if !line.is_empty() && line != "\u{1b}" => {
+                self.history.borrow_mut().push(line.clone())
+            }
+            Some(_) => (),
+            None => *self.interrupted.borrow_mut() = true,
+        }
+
+        Ok(line)
+    }
+
+    fn edit_line(&self, start_column: u16) -> io::Result<Option<String>> {
+        let history = self.history.borrow();
+        let mut history_index = history.len();
+        let mut buffer = LineBuffer::new();
+        let mut stdout = io::stdout();
+
+        loop {
+            let KeyEvent {
+                code,
+                modifiers,
+                kind,
+                ..
+            } = match event::read()? {
+                Event::Key(key_event) => key_event,
+                _ => continue,
+            };
+
+            if kind == KeyEventKind::Release {
+                continue;
+            }
+
+            match code {
+                KeyCode::Enter => return Ok(Some(buffer.text())),
+                // Submitted right away, so that Esc can act as a shortcut (e.g. for menus)
+                KeyCode::Esc => return Ok(Some(String::from("\u{1b}"))),
+                // Raw mode swallows the interrupt signal, so it's reported as the end of input
+                KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => return Ok(None),
+                KeyCode::Char(c) => buffer.insert(c),
+                KeyCode::Backspace => buffer.backspace(),
+                KeyCode::Delete => buffer.delete(),
+                KeyCode::Left => buffer.move_left(),
+                KeyCode::Right => buffer.move_right(),
+                KeyCode::Home => buffer.move_home(),
+                KeyCode::End => buffer.move_end(),
+                KeyCode::Up if history_index > 0 => {
+                    history_index -= 1;
+                    buffer.set(&history[history_index]);
+                }
+                KeyCode::Down if history_index < history.len() => {
+                    history_index += 1;
+                    buffer.set(history.get(history_index).map_or("", String::as_str));
+                }
+                _ => continue,
+            }
+
+            queue!(
+                stdout,
+                cursor::MoveToColumn(start_column),
+                terminal::Clear(ClearType::UntilNewLine),
+                Print(buffer.text()),
+                cursor::MoveToColumn(start_column + buffer.cursor() as u16)
+            )?;
+            stdout.flush()?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_characters() {
+        let mut buffer = LineBuffer::new();
+
+        for c in "Steve".chars() {
+            buffer.insert(c);
+        }
+
+        assert_eq!(buffer.text(), "Steve");
+        assert_eq!(buffer.cursor(), 5, "Cursor should follow inserted text");
+    }
+
+    #[test]
+    fn insert_in_the_middle() {
+        let mut buffer = LineBuffer::new();
+        buffer.set("Stve");
+
+        buffer.move_left();
+        buffer.move_left();
+        buffer.insert('e');
+
+        assert_eq!(
+            buffer.text(),
+            "Steve",
+            "Character should be inserted at cursor"
+        );
+        assert_eq!(buffer.cursor(), 3);
+    }
+
+    #[test]
+    fn backspace_in_the_middle() {
+        let mut buffer = LineBuffer::new();
+        buffer.set("Steeve");
+
+        buffer.move_left();
+        buffer.move_left();
+        buffer.backspace();
+
+        assert_eq!(
+            buffer.text(),
+            "Steve",
+            "Character before cursor should be removed"
+        );
+        assert_eq!(buffer.cursor(), 3);
+
+        buffer.move_home();
+        buffer.backspace();
+
+        assert_eq!(
+            buffer.text(),
+            "Steve",
+            "Backspace at the beginning shouldn't change the line"
+        );
+    }
+
+    #[test]
+    fn no_lines_after_interrupt() {
+        let editor = LineEditor::new();
+        *editor.interrupted.borrow_mut() = true;
+
+        assert!(
+            matches!(editor.read_line(), Ok(None)),
+            "Lines shouldn't be read after Ctrl-C, so that prompts quit"
+        );
+    }
+
+    #[test]
+    fn cursor_stays_within_line() {
+        let mut buffer = LineBuffer::new();
+        buffer.set("ab");
+
+        buffer.move_right();
+        assert_eq!(buffer.cursor(), 2, "Cursor can't move past the end");
+
+        buffer.move_left();
+        buffer.move_left();
+        buffer.move_left();
+        assert_eq!(buffer.cursor(), 0, "Cursor can't move before the beginning");
+
+        buffer.delete();
+        assert_eq!(
+            buffer.text(),
+            "b",
+            "Delete should remove character under cursor"
+        );
+
+        buffer.move_end();
+        buffer.delete();
+        assert_eq!(
+            buffer.text(),
+            "b",
+            "Delete at the end shouldn't change the line"
+        );
+    }
+}
@@ -1,68 +1,330 @@
 //! This module contains implementation of terminal handling. Provides methods for gathering user
 //! input and displaying the board and messages to the user.
 
-use super::Ui;
-use crate::board::{Board, BoardMove, Cell, WINNING_LINES};
-use crate::game::GameResult;
-use crate::tictactoe::GameMode;
-use crossterm::style::{StyledContent, Stylize};
+use super::line_editor::LineEditor;
+use super::{AnnotationStyle, CellAnnotation, Ui};
+use crate::analysis::{Mistake, MoveJudgement, MoveReason};
+use crate::board::{Board, BoardMove, Cell};
+use crate::config::Config;
+use crate::game::{self, DrawReason, GameResult, InvalidMove, PlayerAction, DEFAULT_MOVE_DELAY};
+use crate::scoreboard::{Record, ScoreBoard};
+use crate::tictactoe::{
+    Announcement, GameConfig, GameMode, KeepPlaying, MainMenuChoice, Opponent, PlayerKind,
+};
+use crossterm::{
+    execute,
+    style::{StyledContent, Stylize},
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen},
+};
 use std::{
     cell::RefCell,
-    io::{self, Write},
+    cmp::Ordering,
+    env,
+    ffi::OsStr,
+    fmt::Display,
+    io::{self, BufRead, IsTerminal, Write},
+    time::Duration,
 };
 
 pub struct TerminalUi {
     board: RefCell<Board>,
-    winning_line: RefCell<Option<[usize; 3]>>,
+    winning_lines: RefCell<Vec<Vec<usize>>>,
+    input: Option<RefCell<Box<dyn BufRead>>>, // Read instead of stdin
+    line_editor: Option<LineEditor>,
+    retry_limit: Option<u32>,
+    styled: bool,
+    coordinates: bool,
+    hide_labels: bool,
+    spotlight_win: bool,
+    legend: bool,
+    status_line: bool,
+    player_names: RefCell<Option<(String, String)>>,
+    scroll: bool,
+    alternate_screen: bool,
+    initial_clear: bool,
+    screen_cleared: RefCell<bool>,
+    narration: bool,
+    last_move_narration: RefCell<Option<String>>,
+    explain: bool,
+    last_cpu_reasoning: RefCell<Option<MoveReason>>,
+    last_move_judgement: RefCell<Option<MoveJudgement>>,
+    flag_fall: RefCell<Option<String>>, // Player who ran out of time, until the result is shown
+}
+
+#[derive(Debug, PartialEq)]
+enum MenuChoice {
+    Resume,
+    Restart,
+    Forfeit,
+    Quit,
+}
+
+/// Controls how the board is rendered.
+#[derive(Clone, Debug, Default)]
+struct RenderOptions {
+    winning_lines: Vec<Vec<usize>>,
+    styled: bool,
+    coordinates: bool,   // Column letters above and row numbers beside the grid
+    hide_labels: bool,   // Empty cells drawn without their numbers
+    spotlight_win: bool, // Cells outside winning lines dimmed, instead of the lines highlighted
+    annotations: Vec<CellAnnotation>,
+}
+
+/// Keeps the terminal in its alternate screen buffer for as long as it lives. Leaving happens on
+/// drop, so the user's screen is restored even if the game panics.
+struct AlternateScreen<W: Write> {
+    out: W,
+}
+
+impl<W: Write> AlternateScreen<W> {
+    fn enter(mut out: W) -> io::Result<Self> {
+        execute!(out, EnterAlternateScreen)?;
+        Ok(AlternateScreen { out })
+    }
+}
+
+impl<W: Write> Drop for AlternateScreen<W> {
+    fn drop(&mut self) {
+        // Nothing sensible to do if the terminal is gone already
+        let _ = execute!(self.out, LeaveAlternateScreen);
+    }
 }
 
 const PREFIX: &str = " > ";
+const GAME_MODES: &str = "[1] Player vs Player
+[2] Player vs CPU (Player starts)
+[3] CPU vs Player (CPU starts)
+[4] CPU vs CPU
+[5] Demo (CPU vs CPU, same game every time)
+[6] Custom game
+[7] Survival (Player vs CPU getting stronger every round)
+[8] Practice endgames (find the best move)
+[0 or q] to go back
+";
+const SCORE_BAR_WIDTH: usize = 5;
+const MAX_NAME_LENGTH: usize = 12;
+const BOARD_SIZE: usize = 3; // Games are played on the classic board
 
 impl Ui for TerminalUi {
-    fn get_move(&self, player_name: &str, additional_message: Option<&str>) -> BoardMove {
-        let player_name = Self::format_text_by_player(
+    fn clear(&self) {
+        self.write_clear(&mut io::stdout()).unwrap();
+    }
+
+    fn get_move(&self, player_name: &str) -> PlayerAction {
+        let styled_name = self.paint(Self::format_text_by_player(
             player_name,
             &self.board.borrow().current_player_symbol(),
+        ));
+
+        let message = format!(
+            "{PREFIX}{styled_name}, your move! Enter a number or coordinates (u - undo, h - hint, m - menu): "
         );
 
-        if let Some(msg) = additional_message {
-            print!("{PREFIX}{}, {}. Try again: ", player_name, msg);
-        } else {
-            print!("{PREFIX}{}, your move! Enter a number: ", player_name,);
+        self.prompt(
+            &message,
+            &format!("{PREFIX}Try again: "),
+            |input| match Self::parse_move_input::<BOARD_SIZE>(input) {
+                Ok(action) => Some(action),
+                Err(reason) => {
+                    self.notify_invalid_move(reason);
+                    None
+                }
+            },
+        )
+        .unwrap_or(PlayerAction::Quit)
+    }
+
+    fn notify_invalid_move(&self, reason: InvalidMove) {
+        match reason {
+            InvalidMove::NotAMove | InvalidMove::OutOfRange => {
+                println!(
+                    "{PREFIX}{reason} ({})!",
+                    Self::format_cell_range(BOARD_SIZE)
+                )
+            }
+            _ => println!("{PREFIX}{reason}!"),
         }
+    }
 
-        io::stdout().flush().unwrap();
+    fn notify_hint(&self, board_move: &BoardMove) {
+        println!("{PREFIX}Hint: try {}.", board_move.index() + 1);
+    }
+
+    /// Shows the in-game menu (opened with `m` or `Esc` instead of a move) and asks what to do.
+    fn open_menu(&self) -> Option<PlayerAction> {
+        println!("\n{PREFIX}{}", self.paint("Game paused".bold()));
+        println!("[r] Resume");
+        println!("[s] Restart");
+        println!("[f] Forfeit");
+        println!("[q] Quit to main menu");
 
-        Self::get_move_from_user()
+        let choice = self
+            .prompt(
+                "Your choice: ",
+                "Incorrect input! Enter [r]esume, re[s]tart, [f]orfeit or [q]uit: ",
+                Self::parse_menu_choice,
+            )
+            .unwrap_or(MenuChoice::Quit);
+
+        match choice {
+            MenuChoice::Resume => None,
+            MenuChoice::Restart => Some(PlayerAction::Restart),
+            MenuChoice::Forfeit => Some(PlayerAction::Forfeit),
+            MenuChoice::Quit => Some(PlayerAction::Quit),
+        }
     }
 
     fn update_board(&self, board: &Board) {
         // Update local board copy
-        self.board.replace(board.clone());
+        self.board.replace(*board);
 
         if board.is_empty() {
-            // New game - clear previous win
-            self.winning_line.replace(None);
+            // New game - clear previous win and commentary
+            self.winning_lines.replace(vec![]);
+            self.last_move_narration.replace(None);
+            self.last_cpu_reasoning.replace(None);
+            self.last_move_judgement.replace(None);
         }
 
         self.draw_board();
     }
 
+    fn show_board_with_annotations(&self, board: &Board, annotations: &[CellAnnotation]) {
+        let options = RenderOptions {
+            styled: self.styled,
+            coordinates: self.coordinates,
+            hide_labels: self.hide_labels,
+            annotations: annotations.to_vec(),
+            ..Default::default()
+        };
+
+        println!("{}", Self::render_board(board, &options));
+    }
+
+    fn notify_turn(&self, turn: usize, max_turns: usize) {
+        if !self.status_line {
+            return;
+        }
+
+        let symbol = self.board.borrow().current_player_symbol();
+        let player_names = self.player_names.borrow();
+        let player_name = player_names.as_ref().map(|(player1_name, player2_name)| {
+            if symbol == Cell::O {
+                player1_name.as_str()
+            } else {
+                player2_name.as_str()
+            }
+        });
+        println!(
+            "{PREFIX}{}",
+            self.format_status_line(turn, max_turns, player_name, symbol)
+        );
+    }
+
+    fn notify_players(&self, player1_name: &str, player2_name: &str) {
+        self.player_names
+            .replace(Some((player1_name.to_owned(), player2_name.to_owned())));
+    }
+
+    fn notify_clocks(
+        &self,
+        player1_name: &str,
+        player1_time: Duration,
+        player2_name: &str,
+        player2_time: Duration,
+    ) {
+        println!(
+            "{PREFIX}Time left: {} {} | {} {}",
+            self.paint(Self::format_text_by_player(player1_name, &Cell::O)),
+            Self::format_clock(player1_time),
+            self.paint(Self::format_text_by_player(player2_name, &Cell::X)),
+            Self::format_clock(player2_time),
+        );
+    }
+
+    fn notify_flag_fall(&self, player_name: &str) {
+        self.flag_fall.replace(Some(player_name.to_owned()));
+    }
+
+    fn notify_move(&self, player_name: &str, board_move: &BoardMove) {
+        self.last_cpu_reasoning.replace(None);
+        self.last_move_judgement.replace(None);
+
+        if self.narration {
+            // Printed under the next board, since the screen is cleared before it's drawn
+            let narration = Self::format_narration(player_name, &self.board.borrow(), board_move);
+            self.last_move_narration.replace(Some(narration));
+        }
+    }
+
+    fn notify_cpu_reasoning(&self, reason: MoveReason) {
+        if self.explain {
+            // Printed under the next board, just like the narration
+            self.last_cpu_reasoning.replace(Some(reason));
+        }
+    }
+
+    fn notify_move_judgement(&self, judgement: MoveJudgement) {
+        self.last_move_judgement.replace(Some(judgement));
+    }
+
+    fn notify_review(&self, mistakes: &[Mistake]) {
+        println!("{PREFIX}{}", self.paint("Game review".bold()));
+        if mistakes.is_empty() {
+            println!("{PREFIX}Clean game - every move was one of the best!");
+        }
+
+        let player_names = self.player_names.borrow();
+        for mistake in mistakes {
+            let player_name = match (player_names.as_ref(), mistake.symbol) {
+                (Some((player1_name, _)), Cell::O) => player1_name.clone(),
+                (Some((_, player2_name)), Cell::X) => player2_name.clone(),
+                _ => mistake.symbol.to_string(),
+            };
+            let player_name = Self::format_text_by_player(&player_name, &mistake.symbol);
+            println!("{PREFIX}{}, {mistake}", self.paint(player_name));
+        }
+        io::stdout().flush().unwrap();
+    }
+
     fn notify_result(&self, result: &GameResult) {
         let message = match result {
-            GameResult::Draw => format!("{}", "It's a draw!\n".white()),
-            GameResult::PlayerWon(_winner_index, winner_name, winning_line_index) => {
-                let winning_line = WINNING_LINES[*winning_line_index];
-                self.winning_line.replace(Some(winning_line));
-
-                let winner_name = Self::format_text_by_player(
-                    winner_name,
-                    &self.board.borrow()[winning_line[0]],
-                );
-                format!("{} won!\n", winner_name.underlined())
+            GameResult::Draw(DrawReason::BoardFull) => self.paint("It's a draw!\n".white()),
+            GameResult::Draw(DrawReason::Forced) => {
+                self.paint("It's a draw - no winning line is possible anymore!\n".white())
+            }
+            GameResult::PlayerWon(winner_index, winner_name, winning_line_indices)
+                if winning_line_indices.is_empty() =>
+            {
+                let winner_symbol = if *winner_index == 0 { Cell::O } else { Cell::X };
+                let winner_name = Self::format_text_by_player(winner_name, &winner_symbol);
+                match self.flag_fall.take() {
+                    Some(loser_name) => format!(
+                        "{loser_name} ran out of time! {} won on time!\n",
+                        self.paint(winner_name.underlined())
+                    ),
+                    None => format!("{} won by forfeit!\n", self.paint(winner_name.underlined())),
+                }
+            }
+            GameResult::PlayerWon(_winner_index, winner_name, winning_line_indices) => {
+                let winning_lines: Vec<_> = winning_line_indices
+                    .iter()
+                    .map(|&index| self.board.borrow().run(index).to_vec())
+                    .collect();
+                let winner_symbol = self.board.borrow()[winning_lines[0][0]];
+                self.winning_lines.replace(winning_lines);
+
+                let winner_name = Self::format_text_by_player(winner_name, &winner_symbol);
+                format!(
+                    "{} won on {}!\n",
+                    self.paint(winner_name.underlined()),
+                    game::describe_lines(&self.board.borrow(), winning_line_indices)
+                )
             }
         };
 
+        // Redraw the board with the winning line highlighted
+        self.clear();
         self.draw_board();
 
         println!("{PREFIX}{message}");
@@ -70,60 +332,143 @@ impl Ui for TerminalUi {
     }
 
     fn get_player_name(&self, player_name: &str) -> String {
-        Self::clear_screen();
+        // Any name will do, so this never asks again
+        self.prompt(&format!("{player_name}, enter your name: "), "", |name| {
+            Some(name.to_string())
+        })
+        .unwrap_or_default()
+    }
 
-        print!("{}, enter your name: ", player_name);
-        io::stdout().flush().unwrap();
+    fn main_menu(&self, can_continue: bool) -> MainMenuChoice {
+        println!("{}", self.paint("Tic-Tac-Terminal".bold()));
+        println!("[n] New match");
+        if can_continue {
+            println!("[c] Continue last match");
+        }
+        println!("[s] View stats");
+        println!("[q] Quit");
 
-        Self::get_user_input()
+        let retry_message = if can_continue {
+            "Incorrect input! Enter [n]ew match, [c]ontinue, [s]tats or [q]uit: "
+        } else {
+            "Incorrect input! Enter [n]ew match, [s]tats or [q]uit: "
+        };
+        self.prompt("Your choice: ", retry_message, |answer| {
+            Self::parse_main_menu_choice(answer, can_continue)
+        })
+        .unwrap_or(MainMenuChoice::Quit)
     }
 
-    fn select_mode(&self) -> GameMode {
-        Self::clear_screen();
+    fn show_stats(&self, scoreboard: &ScoreBoard) {
+        println!("\n{PREFIX}{}", self.paint("Stats".bold()));
+
+        let records = scoreboard.records();
+        if records.is_empty() {
+            println!("{PREFIX}No games played yet.");
+        }
+        for (player_name, record) in records {
+            println!("{PREFIX}{}", Self::format_record(player_name, &record));
+        }
+
+        if self.is_interactive() {
+            print!("\n{PREFIX}Press Enter to return to the main menu...");
+            io::stdout().flush().unwrap();
+            self.get_user_input();
+        }
+    }
 
+    fn select_mode(&self) -> GameMode {
         println!("Select game mode!");
-        Self::print_game_modes();
-        print!("Your choice: ");
-        io::stdout().flush().unwrap();
 
-        loop {
-            let user_input = Self::get_user_input();
-
-            break match user_input.to_lowercase().as_str() {
-                "1" | "[1]" => GameMode::PlayerVsPlayer,
-                "2" | "[2]" => GameMode::PlayerVsCpu,
-                "3" | "[3]" => GameMode::CpuVsPlayer,
-                "4" | "[4]" => GameMode::CpuVsCpu,
-                "0" | "q" => GameMode::Quit,
-                _ => {
-                    println!("Incorrect input! Here are the options again:");
-                    Self::print_game_modes();
-                    print!("Enter a number between 1 and 4. To quit, enter 0 or q: ");
-                    io::stdout().flush().unwrap();
-
-                    continue;
+        let retry_message = format!(
+            "Incorrect input! Here are the options again:\n{GAME_MODES}\
+             Enter a number between 1 and 8. To go back, enter 0 or q: "
+        );
+        let mode = self.prompt(
+            &format!("{GAME_MODES}Your choice: "),
+            &retry_message,
+            |answer| {
+                let mode = match answer.to_lowercase().as_str() {
+                    "1" | "[1]" => GameMode::PlayerVsPlayer,
+                    "2" | "[2]" => GameMode::PlayerVsCpu,
+                    "3" | "[3]" => GameMode::CpuVsPlayer,
+                    "4" | "[4]" => GameMode::CpuVsCpu,
+                    "5" | "[5]" => GameMode::Demo,
+                    "6" | "[6]" => self
+                        .select_custom_config()
+                        .map_or(GameMode::Quit, GameMode::Custom),
+                    "7" | "[7]" => self
+                        .prompt(
+                            "Should draws count as survived rounds? y/n: ",
+                            "Incorrect input! Enter [y]es or [n]o: ",
+                            Self::parse_yes_no,
+                        )
+                        .map_or(GameMode::Quit, |draws_survive| GameMode::Survival {
+                            draws_survive,
+                        }),
+                    "8" | "[8]" => GameMode::Practice,
+                    "0" | "q" => GameMode::Quit,
+                    _ => return None,
+                };
+                Some(mode)
+            },
+        );
+
+        mode.unwrap_or(GameMode::Quit)
+    }
+
+    fn keep_playing(&self) -> KeepPlaying {
+        self.prompt(
+            "Again? y/n (s - swap sides, r - reset scores, o - change opponent): ",
+            "Incorrect input! Play again? Enter [y]es, [n]o, [s]wap, [r]eset or [o]pponent: ",
+            |answer| {
+                if !matches!(answer.to_lowercase().as_str(), "o" | "opponent") {
+                    return Self::parse_keep_playing(answer);
                 }
-            };
-        }
+
+                let opponent = self.prompt(
+                    "New opponent? h - human, c - CPU, optionally followed by its chance of \
+                     making a mistake (e.g. c 30): ",
+                    "Incorrect input! Enter h, c or c with a percentage from 0 to 100: ",
+                    Self::parse_opponent,
+                );
+                Some(opponent.map_or(KeepPlaying::Stop, KeepPlaying::ChangeOpponent))
+            },
+        )
+        .unwrap_or(KeepPlaying::Stop)
     }
 
-    fn keep_playing(&self) -> bool {
-        print!("Again? y/n: ");
+    fn notify_survival_over(&self, rounds_survived: u32) {
+        let rounds = if rounds_survived == 1 {
+            "round"
+        } else {
+            "rounds"
+        };
+        println!(
+            "{PREFIX}{}\n",
+            self.paint(format!("Run over! You survived {rounds_survived} {rounds}.").bold())
+        );
         io::stdout().flush().unwrap();
+    }
 
-        loop {
-            let user_input = Self::get_user_input();
-
-            break match user_input.to_lowercase().as_str() {
-                "y" | "yes" => true,
-                "n" | "no" => false,
-                _ => {
-                    print!("Incorrect input! Do you want to play again? Enter [y]es or [n]o: ");
-                    io::stdout().flush().unwrap();
-                    continue;
-                }
-            };
+    fn notify_practice_result(&self, is_best: bool, best_moves: &[BoardMove]) {
+        if is_best {
+            println!(
+                "{PREFIX}{}\n",
+                self.paint("Correct, that's the best move!".bold().green())
+            );
+        } else {
+            let best_moves: Vec<_> = best_moves
+                .iter()
+                .map(|board_move| (board_move.index() + 1).to_string())
+                .collect();
+            println!(
+                "{PREFIX}{} Best moves: {}\n",
+                self.paint("Not quite.".bold().red()),
+                best_moves.join(", ")
+            );
         }
+        io::stdout().flush().unwrap();
     }
 
     fn update_scores(
@@ -133,18 +478,48 @@ impl Ui for TerminalUi {
         player2_name: &str,
         player2_score: i32,
     ) {
+        let [player1_half, player2_half] =
+            Self::format_scores(player1_name, player1_score, player2_name, player2_score);
+
         println!("Current score:");
         println!(
-            "{}: {}",
-            Self::format_text_by_player(player1_name, &Cell::O),
-            player1_score
-        );
-        println!(
-            "{}: {}",
-            Self::format_text_by_player(player2_name, &Cell::X),
-            player2_score
+            "{} | {}",
+            self.paint(Self::format_text_by_player(&player1_half, &Cell::O)),
+            self.paint(Self::format_text_by_player(&player2_half, &Cell::X)),
         );
     }
+
+    fn announce(&self, announcement: &Announcement) {
+        // Plain wins were already told by the result
+        if !matches!(announcement, Announcement::Won(_)) {
+            println!("{PREFIX}{}", self.paint(announcement.to_string().bold()));
+        }
+    }
+
+    fn notify_match_summary(
+        &self,
+        player1_name: &str,
+        player1_score: i32,
+        player2_name: &str,
+        player2_score: i32,
+    ) {
+        let summary =
+            Self::format_match_summary(player1_name, player1_score, player2_name, player2_score);
+        println!("\n{PREFIX}{}", self.paint(summary.bold()));
+
+        // Give the player a chance to read it before the screen is cleared
+        if self.is_interactive() {
+            print!("{PREFIX}Press Enter to return to the main menu...");
+            io::stdout().flush().unwrap();
+            self.get_user_input();
+        }
+    }
+}
+
+impl Default for TerminalUi {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl TerminalUi {
@@ -161,123 +536,1570 @@ impl TerminalUi {
     pub fn new() -> TerminalUi {
         TerminalUi {
             board: RefCell::new(Board::new()),
-            winning_line: RefCell::new(None),
+            winning_lines: RefCell::new(vec![]),
+            input: None,
+            line_editor: None,
+            retry_limit: None,
+            styled: Self::styling_supported(),
+            coordinates: false,
+            hide_labels: false,
+            spotlight_win: false,
+            legend: true,
+            status_line: true,
+            player_names: RefCell::new(None),
+            scroll: false,
+            alternate_screen: false,
+            initial_clear: true,
+            screen_cleared: RefCell::new(false),
+            narration: false,
+            last_move_narration: RefCell::new(None),
+            explain: false,
+            last_cpu_reasoning: RefCell::new(None),
+            last_move_judgement: RefCell::new(None),
+            flag_fall: RefCell::new(None),
         }
     }
 
-    fn draw_board(&self) {
-        let styled_cells: Vec<_> = self
-            .board
-            .borrow()
-            .iter()
-            .enumerate()
-            .map(|(index, cell)| {
-                let mut styled_cell = match cell {
-                    Cell::Empty(_) => format!("[{}]", cell).grey(),
-                    _ => Self::format_text_by_player(format!(" {} ", cell).as_str(), cell),
-                };
+    /// Creates the terminal backend with options taken from the config. Options which the config
+    /// doesn't set keep their defaults.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::{Config, TerminalUi};
+    ///
+    /// let config: Config = "coordinates = true".parse().unwrap();
+    /// let ui = TerminalUi::with_config(&config);
+    /// ```
+    pub fn with_config(config: &Config) -> TerminalUi {
+        let mut ui = TerminalUi::new();
 
-                if let Some(cell_positions) = self.winning_line.borrow().as_ref() {
-                    if cell_positions.contains(&index) {
-                        styled_cell = styled_cell.reverse()
-                    }
-                }
+        if let Some(enabled) = config.styling {
+            ui = ui.styling(enabled);
+        }
+        if let Some(enabled) = config.coordinates {
+            ui = ui.coordinates(enabled);
+        }
+        if let Some(enabled) = config.hide_labels {
+            ui = ui.hide_labels(enabled);
+        }
+        if let Some(enabled) = config.spotlight_win {
+            ui = ui.spotlight_win(enabled);
+        }
+        if let Some(enabled) = config.legend {
+            ui = ui.legend(enabled);
+        }
+        if let Some(enabled) = config.status_line {
+            ui = ui.status_line(enabled);
+        }
+        if let Some(enabled) = config.scroll {
+            ui = ui.scroll(enabled);
+        }
+        if let Some(enabled) = config.alternate_screen {
+            ui = ui.alternate_screen(enabled);
+        }
+        if let Some(enabled) = config.narration {
+            ui = ui.narration(enabled);
+        }
+        if let Some(enabled) = config.explain {
+            ui = ui.explain(enabled);
+        }
+        if let Some(enabled) = config.line_editing {
+            ui = ui.line_editing(enabled);
+        }
+        if let Some(retry_limit) = config.retry_limit {
+            ui = ui.retry_limit(retry_limit);
+        }
 
-                styled_cell
-            })
-            .collect();
+        ui
+    }
 
-        Self::clear_screen();
+    /// Reads all answers (moves, menu choices, names...) from the given reader instead of stdin,
+    /// one per line, so that a scripted demo can drive the game. Line editing doesn't apply to
+    /// such input. Once the reader runs out, every answer is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use tic_tac_terminal::TerminalUi;
+    ///
+    /// // Player vs CPU, named Steve, taking the center
+    /// let ui = TerminalUi::new().input(Cursor::new("2\nSteve\n5\n"));
+    /// ```
+    pub fn input(mut self, reader: impl BufRead + 'static) -> Self {
+        self.input = Some(RefCell::new(Box::new(reader)));
+        self
+    }
 
-        println!("\n   +-----+-----+-----+");
-        println!("   |     |     |     |");
-        println!(
-            "   | {} | {} | {} |",
-            styled_cells[0], styled_cells[1], styled_cells[2]
-        );
-        println!("   |     |     |     |");
-        println!("   +-----+-----+-----+");
-        println!("   |     |     |     |");
-        println!(
-            "   | {} | {} | {} |",
-            styled_cells[3], styled_cells[4], styled_cells[5]
-        );
-        println!("   |     |     |     |");
-        println!("   +-----+-----+-----+");
-        println!("   |     |     |     |");
-        println!(
-            "   | {} | {} | {} |",
-            styled_cells[6], styled_cells[7], styled_cells[8]
-        );
-        println!("   |     |     |     |");
-        println!("   +-----+-----+-----+\n");
+    /// Enables line editing (cursor movement, editing in the middle of the line and recalling
+    /// previous entries with arrow keys) for user input. Piped input is always read line by line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::TerminalUi;
+    ///
+    /// let ui = TerminalUi::new().line_editing(true);
+    /// ```
+    pub fn line_editing(mut self, enabled: bool) -> Self {
+        self.line_editor = enabled.then(LineEditor::new);
+        self
+    }
 
-        io::stdout().flush().unwrap();
+    /// Limits the number of incorrect answers to every prompt. Once the limit is reached, the UI
+    /// gives up and quits (a move prompt quits to the main menu). Unlimited by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::TerminalUi;
+    ///
+    /// let ui = TerminalUi::new().retry_limit(3);
+    /// ```
+    pub fn retry_limit(mut self, retry_limit: u32) -> Self {
+        self.retry_limit = Some(retry_limit);
+        self
     }
 
-    fn get_move_from_user() -> BoardMove {
-        loop {
-            let user_input = Self::get_user_input();
-
-            break match user_input.parse() {
-                Ok(number) => {
-                    let board_move = BoardMove::try_new(number);
-                    match board_move {
-                        Ok(board_move) => board_move,
-                        Err(_) => {
-                            print!("{PREFIX}Your input must be between 1 and 9! Try again: ");
-                            io::stdout().flush().unwrap();
-
-                            continue;
-                        }
-                    }
-                }
-                Err(_) => {
-                    print!("{PREFIX}Your input must be a number between 1 and 9! Try again: ");
-                    io::stdout().flush().unwrap();
+    /// Turns colours and text styles on or off. By default output is styled only if it goes to a
+    /// terminal which supports it, so piped output doesn't contain escape codes. Setting the
+    /// `NO_COLOR` environment variable turns styling off by default as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::TerminalUi;
+    ///
+    /// let ui = TerminalUi::new().styling(false);
+    /// ```
+    pub fn styling(mut self, enabled: bool) -> Self {
+        self.styled = enabled;
+        self
+    }
 
-                    continue;
-                }
-            };
-        }
+    /// Shows column letters (a, b, c) above and row numbers (1, 2, 3) beside the board. Off by
+    /// default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::TerminalUi;
+    ///
+    /// let ui = TerminalUi::new().coordinates(true);
+    /// ```
+    pub fn coordinates(mut self, enabled: bool) -> Self {
+        self.coordinates = enabled;
+        self
     }
 
-    fn get_user_input() -> String {
-        let mut buffer = String::new();
-        io::stdin().read_line(&mut buffer).unwrap();
+    /// Draws empty cells without their numbers, for a cleaner look once the layout is familiar.
+    /// Moves are still entered as cell numbers. Off by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::TerminalUi;
+    ///
+    /// let ui = TerminalUi::new().hide_labels(true);
+    /// ```
+    pub fn hide_labels(mut self, enabled: bool) -> Self {
+        self.hide_labels = enabled;
+        self
+    }
 
-        buffer.trim().to_string()
+    /// Spotlights the win on the final board by dimming every cell outside the winning lines,
+    /// instead of highlighting the lines themselves. Has no effect when styling is off. Off by
+    /// default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::TerminalUi;
+    ///
+    /// let ui = TerminalUi::new().spotlight_win(true);
+    /// ```
+    pub fn spotlight_win(mut self, enabled: bool) -> Self {
+        self.spotlight_win = enabled;
+        self
     }
 
-    fn clear_screen() {
-        print!("\x1B[2J");
-        print!("\x1B[H");
-        io::stdout().flush().unwrap();
+    /// Shows a legend below the board, telling which player has which mark, e.g.
+    /// "Steve: O | CPU: X". On by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::TerminalUi;
+    ///
+    /// let ui = TerminalUi::new().legend(false);
+    /// ```
+    pub fn legend(mut self, enabled: bool) -> Self {
+        self.legend = enabled;
+        self
     }
 
-    fn format_text_by_player(text: &str, current_player_symbol: &Cell) -> StyledContent<String> {
-        match current_player_symbol {
-            &Cell::O => text.to_string().bold().blue(),
-            &Cell::X => text.to_string().bold().green(),
-            _ => text.to_string().grey(),
-        }
+    /// Shows a status line below the board, telling which turn it is and who's to move, e.g.
+    /// "Turn 4 of 9 - Steve (X) to move". On by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::TerminalUi;
+    ///
+    /// let ui = TerminalUi::new().status_line(false);
+    /// ```
+    pub fn status_line(mut self, enabled: bool) -> Self {
+        self.status_line = enabled;
+        self
     }
 
-    fn print_game_modes() {
-        println!("[1] Player vs Player");
-        println!("[2] Player vs CPU (Player starts)");
-        println!("[3] CPU vs Player (CPU starts)");
-        println!("[4] CPU vs CPU");
-        println!("[0 or q] to quit!")
+    /// Prints every new board below the previous one instead of clearing the screen, so the whole
+    /// game stays in the scrollback. Off by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::TerminalUi;
+    ///
+    /// let ui = TerminalUi::new().scroll(true);
+    /// ```
+    pub fn scroll(mut self, enabled: bool) -> Self {
+        self.scroll = enabled;
+        self
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Runs the game in the terminal's alternate screen buffer, so that the user's scrollback is
+    /// left untouched and comes back on quit. Only takes effect inside `run`. Off by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::TerminalUi;
+    ///
+    /// let ui = TerminalUi::new().alternate_screen(true);
+    /// ```
+    pub fn alternate_screen(mut self, enabled: bool) -> Self {
+        self.alternate_screen = enabled;
+        self
+    }
 
-    #[test]
-    fn empty_at_creation() {
+    /// Runs `f`, e.g. a whole game, with the terminal set up for it. With the alternate screen
+    /// enabled, the terminal enters it first and leaves it once `f` returns or panics.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use tic_tac_terminal::{TerminalUi, TicTacToe};
+    ///
+    /// let ui = TerminalUi::new().alternate_screen(true);
+    /// let mut game = TicTacToe::new(&ui);
+    /// ui.run(|| game.start());
+    /// ```
+    pub fn run<T>(&self, f: impl FnOnce() -> T) -> T {
+        let _screen = if self.alternate_screen {
+            AlternateScreen::enter(io::stdout()).ok()
+        } else {
+            None
+        };
+
+        f()
+    }
+
+    /// Prints a line of commentary below the board after every move, e.g. "CPU blocks at 7". Off
+    /// by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::TerminalUi;
+    ///
+    /// let ui = TerminalUi::new().narration(true);
+    /// ```
+    pub fn narration(mut self, enabled: bool) -> Self {
+        self.narration = enabled;
+        self
+    }
+
+    /// Explains the CPU's moves below the board, e.g. "blocking your threat" or "creating a fork",
+    /// as a teaching aid. Off by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::TerminalUi;
+    ///
+    /// let ui = TerminalUi::new().explain(true);
+    /// ```
+    pub fn explain(mut self, enabled: bool) -> Self {
+        self.explain = enabled;
+        self
+    }
+
+    /// Controls whether the very first clear (before the main menu) wipes the screen. Turning it
+    /// off keeps whatever the host program printed before the game started. Later clears aren't
+    /// affected. On by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tic_tac_terminal::TerminalUi;
+    ///
+    /// let ui = TerminalUi::new().initial_clear(false);
+    /// ```
+    pub fn initial_clear(mut self, enabled: bool) -> Self {
+        self.initial_clear = enabled;
+        self
+    }
+
+    /// Clears the screen and moves the cursor to the top, or in scroll mode just separates the
+    /// next screen with an empty line.
+    fn write_clear(&self, out: &mut impl Write) -> io::Result<()> {
+        let first_clear = !self.screen_cleared.replace(true);
+
+        if first_clear && !self.initial_clear {
+            return Ok(());
+        }
+
+        if self.scroll {
+            writeln!(out)?;
+        } else {
+            write!(out, "\x1B[2J\x1B[H")?;
+        }
+        out.flush()
+    }
+
+    fn draw_board(&self) {
+        let options = RenderOptions {
+            winning_lines: self.winning_lines.borrow().clone(),
+            styled: self.styled,
+            coordinates: self.coordinates,
+            hide_labels: self.hide_labels,
+            spotlight_win: self.spotlight_win,
+            ..Default::default()
+        };
+        let rendered = Self::render_board(&self.board.borrow(), &options);
+
+        println!("{rendered}");
+        if let Some((player1_name, player2_name)) = self.player_names.borrow().as_ref() {
+            if self.legend {
+                println!("{PREFIX}{}", self.format_legend(player1_name, player2_name));
+            }
+        }
+        if let Some(narration) = self.last_move_narration.borrow().as_ref() {
+            println!("{PREFIX}{narration}");
+        }
+        if let Some(reason) = self.last_cpu_reasoning.borrow().as_ref() {
+            println!("{PREFIX}CPU's reasoning: {reason}");
+        }
+        if let Some(judgement) = self.last_move_judgement.borrow().as_ref() {
+            println!("{PREFIX}Engine: {judgement}");
+        }
+        io::stdout().flush().unwrap();
+    }
+
+    /// Pairs each player's name with their mark, both in the player's colour, e.g.
+    /// `Steve: O | CPU: X`.
+    fn format_legend(&self, player1_name: &str, player2_name: &str) -> String {
+        [(player1_name, Cell::O), (player2_name, Cell::X)]
+            .map(|(name, symbol)| {
+                format!(
+                    "{}: {}",
+                    self.paint(Self::format_text_by_player(name, &symbol)),
+                    self.paint(Self::format_text_by_player(&symbol.to_string(), &symbol))
+                )
+            })
+            .join(" | ")
+    }
+
+    /// Tells which turn it is and who's to move, e.g. `Turn 4 of 9 - Steve (X) to move`. Only the
+    /// mark is named when the players aren't known.
+    fn format_status_line(
+        &self,
+        turn: usize,
+        max_turns: usize,
+        player_name: Option<&str>,
+        symbol: Cell,
+    ) -> String {
+        let player = match player_name {
+            Some(player_name) => format!("{player_name} ({symbol})"),
+            None => symbol.to_string(),
+        };
+        format!(
+            "Turn {turn} of {max_turns} - {} to move",
+            self.paint(Self::format_text_by_player(&player, &symbol))
+        )
+    }
+
+    /// Describes a move in words, given the board from before the move.
+    fn format_narration(player_name: &str, board: &Board, board_move: &BoardMove) -> String {
+        let symbol = board.current_player_symbol();
+        let cell = board_move.index() + 1;
+
+        if board.is_winning_move(symbol, board_move) {
+            format!("{player_name} wins at {cell}")
+        } else if board.is_winning_move(symbol.opposite(), board_move) {
+            format!("{player_name} blocks at {cell}")
+        } else if cell == 5 {
+            format!("{player_name} plays center")
+        } else {
+            format!("{player_name} plays {cell}")
+        }
+    }
+
+    /// Renders the board as a multi-line string, without printing it. Boards of any size are laid
+    /// out row by row.
+    fn render_board<const N: usize>(board: &Board<N>, options: &RenderOptions) -> String {
+        let separator = format!("   +{}\n", "-----+".repeat(N));
+        let padding = format!("   |{}\n", "     |".repeat(N));
+        let (column_labels, row_labels) = Self::coordinate_labels(N);
+
+        let mut rendered = String::from("\n");
+        if options.coordinates {
+            rendered += &format!("{column_labels}\n");
+        }
+        rendered += &separator;
+        for ((row_index, row), row_label) in board.rows().enumerate().zip(row_labels) {
+            let row_label = if options.coordinates {
+                row_label
+            } else {
+                String::from("   ")
+            };
+            let cells: String = row
+                .iter()
+                .enumerate()
+                .map(|(column, cell)| {
+                    let cell = Self::render_cell(cell, row_index * N + column, options);
+                    format!(" {cell} |")
+                })
+                .collect();
+
+            rendered += &padding;
+            rendered += &format!("{row_label}|{cells}\n");
+            rendered += &padding;
+            rendered += &separator;
+        }
+
+        rendered
+    }
+
+    /// Renders a single cell, 3 characters wide, styled according to the options.
+    fn render_cell(cell: &Cell, index: usize, options: &RenderOptions) -> String {
+        let annotation = options
+            .annotations
+            .iter()
+            .find(|annotation| annotation.index == index);
+        if let Some(annotation) = annotation {
+            return Self::render_annotation(annotation, options.styled);
+        }
+
+        let text = match cell {
+            Cell::Empty(_) if options.hide_labels => String::from("[ ]"),
+            Cell::Empty(_) => format!("[{}]", cell),
+            _ => format!(" {} ", cell),
+        };
+
+        if !options.styled {
+            return text;
+        }
+
+        let mut styled_cell = match cell {
+            Cell::Empty(_) => text.grey(),
+            Cell::Blocked => text.dark_grey(),
+            _ => Self::format_text_by_player(&text, cell),
+        };
+
+        let on_winning_line = options
+            .winning_lines
+            .iter()
+            .any(|line| line.contains(&index));
+        let game_won = !options.winning_lines.is_empty();
+        if on_winning_line && !options.spotlight_win {
+            styled_cell = styled_cell.reverse();
+        } else if game_won && !on_winning_line && options.spotlight_win {
+            styled_cell = styled_cell.dim();
+        }
+
+        styled_cell.to_string()
+    }
+
+    /// Renders an annotation in place of a cell, centered in 3 characters.
+    fn render_annotation(annotation: &CellAnnotation, styled: bool) -> String {
+        let text: String = annotation.text.chars().take(3).collect();
+        let text = format!("{text:^3}");
+
+        if !styled {
+            return text;
+        }
+
+        let styled_text = match annotation.style {
+            AnnotationStyle::Good => text.green().bold(),
+            AnnotationStyle::Bad => text.red().bold(),
+            AnnotationStyle::Note => text.yellow(),
+        };
+        styled_text.to_string()
+    }
+
+    /// Describes which cells can be entered on a board of the given size, e.g. "1 to 9, or a1 to
+    /// c3".
+    fn format_cell_range(size: usize) -> String {
+        let last_column = (b'a' + size as u8 - 1) as char;
+        format!("1 to {}, or a1 to {last_column}{size}", size * size)
+    }
+
+    /// Returns the line of column letters, centered above the cells, and a 3 characters wide label
+    /// for every row, printed in the margin to the left of the grid.
+    fn coordinate_labels(size: usize) -> (String, Vec<String>) {
+        let column_labels: String = (0..size)
+            .map(|column| format!("{:^6}", (b'a' + column as u8) as char))
+            .collect();
+        let row_labels = (1..=size).map(|row| format!("{row:>2} ")).collect();
+
+        (format!("    {}", column_labels.trim_end()), row_labels)
+    }
+
+    fn select_custom_config(&self) -> Option<GameConfig> {
+        Some(GameConfig {
+            player1: self.prompt(
+                "Player1 - [h]uman or [c]pu? ",
+                "Incorrect input! Enter [h]uman or [c]pu: ",
+                Self::parse_player_kind,
+            )?,
+            player2: self.prompt(
+                "Player2 - [h]uman or [c]pu? ",
+                "Incorrect input! Enter [h]uman or [c]pu: ",
+                Self::parse_player_kind,
+            )?,
+            move_delay: self.prompt(
+                "CPU move delay in milliseconds (leave empty for default): ",
+                "Incorrect input! Enter a number of milliseconds or leave empty: ",
+                Self::parse_move_delay,
+            )?,
+            seed: self.prompt(
+                "Seed for CPU moves (leave empty for random): ",
+                "Incorrect input! Enter a number or leave empty: ",
+                Self::parse_seed,
+            )?,
+            auto_names: self.prompt(
+                "Name players \"Player 1\" and \"Player 2\" automatically? y/n: ",
+                "Incorrect input! Enter [y]es or [n]o: ",
+                Self::parse_yes_no,
+            )?,
+        })
+    }
+
+    /// Prints `message` and reads answers until `parse` accepts one, printing `retry_message`
+    /// after every rejected answer. Returns `None` if the retry limit is reached or the input
+    /// ends.
+    fn prompt<R>(
+        &self,
+        message: &str,
+        retry_message: &str,
+        parse: impl FnMut(&str) -> Option<R>,
+    ) -> Option<R> {
+        self.prompt_from(message, retry_message, parse, || self.get_user_input())
+    }
+
+    /// Same as `prompt`, but takes answers from `read_input` instead of the terminal.
+    fn prompt_from<R>(
+        &self,
+        message: &str,
+        retry_message: &str,
+        mut parse: impl FnMut(&str) -> Option<R>,
+        mut read_input: impl FnMut() -> Option<String>,
+    ) -> Option<R> {
+        print!("{message}");
+        io::stdout().flush().unwrap();
+
+        self.retry(|| {
+            // No more answers will come, so stop asking
+            let Some(input) = read_input() else {
+                return Some(None);
+            };
+
+            let answer = parse(&input);
+            if answer.is_none() {
+                print!("{retry_message}");
+                io::stdout().flush().unwrap();
+            }
+            answer.map(Some)
+        })
+        .flatten()
+    }
+
+    /// Repeats `attempt` until it succeeds. Gives up and returns `None` once the number of failed
+    /// attempts reaches the retry limit, if there is one.
+    fn retry<R>(&self, mut attempt: impl FnMut() -> Option<R>) -> Option<R> {
+        let mut failures = 0;
+
+        loop {
+            if let Some(result) = attempt() {
+                return Some(result);
+            }
+
+            failures += 1;
+            if self.retry_limit.is_some_and(|limit| failures >= limit) {
+                println!("\n{PREFIX}Too many incorrect attempts, giving up.");
+                return None;
+            }
+        }
+    }
+
+    fn parse_move_input<const N: usize>(user_input: &str) -> Result<PlayerAction, InvalidMove> {
+        match user_input.to_lowercase().as_str() {
+            "m" | "menu" | "\u{1b}" => return Ok(PlayerAction::OpenMenu),
+            "u" | "undo" => return Ok(PlayerAction::Undo),
+            "h" | "hint" => return Ok(PlayerAction::Hint),
+            _ => (),
+        }
+
+        Self::parse_move::<N>(user_input).map(PlayerAction::Move)
+    }
+
+    /// Parses a move given as a cell number (`5`), a column letter and a row number in either
+    /// order (`b2` or `2b`), or a row and a column number separated by a comma or space (`2,2`).
+    /// Letters and numbers follow the coordinates drawn around the board. Plain digits are always
+    /// a cell number, so `22` is out of range rather than a coordinate.
+    fn parse_move<const N: usize>(user_input: &str) -> Result<BoardMove, InvalidMove> {
+        let input = user_input.trim().to_lowercase();
+        if let Ok(number) = input.parse() {
+            return BoardMove::try_new_for::<N>(number).map_err(|_| InvalidMove::OutOfRange);
+        }
+
+        let is_separator = |c: char| c == ',' || c.is_whitespace();
+        let (row, column) = if let Some((row, column)) = input.split_once(is_separator) {
+            let parse = |number: &str| number.trim_matches(is_separator).parse::<usize>().ok();
+            parse(row).zip(parse(column)).ok_or(InvalidMove::NotAMove)?
+        } else {
+            let letter_column = |letter: char| letter as usize - 'a' as usize + 1;
+            let digit_row = |digit: char| digit as usize - '0' as usize;
+
+            match input.chars().collect::<Vec<_>>()[..] {
+                [letter @ 'a'..='z', digit @ '0'..='9']
+                | [digit @ '0'..='9', letter @ 'a'..='z'] => {
+                    (digit_row(digit), letter_column(letter))
+                }
+                _ => return Err(InvalidMove::NotAMove),
+            }
+        };
+
+        if !(1..=N).contains(&row) || !(1..=N).contains(&column) {
+            return Err(InvalidMove::OutOfRange);
+        }
+        BoardMove::try_new_for::<N>((row - 1) * N + column).map_err(|_| InvalidMove::OutOfRange)
+    }
+
+    /// Parses the main menu choice. Continuing is only accepted if there's a match to continue.
+    fn parse_main_menu_choice(user_input: &str, can_continue: bool) -> Option<MainMenuChoice> {
+        match user_input.to_lowercase().as_str() {
+            "n" | "new" => Some(MainMenuChoice::NewMatch),
+            "c" | "continue" if can_continue => Some(MainMenuChoice::ContinueMatch),
+            "s" | "stats" => Some(MainMenuChoice::ViewStats),
+            "q" | "quit" => Some(MainMenuChoice::Quit),
+            _ => None,
+        }
+    }
+
+    fn parse_menu_choice(user_input: &str) -> Option<MenuChoice> {
+        match user_input.to_lowercase().as_str() {
+            "" | "r" | "resume" => Some(MenuChoice::Resume),
+            "s" | "restart" => Some(MenuChoice::Restart),
+            "f" | "forfeit" => Some(MenuChoice::Forfeit),
+            "q" | "quit" => Some(MenuChoice::Quit),
+            _ => None,
+        }
+    }
+
+    fn parse_player_kind(user_input: &str) -> Option<PlayerKind> {
+        match user_input.to_lowercase().as_str() {
+            "h" | "human" => Some(PlayerKind::Human),
+            "c" | "cpu" => Some(PlayerKind::Cpu),
+            _ => None,
+        }
+    }
+
+    fn parse_move_delay(user_input: &str) -> Option<Duration> {
+        if user_input.is_empty() {
+            return Some(DEFAULT_MOVE_DELAY);
+        }
+
+        user_input.parse().ok().map(Duration::from_millis)
+    }
+
+    fn parse_seed(user_input: &str) -> Option<Option<u64>> {
+        if user_input.is_empty() {
+            return Some(None);
+        }
+
+        user_input.parse().ok().map(Some)
+    }
+
+    fn parse_yes_no(user_input: &str) -> Option<bool> {
+        match user_input.to_lowercase().as_str() {
+            "y" | "yes" => Some(true),
+            "n" | "no" => Some(false),
+            _ => None,
+        }
+    }
+
+    fn parse_keep_playing(user_input: &str) -> Option<KeepPlaying> {
+        match user_input.to_lowercase().as_str() {
+            "y" | "yes" => Some(KeepPlaying::Continue),
+            "n" | "no" => Some(KeepPlaying::Stop),
+            "r" | "reset" => Some(KeepPlaying::Reset),
+            "s" | "swap" => Some(KeepPlaying::Swap),
+            _ => None,
+        }
+    }
+
+    fn parse_opponent(user_input: &str) -> Option<Opponent> {
+        let user_input = user_input.to_lowercase();
+        let mut words = user_input.split_whitespace();
+
+        let opponent = match (words.next()?, words.next()) {
+            ("h" | "human", None) => Opponent::Human,
+            ("c" | "cpu", None) => Opponent::Cpu { mistake_chance: 0 },
+            ("c" | "cpu", Some(percentage)) => Opponent::Cpu {
+                mistake_chance: percentage
+                    .trim_end_matches('%')
+                    .parse()
+                    .ok()
+                    .filter(|&chance| chance <= 100)?,
+            },
+            _ => return None,
+        };
+
+        words.next().is_none().then_some(opponent)
+    }
+
+    /// Reads a trimmed line of input. Returns `None` at the end of input, e.g. once a piped file
+    /// has been read through.
+    fn get_user_input(&self) -> Option<String> {
+        let mut buffer = String::new();
+
+        let bytes_read = if let Some(input) = &self.input {
+            input.borrow_mut().read_line(&mut buffer).unwrap()
+        } else if let (Some(line_editor), true) = (&self.line_editor, self.is_interactive()) {
+            match line_editor.read_line() {
+                Ok(line) => return line,
+                // The terminal couldn't be queried or switched to raw mode, so read it plainly
+                Err(_) => io::stdin().read_line(&mut buffer).unwrap(),
+            }
+        } else {
+            io::stdin().read_line(&mut buffer).unwrap()
+        };
+
+        (bytes_read > 0).then(|| buffer.trim().to_string())
+    }
+
+    /// Checks whether answers are typed in by a person, rather than scripted or piped.
+    fn is_interactive(&self) -> bool {
+        self.input.is_none() && io::stdin().is_terminal()
+    }
+
+    /// Checks whether styled output would render properly: it has to go to a terminal, which isn't
+    /// a dumb one, and the user mustn't have asked for no colours (see <https://no-color.org>).
+    fn styling_supported() -> bool {
+        Self::styling_supported_for(
+            env::var_os("NO_COLOR").as_deref(),
+            env::var_os("TERM").as_deref(),
+            io::stdout().is_terminal(),
+        )
+    }
+
+    /// Same as `styling_supported`, but takes the environment as arguments: the values of
+    /// `NO_COLOR` and `TERM`, and whether stdout is a terminal.
+    fn styling_supported_for(
+        no_color: Option<&OsStr>,
+        term: Option<&OsStr>,
+        is_terminal: bool,
+    ) -> bool {
+        let no_color = no_color.is_some_and(|value| !value.is_empty());
+        let dumb_terminal = term.is_some_and(|term| term == "dumb");
+
+        is_terminal && !no_color && !dumb_terminal
+    }
+
+    /// Renders styled text, or just the bare text if styling is turned off. Every styled message
+    /// goes through here, so the decision is made in one place.
+    fn paint<D: Display>(&self, content: StyledContent<D>) -> String {
+        if self.styled {
+            content.to_string()
+        } else {
+            content.content().to_string()
+        }
+    }
+
+    fn format_text_by_player(text: &str, current_player_symbol: &Cell) -> StyledContent<String> {
+        match *current_player_symbol {
+            Cell::O => text.to_string().bold().blue(),
+            Cell::X => text.to_string().bold().green(),
+            _ => text.to_string().grey(),
+        }
+    }
+
+    /// Formats remaining time as minutes and seconds, e.g. `1:05`. Tenths of a second are shown
+    /// for the last ten seconds, e.g. `0:07.4`.
+    fn format_clock(time: Duration) -> String {
+        let seconds = time.as_secs();
+        if seconds < 10 {
+            format!("0:{seconds:02}.{}", time.subsec_millis() / 100)
+        } else {
+            format!("{}:{:02}", seconds / 60, seconds % 60)
+        }
+    }
+
+    /// Formats a player's results, e.g. `Steve: 3 W / 1 D / 0 L`.
+    fn format_record(player_name: &str, record: &Record) -> String {
+        format!(
+            "{player_name}: {} W / {} D / {} L",
+            record.wins, record.draws, record.losses
+        )
+    }
+
+    /// Formats both players' halves of the scoreboard, e.g. `Steve ███░░ 3` and `2 ░░███ CPU`.
+    /// Bars are scaled down once any score exceeds the bar width.
+    fn format_scores(
+        player1_name: &str,
+        player1_score: i32,
+        player2_name: &str,
+        player2_score: i32,
+    ) -> [String; 2] {
+        let scale = player1_score.max(player2_score).max(SCORE_BAR_WIDTH as i32) as usize;
+        let filled = |score: i32| score.max(0) as usize * SCORE_BAR_WIDTH / scale;
+
+        let player1_filled = filled(player1_score);
+        let player2_filled = filled(player2_score);
+
+        [
+            format!(
+                "{} {}{} {}",
+                Self::truncate_name(player1_name),
+                "█".repeat(player1_filled),
+                "░".repeat(SCORE_BAR_WIDTH - player1_filled),
+                player1_score
+            ),
+            format!(
+                "{} {}{} {}",
+                player2_score,
+                "░".repeat(SCORE_BAR_WIDTH - player2_filled),
+                "█".repeat(player2_filled),
+                Self::truncate_name(player2_name)
+            ),
+        ]
+    }
+
+    /// Formats the final score of a match and names its winner, e.g.
+    /// `Final score: Steve 2 - 1 CPU. Steve wins the match!`.
+    fn format_match_summary(
+        player1_name: &str,
+        player1_score: i32,
+        player2_name: &str,
+        player2_score: i32,
+    ) -> String {
+        let verdict = match player1_score.cmp(&player2_score) {
+            Ordering::Greater => format!("{player1_name} wins the match!"),
+            Ordering::Less => format!("{player2_name} wins the match!"),
+            Ordering::Equal => String::from("The match is tied."),
+        };
+
+        format!(
+            "Final score: {player1_name} {player1_score} - {player2_score} {player2_name}. {verdict}"
+        )
+    }
+
+    fn truncate_name(name: &str) -> String {
+        if name.chars().count() > MAX_NAME_LENGTH {
+            let truncated: String = name.chars().take(MAX_NAME_LENGTH - 1).collect();
+            format!("{truncated}…")
+        } else {
+            name.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::WINNING_LINES;
+    use std::io::Cursor;
+
+    #[test]
+    fn match_summary() {
+        assert_eq!(
+            TerminalUi::format_match_summary("Steve", 2, "CPU", 1),
+            "Final score: Steve 2 - 1 CPU. Steve wins the match!"
+        );
+        assert_eq!(
+            TerminalUi::format_match_summary("Steve", 0, "CPU", 3),
+            "Final score: Steve 0 - 3 CPU. CPU wins the match!"
+        );
+        assert_eq!(
+            TerminalUi::format_match_summary("Steve", 0, "Elon", 0),
+            "Final score: Steve 0 - 0 Elon. The match is tied.",
+            "Quitting before any game is finished should be a tie"
+        );
+    }
+
+    #[test]
+    fn narration_lines() {
+        let board: Board = "OO..X....".parse().unwrap();
+
+        assert_eq!(
+            TerminalUi::format_narration("CPU", &board, &BoardMove::try_new(3).unwrap()),
+            "CPU blocks at 3",
+            "'X' stops 'O' from completing the top row"
+        );
+        assert_eq!(
+            TerminalUi::format_narration("Steve", &board, &BoardMove::try_new(7).unwrap()),
+            "Steve plays 7"
+        );
+
+        let board: Board = "OX.OX....".parse().unwrap();
+        assert_eq!(
+            TerminalUi::format_narration("Steve", &board, &BoardMove::try_new(7).unwrap()),
+            "Steve wins at 7"
+        );
+        assert_eq!(
+            TerminalUi::format_narration("Steve", &Board::new(), &BoardMove::try_new(5).unwrap()),
+            "Steve plays center"
+        );
+    }
+
+    #[test]
+    fn initial_clear_can_be_suppressed() {
+        let tui = TerminalUi::new().initial_clear(false);
+
+        let mut output = vec![];
+        tui.write_clear(&mut output).unwrap();
+        assert!(output.is_empty(), "First clear should be skipped");
+
+        let mut output = vec![];
+        tui.write_clear(&mut output).unwrap();
+        assert_eq!(
+            output, b"\x1B[2J\x1B[H",
+            "Following clears should work as usual"
+        );
+    }
+
+    #[test]
+    fn alternate_screen_is_left_on_drop() {
+        let mut output = vec![];
+        {
+            let _screen = AlternateScreen::enter(&mut output).unwrap();
+        }
+        assert_eq!(
+            output, b"\x1B[?1049h\x1B[?1049l",
+            "Alternate screen should be entered and then left"
+        );
+
+        let mut output = vec![];
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _screen = AlternateScreen::enter(&mut output).unwrap();
+            panic!("Game crashed");
+        }));
+        assert!(result.is_err());
+        assert!(
+            output.ends_with(b"\x1B[?1049l"),
+            "Alternate screen should be left even on panic"
+        );
+    }
+
+    #[test]
+    fn scroll_mode_doesnt_clear() {
+        let mut output = vec![];
+        TerminalUi::new().write_clear(&mut output).unwrap();
+        assert_eq!(
+            output, b"\x1B[2J\x1B[H",
+            "Screen should be cleared by default"
+        );
+
+        let mut output = vec![];
+        TerminalUi::new()
+            .scroll(true)
+            .write_clear(&mut output)
+            .unwrap();
+        assert!(
+            !output.contains(&0x1B),
+            "No escape sequence should be emitted in scroll mode"
+        );
+        assert_eq!(
+            output, b"\n",
+            "Screens should be separated by an empty line"
+        );
+    }
+
+    #[test]
+    fn parse_move_input() {
+        assert_eq!(
+            TerminalUi::parse_move_input::<3>("5"),
+            Ok(PlayerAction::Move(BoardMove::try_new(5).unwrap()))
+        );
+        assert_eq!(
+            TerminalUi::parse_move_input::<3>("m"),
+            Ok(PlayerAction::OpenMenu),
+            "'m' should open the menu"
+        );
+        assert_eq!(
+            TerminalUi::parse_move_input::<3>("\u{1b}"),
+            Ok(PlayerAction::OpenMenu),
+            "Esc should open the menu"
+        );
+        assert_eq!(
+            TerminalUi::parse_move_input::<3>("U"),
+            Ok(PlayerAction::Undo),
+            "'u' should take back a move"
+        );
+        assert_eq!(
+            TerminalUi::parse_move_input::<3>("hint"),
+            Ok(PlayerAction::Hint),
+            "'h' should ask for a hint"
+        );
+        assert_eq!(
+            TerminalUi::parse_move_input::<3>("10"),
+            Err(InvalidMove::OutOfRange)
+        );
+        assert_eq!(
+            TerminalUi::parse_move_input::<3>("five"),
+            Err(InvalidMove::NotAMove)
+        );
+    }
+
+    #[test]
+    fn parse_move() {
+        let cell = |number| Ok(BoardMove::try_new(number).unwrap());
+
+        assert_eq!(TerminalUi::parse_move::<3>("5"), cell(5), "Cell number");
+        assert_eq!(TerminalUi::parse_move::<3>("b2"), cell(5), "Column and row");
+        assert_eq!(TerminalUi::parse_move::<3>("C1"), cell(3));
+        assert_eq!(TerminalUi::parse_move::<3>("3a"), cell(7), "Row and column");
+        assert_eq!(
+            TerminalUi::parse_move::<3>("2,3"),
+            cell(6),
+            "Row and column numbers"
+        );
+        assert_eq!(TerminalUi::parse_move::<3>("3 1"), cell(7));
+        assert_eq!(TerminalUi::parse_move::<3>(" 1 , 2 "), cell(2));
+
+        assert_eq!(
+            TerminalUi::parse_move::<3>("22"),
+            Err(InvalidMove::OutOfRange),
+            "Digits alone are always a cell number"
+        );
+        assert_eq!(
+            TerminalUi::parse_move::<3>("0"),
+            Err(InvalidMove::OutOfRange)
+        );
+        assert_eq!(
+            TerminalUi::parse_move::<3>("d1"),
+            Err(InvalidMove::OutOfRange)
+        );
+        assert_eq!(
+            TerminalUi::parse_move::<3>("a4"),
+            Err(InvalidMove::OutOfRange)
+        );
+        assert_eq!(
+            TerminalUi::parse_move::<3>("4,1"),
+            Err(InvalidMove::OutOfRange)
+        );
+        assert_eq!(TerminalUi::parse_move::<3>("b"), Err(InvalidMove::NotAMove));
+        assert_eq!(
+            TerminalUi::parse_move::<3>("bb"),
+            Err(InvalidMove::NotAMove)
+        );
+        assert_eq!(
+            TerminalUi::parse_move::<3>("b2c"),
+            Err(InvalidMove::NotAMove),
+            "Extra characters make the move ambiguous"
+        );
+        assert_eq!(
+            TerminalUi::parse_move::<3>("b,2"),
+            Err(InvalidMove::NotAMove)
+        );
+        assert_eq!(
+            TerminalUi::parse_move::<3>("1 2 3"),
+            Err(InvalidMove::NotAMove)
+        );
+        assert_eq!(TerminalUi::parse_move::<3>(""), Err(InvalidMove::NotAMove));
+
+        let cell = |number| Ok(BoardMove::try_new_for::<5>(number).unwrap());
+        assert_eq!(
+            TerminalUi::parse_move::<5>("25"),
+            cell(25),
+            "Larger board has more cells"
+        );
+        assert_eq!(TerminalUi::parse_move::<5>("e5"), cell(25));
+        assert_eq!(TerminalUi::parse_move::<5>("4,2"), cell(17));
+        assert_eq!(
+            TerminalUi::parse_move::<5>("26"),
+            Err(InvalidMove::OutOfRange)
+        );
+        assert_eq!(
+            TerminalUi::parse_move::<5>("f1"),
+            Err(InvalidMove::OutOfRange)
+        );
+        assert_eq!(
+            TerminalUi::format_cell_range(5),
+            "1 to 25, or a1 to e5",
+            "Hint should match the size of the board"
+        );
+        assert_eq!(TerminalUi::format_cell_range(3), "1 to 9, or a1 to c3");
+    }
+
+    #[test]
+    fn parse_main_menu_choice() {
+        assert_eq!(
+            TerminalUi::parse_main_menu_choice("", false),
+            None,
+            "Empty answer shouldn't start a match"
+        );
+        assert_eq!(
+            TerminalUi::parse_main_menu_choice("N", true),
+            Some(MainMenuChoice::NewMatch)
+        );
+        assert_eq!(
+            TerminalUi::parse_main_menu_choice("continue", true),
+            Some(MainMenuChoice::ContinueMatch)
+        );
+        assert_eq!(
+            TerminalUi::parse_main_menu_choice("c", false),
+            None,
+            "There's no match to continue"
+        );
+        assert_eq!(
+            TerminalUi::parse_main_menu_choice("s", false),
+            Some(MainMenuChoice::ViewStats)
+        );
+        assert_eq!(
+            TerminalUi::parse_main_menu_choice("quit", true),
+            Some(MainMenuChoice::Quit)
+        );
+        assert_eq!(TerminalUi::parse_main_menu_choice("x", true), None);
+    }
+
+    #[test]
+    fn parse_menu_choice() {
+        assert_eq!(
+            TerminalUi::parse_menu_choice(""),
+            Some(MenuChoice::Resume),
+            "Resuming should be the default"
+        );
+        assert_eq!(
+            TerminalUi::parse_menu_choice("S"),
+            Some(MenuChoice::Restart)
+        );
+        assert_eq!(
+            TerminalUi::parse_menu_choice("f"),
+            Some(MenuChoice::Forfeit)
+        );
+        assert_eq!(
+            TerminalUi::parse_menu_choice("quit"),
+            Some(MenuChoice::Quit)
+        );
+        assert_eq!(TerminalUi::parse_menu_choice("x"), None);
+    }
+
+    #[test]
+    fn retry_limit() {
+        let answers = |inputs: &[&str]| {
+            let mut inputs: Vec<String> = inputs.iter().rev().map(|s| s.to_string()).collect();
+            move || TerminalUi::parse_yes_no(&inputs.pop().unwrap())
+        };
+
+        let limited = TerminalUi::new().retry_limit(3);
+        let mut attempts = 0;
+        let mut next_answer = answers(&["maybe", "perhaps", "dunno", "y"]);
+        let answer = limited.retry(|| {
+            attempts += 1;
+            next_answer()
+        });
+
+        assert_eq!(
+            answer, None,
+            "Prompt should give up after 3 incorrect answers"
+        );
+        assert_eq!(attempts, 3, "Prompt shouldn't ask again after giving up");
+
+        let mut next_answer = answers(&["maybe", "perhaps", "y"]);
+        assert_eq!(
+            limited.retry(&mut next_answer),
+            Some(true),
+            "Correct answer within the limit should be accepted"
+        );
+
+        let unlimited = TerminalUi::new();
+        let mut next_answer = answers(&["maybe", "perhaps", "dunno", "nope", "n"]);
+        assert_eq!(
+            unlimited.retry(&mut next_answer),
+            Some(false),
+            "Without a limit prompt should ask until the answer is correct"
+        );
+    }
+
+    #[test]
+    fn input_from_reader() {
+        let ui = TerminalUi::new()
+            .input(Cursor::new("maybe\n  y \nSteve\n6\n"))
+            .retry_limit(2);
+
+        assert_eq!(
+            ui.prompt("", "", TerminalUi::parse_yes_no),
+            Some(true),
+            "Answers should be read line by line, trimmed"
+        );
+        assert_eq!(ui.get_player_name("Player 1"), "Steve");
+        assert_eq!(
+            ui.get_move("Steve"),
+            PlayerAction::Move(BoardMove::try_new(6).unwrap())
+        );
+        assert_eq!(
+            ui.prompt("", "", TerminalUi::parse_yes_no),
+            None,
+            "Prompt should give up at the end of input"
+        );
+        assert!(!ui.is_interactive());
+    }
+
+    #[test]
+    fn prompts_quit_at_end_of_input() {
+        // Without a retry limit, a prompt reading empty answers forever would never return
+        let ui = TerminalUi::new().input(io::empty());
+
+        assert_eq!(ui.main_menu(true), MainMenuChoice::Quit);
+        assert_eq!(ui.select_mode(), GameMode::Quit);
+        assert_eq!(ui.get_move("Steve"), PlayerAction::Quit);
+        assert_eq!(ui.open_menu(), Some(PlayerAction::Quit));
+        assert!(matches!(ui.keep_playing(), KeepPlaying::Stop));
+        assert_eq!(ui.get_player_name("Player 1"), "");
+    }
+
+    #[test]
+    fn prompt_until_parsed() {
+        let scripted = |inputs: &[&str]| {
+            let mut inputs: Vec<String> = inputs.iter().rev().map(|s| s.to_string()).collect();
+            move || inputs.pop()
+        };
+        let ui = TerminalUi::new().retry_limit(3);
+
+        assert_eq!(
+            ui.prompt_from("", "", TerminalUi::parse_yes_no, scripted(&["maybe", "y"])),
+            Some(true),
+            "Prompt should ask again until the answer can be parsed"
+        );
+        assert_eq!(
+            ui.prompt_from(
+                "",
+                "",
+                TerminalUi::parse_keep_playing,
+                scripted(&["x", "", "?", "y"])
+            )
+            .map(|answer| matches!(answer, KeepPlaying::Continue)),
+            None,
+            "Prompt should give up at the retry limit"
+        );
+
+        let mut rejected = vec![];
+        let answer = ui.prompt_from(
+            "",
+            "",
+            |input| match TerminalUi::parse_move_input::<3>(input) {
+                Ok(action) => Some(action),
+                Err(reason) => {
+                    rejected.push(reason);
+                    None
+                }
+            },
+            scripted(&["ten", "10", "5"]),
+        );
+        assert_eq!(
+            answer,
+            Some(PlayerAction::Move(BoardMove::try_new(5).unwrap()))
+        );
+        assert_eq!(
+            rejected,
+            [InvalidMove::NotAMove, InvalidMove::OutOfRange],
+            "Parser should see every rejected answer"
+        );
+    }
+
+    #[test]
+    fn styling_can_be_turned_off() {
+        let plain = TerminalUi::new().styling(false);
+        let styled = TerminalUi::new().styling(true);
+        let text = TerminalUi::format_text_by_player("Steve", &Cell::O);
+
+        assert_eq!(
+            plain.paint(text.clone()),
+            "Steve",
+            "Text shouldn't be styled without a terminal"
+        );
+        assert_ne!(
+            styled.paint(text),
+            "Steve",
+            "Text should be styled in a terminal"
+        );
+    }
+
+    #[test]
+    fn no_color_disables_styling() {
+        let supported = |no_color: Option<&str>, term: Option<&str>, is_terminal| {
+            TerminalUi::styling_supported_for(
+                no_color.map(OsStr::new),
+                term.map(OsStr::new),
+                is_terminal,
+            )
+        };
+
+        assert!(supported(None, Some("xterm-256color"), true));
+        assert!(
+            !supported(Some("1"), Some("xterm"), true),
+            "NO_COLOR should be honored"
+        );
+        assert!(
+            supported(Some(""), None, true),
+            "Empty NO_COLOR doesn't count as set"
+        );
+        assert!(
+            !supported(None, Some("dumb"), true),
+            "Dumb terminal can't style"
+        );
+        assert!(!supported(None, None, false), "Output isn't a terminal");
+
+        let text = TerminalUi::format_text_by_player("Steve", &Cell::X);
+        assert_ne!(
+            TerminalUi::new().styling(true).paint(text),
+            "Steve",
+            "Styling can still be turned on explicitly"
+        );
+    }
+
+    #[test]
+    fn render_plain_board() {
+        let board: Board = "O...X...#".parse().unwrap();
+        let rendered = TerminalUi::render_board(&board, &RenderOptions::default());
+
+        let expected = [
+            "",
+            "   +-----+-----+-----+",
+            "   |     |     |     |",
+            "   |  O  | [2] | [3] |",
+            "   |     |     |     |",
+            "   +-----+-----+-----+",
+            "   |     |     |     |",
+            "   | [4] |  X  | [6] |",
+            "   |     |     |     |",
+            "   +-----+-----+-----+",
+            "   |     |     |     |",
+            "   | [7] | [8] |  #  |",
+            "   |     |     |     |",
+            "   +-----+-----+-----+",
+            "",
+        ]
+        .join("\n");
+
+        assert_eq!(rendered, expected);
+    }
+
+    #[test]
+    fn render_annotations() {
+        let board: Board = "O...X....".parse().unwrap();
+        let options = RenderOptions {
+            annotations: vec![
+                CellAnnotation::new(0, "!", AnnotationStyle::Bad),
+                CellAnnotation::new(2, "+10", AnnotationStyle::Good),
+                CellAnnotation::new(7, "-100", AnnotationStyle::Note),
+            ],
+            ..Default::default()
+        };
+        let rendered = TerminalUi::render_board(&board, &options);
+        let lines: Vec<_> = rendered.lines().collect();
+
+        assert_eq!(
+            lines[3], "   |  !  | [2] | +10 |",
+            "Annotations should replace both marks and empty cells"
+        );
+        assert_eq!(lines[7], "   | [4] |  X  | [6] |");
+        assert_eq!(
+            lines[11], "   | [7] | -10 | [9] |",
+            "Long annotations should be cut to the cell's width"
+        );
+
+        let styled = TerminalUi::render_board(
+            &board,
+            &RenderOptions {
+                styled: true,
+                ..options
+            },
+        );
+        assert!(
+            styled.contains(&" ! ".red().bold().to_string()),
+            "Annotations should be styled"
+        );
+    }
+
+    #[test]
+    fn render_larger_board() {
+        let board: Board<4> = "O....X..........".parse().unwrap();
+        let options = RenderOptions {
+            coordinates: true,
+            ..Default::default()
+        };
+        let rendered = TerminalUi::render_board(&board, &options);
+        let lines: Vec<_> = rendered.lines().collect();
+
+        assert_eq!(lines[1], "      a     b     c     d");
+        assert_eq!(lines[2], "   +-----+-----+-----+-----+");
+        assert_eq!(lines[4], " 1 |  O  | [2] | [3] | [4] |");
+        assert_eq!(lines[8], " 2 | [5] |  X  | [7] | [8] |");
+        assert_eq!(
+            lines.len(),
+            3 + 4 * 4,
+            "Blank line, labels and top border, then four rows, 4 lines each"
+        );
+    }
+
+    #[test]
+    fn coordinate_labels() {
+        let (columns, rows) = TerminalUi::coordinate_labels(3);
+        assert_eq!(columns, "      a     b     c");
+        assert_eq!(rows, [" 1 ", " 2 ", " 3 "]);
+
+        let (columns, rows) = TerminalUi::coordinate_labels(4);
+        assert_eq!(columns, "      a     b     c     d");
+        assert_eq!(rows, [" 1 ", " 2 ", " 3 ", " 4 "]);
+
+        let options = RenderOptions {
+            coordinates: true,
+            ..Default::default()
+        };
+        let rendered = TerminalUi::render_board(&Board::new(), &options);
+        let lines: Vec<_> = rendered.lines().collect();
+
+        assert_eq!(lines[1], "      a     b     c");
+        assert_eq!(lines[4], " 1 | [1] | [2] | [3] |");
+    }
+
+    #[test]
+    fn ui_from_config() {
+        let config: Config = "styling = false\ncoordinates = true\nretry_limit = 2"
+            .parse()
+            .unwrap();
+        let ui = TerminalUi::with_config(&config);
+
+        assert!(!ui.styled);
+        assert!(ui.coordinates);
+        assert_eq!(ui.retry_limit, Some(2));
+        assert!(!ui.scroll, "Options missing from the config keep defaults");
+        assert!(ui.line_editor.is_none());
+    }
+
+    #[test]
+    fn hidden_labels() {
+        let board: Board = "O...X....".parse().unwrap();
+        let options = RenderOptions {
+            hide_labels: true,
+            ..Default::default()
+        };
+        let rendered = TerminalUi::render_board(&board, &options);
+        let lines: Vec<_> = rendered.lines().collect();
+
+        assert_eq!(lines[3], "   |  O  | [ ] | [ ] |");
+        assert_eq!(lines[7], "   | [ ] |  X  | [ ] |");
+        assert!(
+            !rendered.chars().any(|c| c.is_ascii_digit()),
+            "No cell numbers should be drawn"
+        );
+        assert_eq!(
+            TerminalUi::parse_move_input::<3>("6"),
+            Ok(PlayerAction::Move(BoardMove::try_new(6).unwrap())),
+            "Cell numbers should still be accepted as moves"
+        );
+    }
+
+    #[test]
+    fn render_styled_board() {
+        let board: Board = "OOOXX....".parse().unwrap();
+        let options = RenderOptions {
+            winning_lines: vec![vec![0, 1, 2]],
+            styled: true,
+            coordinates: false,
+            hide_labels: false,
+            spotlight_win: false,
+            annotations: vec![],
+        };
+        let rendered = TerminalUi::render_board(&board, &options);
+
+        assert!(
+            rendered.contains('\u{1b}'),
+            "Styled board should contain escape codes"
+        );
+        assert_ne!(
+            rendered,
+            TerminalUi::render_board(
+                &board,
+                &RenderOptions {
+                    winning_lines: vec![],
+                    styled: true,
+                    coordinates: false,
+                    hide_labels: false,
+                    spotlight_win: false,
+                    annotations: vec![],
+                }
+            ),
+            "Winning line should be highlighted"
+        );
+    }
+
+    #[test]
+    fn spotlight_win() {
+        let board: Board = "OOOXX....".parse().unwrap();
+        let options = RenderOptions {
+            winning_lines: vec![vec![0, 1, 2]],
+            styled: true,
+            spotlight_win: true,
+            ..Default::default()
+        };
+        let rendered = TerminalUi::render_board(&board, &options);
+        let lines: Vec<_> = rendered.lines().collect();
+        let dim = "\u{1b}[2m";
+        let reverse = "\u{1b}[7m";
+
+        assert!(
+            !lines[3].contains(dim) && !lines[3].contains(reverse),
+            "Winning line should keep the players' styling: {:?}",
+            lines[3]
+        );
+        assert_eq!(
+            lines[7].matches(dim).count(),
+            3,
+            "Every cell outside the winning line should be dimmed: {:?}",
+            lines[7]
+        );
+
+        let unfinished = TerminalUi::render_board(
+            &board,
+            &RenderOptions {
+                winning_lines: vec![],
+                ..options.clone()
+            },
+        );
+        assert!(
+            !unfinished.contains(dim),
+            "Nothing is dimmed before the game is won"
+        );
+
+        let plain = TerminalUi::render_board(
+            &board,
+            &RenderOptions {
+                styled: false,
+                ..options
+            },
+        );
+        assert!(!plain.contains('\u{1b}'), "Unstyled board stays plain");
+    }
+
+    #[test]
+    fn empty_at_creation() {
         let tui = TerminalUi::new();
 
         for cell in tui.board.borrow().iter() {
@@ -288,12 +2110,28 @@ mod tests {
         }
 
         assert_eq!(
-            *tui.winning_line.borrow(),
-            None,
+            *tui.winning_lines.borrow(),
+            Vec::<Vec<usize>>::new(),
             "There should be no winning line at creation"
         );
     }
 
+    #[test]
+    fn default_ui() {
+        let tui = TerminalUi::default();
+
+        assert_eq!(
+            *tui.board.borrow(),
+            Board::new(),
+            "Default UI should start with an empty board"
+        );
+        assert_eq!(
+            *tui.winning_lines.borrow(),
+            Vec::<Vec<usize>>::new(),
+            "Default UI should start with no winning line"
+        );
+    }
+
     #[test]
     fn update_board() {
         let tui = TerminalUi::new();
@@ -308,16 +2146,173 @@ mod tests {
         assert_eq!(*tui.board.borrow(), fake_board, "Board should be updated");
     }
 
+    #[test]
+    fn keep_playing_answers() {
+        assert!(matches!(
+            TerminalUi::parse_keep_playing("y"),
+            Some(KeepPlaying::Continue)
+        ));
+        assert!(matches!(
+            TerminalUi::parse_keep_playing("No"),
+            Some(KeepPlaying::Stop)
+        ));
+        assert!(matches!(
+            TerminalUi::parse_keep_playing("r"),
+            Some(KeepPlaying::Reset)
+        ));
+        assert!(
+            matches!(TerminalUi::parse_keep_playing("s"), Some(KeepPlaying::Swap)),
+            "'s' should start a rematch with sides swapped"
+        );
+        assert!(
+            TerminalUi::parse_keep_playing("maybe").is_none(),
+            "Unknown answer should be rejected"
+        );
+    }
+
+    #[test]
+    fn opponent_answers() {
+        assert_eq!(TerminalUi::parse_opponent("h"), Some(Opponent::Human));
+        assert_eq!(
+            TerminalUi::parse_opponent("CPU"),
+            Some(Opponent::Cpu { mistake_chance: 0 }),
+            "CPU should be perfect unless told otherwise"
+        );
+        assert_eq!(
+            TerminalUi::parse_opponent("c 30%"),
+            Some(Opponent::Cpu { mistake_chance: 30 })
+        );
+        assert_eq!(
+            TerminalUi::parse_opponent("c 101"),
+            None,
+            "Chance can't exceed 100%"
+        );
+        assert_eq!(TerminalUi::parse_opponent("h 30"), None);
+        assert_eq!(TerminalUi::parse_opponent("c 30 40"), None);
+        assert_eq!(TerminalUi::parse_opponent(""), None);
+    }
+
+    #[test]
+    fn custom_config_answers() {
+        let config = GameConfig {
+            player1: TerminalUi::parse_player_kind("h").unwrap(),
+            player2: TerminalUi::parse_player_kind("CPU").unwrap(),
+            move_delay: TerminalUi::parse_move_delay("0").unwrap(),
+            seed: TerminalUi::parse_seed("42").unwrap(),
+            auto_names: TerminalUi::parse_yes_no("y").unwrap(),
+        };
+
+        assert_eq!(
+            config,
+            GameConfig {
+                player1: PlayerKind::Human,
+                player2: PlayerKind::Cpu,
+                seed: Some(42),
+                move_delay: Duration::ZERO,
+                auto_names: true,
+            }
+        );
+
+        assert_eq!(
+            TerminalUi::parse_move_delay(""),
+            Some(DEFAULT_MOVE_DELAY),
+            "Empty answer should keep the default delay"
+        );
+        assert_eq!(
+            TerminalUi::parse_seed(""),
+            Some(None),
+            "Empty answer should keep CPU moves random"
+        );
+        assert!(TerminalUi::parse_player_kind("robot").is_none());
+        assert!(TerminalUi::parse_move_delay("soon").is_none());
+        assert!(TerminalUi::parse_seed("-1").is_none());
+    }
+
+    #[test]
+    fn score_bars() {
+        assert_eq!(
+            TerminalUi::format_scores("Steve", 3, "CPU", 2).join(" | "),
+            "Steve ███░░ 3 | 2 ░░░██ CPU"
+        );
+        assert_eq!(
+            TerminalUi::format_scores("Steve", 0, "CPU", 0).join(" | "),
+            "Steve ░░░░░ 0 | 0 ░░░░░ CPU",
+            "Bars should be empty before any game is won"
+        );
+        assert_eq!(
+            TerminalUi::format_scores("Steve", 100, "CPU", 50).join(" | "),
+            "Steve █████ 100 | 50 ░░░██ CPU",
+            "Bars should be scaled relative to the leader for large scores"
+        );
+        assert_eq!(
+            TerminalUi::format_scores("Bartholomew the Third", 1, "CPU", 0)[0],
+            "Bartholomew… █░░░░ 1",
+            "Long names should be truncated"
+        );
+    }
+
+    #[test]
+    fn legend() {
+        let tui = TerminalUi::new().styling(false);
+        assert_eq!(tui.format_legend("Steve", "CPU"), "Steve: O | CPU: X");
+
+        let tui = TerminalUi::new().styling(true);
+        assert_eq!(
+            tui.format_legend("Steve", "CPU"),
+            format!(
+                "{}: {} | {}: {}",
+                "Steve".bold().blue(),
+                "O".bold().blue(),
+                "CPU".bold().green(),
+                "X".bold().green()
+            ),
+            "Names and marks should be in the players' colours"
+        );
+    }
+
+    #[test]
+    fn status_line() {
+        let tui = TerminalUi::new().styling(false);
+        assert_eq!(
+            tui.format_status_line(4, 9, Some("Steve"), Cell::X),
+            "Turn 4 of 9 - Steve (X) to move"
+        );
+        assert_eq!(
+            tui.format_status_line(1, 6, None, Cell::O),
+            "Turn 1 of 6 - O to move",
+            "Mark alone should do when names are unknown"
+        );
+
+        let tui = TerminalUi::new().styling(true);
+        assert_eq!(
+            tui.format_status_line(1, 9, Some("Steve"), Cell::O),
+            format!("Turn 1 of 9 - {} to move", "Steve (O)".bold().blue()),
+            "Player should be in their colour"
+        );
+    }
+
+    #[test]
+    fn clocks() {
+        assert_eq!(TerminalUi::format_clock(Duration::from_secs(65)), "1:05");
+        assert_eq!(TerminalUi::format_clock(Duration::from_secs(600)), "10:00");
+        assert_eq!(
+            TerminalUi::format_clock(Duration::from_millis(7450)),
+            "0:07.4",
+            "Tenths should be shown when time is running out"
+        );
+        assert_eq!(TerminalUi::format_clock(Duration::ZERO), "0:00.0");
+    }
+
     #[test]
     fn draw() {
         let tui = TerminalUi::new();
-        let result = GameResult::Draw;
+        let result = GameResult::Draw(DrawReason::BoardFull);
 
         tui.notify_result(&result);
 
         assert_eq!(
-            *tui.winning_line.borrow(),
-            None,
+            *tui.winning_lines.borrow(),
+            Vec::<Vec<usize>>::new(),
             "There should be no winning line in draw"
         );
     }
@@ -325,21 +2320,31 @@ mod tests {
     #[test]
     fn player_won() {
         let tui = TerminalUi::new();
-        let result = GameResult::PlayerWon(0, String::from("Steve"), 3);
+        let result = GameResult::PlayerWon(0, String::from("Steve"), vec![3]);
 
         tui.notify_result(&result);
 
         assert_eq!(
-            *tui.winning_line.borrow(),
-            Some(WINNING_LINES[3]),
+            *tui.winning_lines.borrow(),
+            vec![WINNING_LINES[3].to_vec()],
             "Winning line should be stored"
         );
 
+        let result = GameResult::PlayerWon(0, String::from("Steve"), vec![3, 6]);
+
+        tui.notify_result(&result);
+
+        assert_eq!(
+            *tui.winning_lines.borrow(),
+            vec![WINNING_LINES[3].to_vec(), WINNING_LINES[6].to_vec()],
+            "Every completed line should be stored for highlighting"
+        );
+
         tui.update_board(&Board::new()); // New game starts
 
         assert_eq!(
-            *tui.winning_line.borrow(),
-            None,
+            *tui.winning_lines.borrow(),
+            Vec::<Vec<usize>>::new(),
             "Winning line should be deleted as soon as new game starts"
         );
     }